@@ -0,0 +1,252 @@
+//! Archive formats written on the fly, entry by entry, without knowing the whole content up front.
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+
+use flate2::{write::GzEncoder, Compression};
+use zip::write::{SimpleFileOptions, StreamWriter};
+use zip::{CompressionMethod, DateTime};
+
+use crate::error::AppError;
+
+const BLOCK: usize = 512;
+/// Entries from this size on need Zip64 fields.
+const ZIP64_LIMIT: u64 = 0xFFFF_FFFF;
+
+fn archive_error(err: impl std::fmt::Display) -> AppError {
+    AppError::InternalError(format!("Failed to write the archive: {}", err))
+}
+
+/// Header blocks of a regular file entry of `size` bytes, its content follows padded with `tar_padding`.
+///
+/// Empty, `.` and `..` segments are dropped from the path, paths too long for the header get a GNU long name entry.
+pub fn tar_header(path: &str, size: u64, mtime: i64) -> Result<Vec<u8>, AppError> {
+    let path: Vec<&str> = path.split('/').filter(|s| !s.is_empty() && *s != "." && *s != "..").collect();
+    let mut header = tar::Header::new_gnu();
+    header.set_entry_type(tar::EntryType::Regular);
+    header.set_size(size);
+    header.set_mtime(mtime.max(0) as u64);
+    header.set_mode(0o644);
+
+    let mut builder = tar::Builder::new(Vec::new());
+    builder.append_data(&mut header, path.join("/"), io::empty()).map_err(archive_error)?;
+    // taken before the builder closes the archive when dropped
+    Ok(std::mem::take(builder.get_mut()))
+}
+
+/// Zeros completing an entry of `size` bytes to a whole block.
+pub fn tar_padding(size: u64) -> usize {
+    (BLOCK - (size % BLOCK as u64) as usize) % BLOCK
+}
+
+/// Two empty blocks close the archive.
+pub fn tar_end() -> Vec<u8> {
+    vec![0; 2 * BLOCK]
+}
+
+/// Bytes written by an encoder, taken out as they come.
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Output {
+    fn take(&self) -> Vec<u8> {
+        std::mem::take(&mut *self.0.lock().expect("archive output lock"))
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().expect("archive output lock").extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Zip archive of deflated entries, sent as it is written: the sizes and CRC of each entry follow its content
+/// in a data descriptor, and `finish` adds the central directory.
+pub struct ZipWriter {
+    zip: zip::ZipWriter<StreamWriter<Output>>,
+    output: Output,
+}
+
+impl Default for ZipWriter {
+    fn default() -> Self {
+        let output = Output::default();
+        Self { zip: zip::ZipWriter::new_stream(output.clone()), output }
+    }
+}
+
+impl ZipWriter {
+    /// Closes the previous entry and starts the next one, `size` decides whether it needs Zip64 fields.
+    pub fn start_entry(&mut self, path: &str, size: u64, mtime: i64) -> Result<Vec<u8>, AppError> {
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .last_modified_time(zip_time(mtime))
+            .unix_permissions(0o644)
+            .large_file(size >= ZIP64_LIMIT);
+        self.zip.start_file(path, options).map_err(archive_error)?;
+        Ok(self.output.take())
+    }
+
+    /// Compressed content of the current entry, as much of it as is ready.
+    pub fn write(&mut self, data: &[u8]) -> Result<Vec<u8>, AppError> {
+        self.zip.write_all(data).map_err(archive_error)?;
+        Ok(self.output.take())
+    }
+
+    /// End of the last entry, then the central directory and end records.
+    pub fn finish(self) -> Result<Vec<u8>, AppError> {
+        self.zip.finish().map_err(archive_error)?;
+        Ok(self.output.take())
+    }
+}
+
+/// Gzip stream of everything written to it.
+pub struct GzipWriter(GzEncoder<Vec<u8>>);
+
+impl Default for GzipWriter {
+    fn default() -> Self {
+        Self(GzEncoder::new(Vec::new(), Compression::default()))
+    }
+}
+
+impl GzipWriter {
+    /// Compressed output ready so far, the encoder holds on to the rest until it fills a block.
+    pub fn write(&mut self, data: &[u8]) -> Result<Vec<u8>, AppError> {
+        self.0.write_all(data).map_err(archive_error)?;
+        Ok(std::mem::take(self.0.get_mut()))
+    }
+
+    /// Rest of the compressed stream, then the CRC and size trailer.
+    pub fn finish(self) -> Result<Vec<u8>, AppError> {
+        self.0.finish().map_err(archive_error)
+    }
+}
+
+/// MS-DOS time of the zip headers, which cannot go before 1980.
+fn zip_time(mtime: i64) -> DateTime {
+    use chrono::{Datelike, Timelike};
+
+    chrono::DateTime::from_timestamp(mtime, 0)
+        .and_then(|t| {
+            DateTime::from_date_and_time(
+                u16::try_from(t.year()).ok()?,
+                t.month() as u8,
+                t.day() as u8,
+                t.hour() as u8,
+                t.minute() as u8,
+                t.second() as u8,
+            )
+            .ok()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Cursor, Read};
+
+    fn tar(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for (name, data) in files {
+            out.extend(tar_header(name, data.len() as u64, 1_700_000_000).unwrap());
+            out.extend_from_slice(data);
+            out.resize(out.len() + tar_padding(data.len() as u64), 0);
+        }
+        out.extend(tar_end());
+        out
+    }
+
+    fn read_tar(data: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut archive = tar::Archive::new(data);
+        archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let mut content = Vec::new();
+                entry.read_to_end(&mut content).unwrap();
+                (entry.path().unwrap().to_string_lossy().into_owned(), content)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn tar_header_makes_readable_entries() {
+        let long = format!("objects/{}/résumé.txt", "a".repeat(120));
+        let archive = tar(&[("objects/report.pdf", b"hello"), (&long, b""), ("docs/../x/./y.txt", b"y")]);
+
+        assert_eq!(
+            read_tar(&archive),
+            vec![
+                ("objects/report.pdf".to_string(), b"hello".to_vec()),
+                (long, Vec::new()),
+                ("docs/x/y.txt".to_string(), b"y".to_vec()),
+            ]
+        );
+        let header = tar_header("a.txt", 5, 1_700_000_000).unwrap();
+        assert_eq!(header.len(), BLOCK);
+        assert_eq!(tar::Header::from_byte_slice(&header).mtime().unwrap(), 1_700_000_000);
+    }
+
+    #[test]
+    fn tar_padding_completes_blocks() {
+        assert_eq!(tar_padding(0), 0);
+        assert_eq!(tar_padding(1), 511);
+        assert_eq!(tar_padding(512), 0);
+        assert_eq!(tar_padding(1000), 24);
+    }
+
+    #[test]
+    fn zip_writer_streams_a_readable_archive() {
+        let text = "some words repeat, ".repeat(200);
+        let mut zip = ZipWriter::default();
+        let mut archive = zip.start_entry("a.txt", text.len() as u64, 1_700_000_000).unwrap();
+        for chunk in text.as_bytes().chunks(100) {
+            archive.extend(zip.write(chunk).unwrap());
+        }
+        archive.extend(zip.start_entry("docs/b.txt", 0, 0).unwrap());
+        archive.extend(zip.finish().unwrap());
+        assert!(archive.len() < text.len());
+
+        let mut reader = zip::ZipArchive::new(Cursor::new(archive)).unwrap();
+        assert_eq!(reader.len(), 2);
+        let mut first = reader.by_index(0).unwrap();
+        assert_eq!(first.name().unwrap(), "a.txt");
+        assert_eq!(first.compression(), CompressionMethod::Deflated);
+        let mut content = String::new();
+        first.read_to_string(&mut content).unwrap();
+        assert_eq!(content, text);
+        drop(first);
+        assert_eq!(reader.by_index(1).unwrap().name().unwrap(), "docs/b.txt");
+    }
+
+    #[test]
+    fn zip_time_clamps_to_1980() {
+        // 2026-10-18 12:34:56 UTC
+        let time = zip_time(1_792_326_896);
+        assert_eq!((time.year(), time.month(), time.day()), (2026, 10, 18));
+        assert_eq!((time.hour(), time.minute(), time.second()), (12, 34, 56));
+        assert_eq!(zip_time(0), DateTime::default());
+    }
+
+    #[test]
+    fn gzip_writer_compresses() {
+        let text = "line of the sample text, some words repeat: rose rose rose\n".repeat(1000);
+        let mut gzip = GzipWriter::default();
+        let mut data = Vec::new();
+        for chunk in text.as_bytes().chunks(1000) {
+            data.extend(gzip.write(chunk).unwrap());
+        }
+        data.extend(gzip.finish().unwrap());
+        assert!(data.len() < text.len() / 10);
+
+        let mut content = String::new();
+        flate2::read::GzDecoder::new(&data[..]).read_to_string(&mut content).unwrap();
+        assert_eq!(content, text);
+    }
+}
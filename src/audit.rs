@@ -0,0 +1,279 @@
+//! Audit trail of object operations: handlers describe the object they touched in an
+//! `AuditRecord` response extension, the `record` middleware adds who asked, from where,
+//! and the outcome, then appends the event to `audit_events`. Requests refused before a
+//! handler could describe them still get the caller and the key named by the route.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts, RawPathParams, Request, State},
+    http::{HeaderMap, HeaderName, HeaderValue, Method},
+    middleware::Next,
+    response::Response,
+};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use uuid::Uuid;
+
+use crate::auth::access;
+use crate::entities::{audit_event, file};
+use crate::objects::Namespace;
+use crate::AppState;
+
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+const MAX_REQUEST_ID_LEN: usize = 128;
+const MAX_USER_AGENT_LEN: usize = 512;
+const RETENTION_SWEEP_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Object side of an audit event, set by the handler that served the request.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuditRecord {
+    pub actor_id: Option<Uuid>,
+    pub owner_id: Option<Uuid>,
+    pub bucket_id: Option<Uuid>,
+    pub file_path: Option<String>,
+    pub version_id: Option<String>,
+    pub bytes: Option<i64>,
+}
+
+impl AuditRecord {
+    pub fn new(ns: &Namespace, file: &file::Model) -> Self {
+        Self {
+            actor_id: Some(ns.actor_id),
+            owner_id: Some(ns.owner_id),
+            bucket_id: file.bucket_id,
+            file_path: Some(file.file_path.clone()),
+            version_id: Some(file.s3_version_id.clone()),
+            bytes: Some(file.content_size),
+        }
+    }
+
+    /// Operation through a public bucket or link, done on the owner's behalf by someone unknown.
+    pub fn anonymous(self) -> Self {
+        Self { actor_id: None, ..self }
+    }
+
+    pub fn attach(self, mut response: Response) -> Response {
+        response.extensions_mut().insert(self);
+        response
+    }
+
+    /// Fields left empty by the handler are taken from `fallback`.
+    fn or(self, fallback: AuditRecord) -> Self {
+        Self {
+            actor_id: self.actor_id.or(fallback.actor_id),
+            owner_id: self.owner_id.or(fallback.owner_id),
+            bucket_id: self.bucket_id.or(fallback.bucket_id),
+            file_path: self.file_path.or(fallback.file_path),
+            version_id: self.version_id.or(fallback.version_id),
+            bytes: self.bytes.or(fallback.bytes),
+        }
+    }
+}
+
+/// What the route tells of a request: the key (or prefix) it names, and whose namespace it targets. Without
+/// `x-owner-id`, routes outside of a bucket or link act on the caller's own namespace.
+fn route_record(params: &[(String, String)], headers: &HeaderMap, actor_id: Option<Uuid>) -> AuditRecord {
+    let param = |name: &str| params.iter().find(|(k, _)| k == name).map(|(_, v)| v.clone());
+    let owner_from_header = headers
+        .get(access::OWNER_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| Uuid::parse_str(v).ok());
+    let foreign_route = param("bucket").is_some() || param("token").is_some();
+    AuditRecord {
+        actor_id,
+        owner_id: owner_from_header.or(actor_id.filter(|_| !foreign_route)),
+        file_path: param("key").or_else(|| param("prefix")),
+        ..Default::default()
+    }
+}
+
+fn action(method: &Method) -> &'static str {
+    match *method {
+        Method::GET => "get",
+        Method::HEAD => "head",
+        Method::PUT => "put",
+        Method::DELETE => "delete",
+        Method::POST => "post",
+        _ => "other",
+    }
+}
+
+/// Id sent by the client (or a proxy in front) when usable, a new one otherwise.
+fn request_id(headers: &HeaderMap) -> String {
+    headers
+        .get(&REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty() && v.len() <= MAX_REQUEST_ID_LEN)
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::now_v7().to_string())
+}
+
+/// First `x-forwarded-for` hop when that header comes from a trusted proxy, the peer address otherwise.
+fn client_ip(headers: &HeaderMap, peer: Option<SocketAddr>, trust_forwarded_for: bool) -> Option<String> {
+    headers
+        .get("x-forwarded-for")
+        .filter(|_| trust_forwarded_for)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.split(',').next())
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .or_else(|| peer.map(|addr| addr.ip().to_string()))
+}
+
+fn user_agent(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(axum::http::header::USER_AGENT)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.chars().take(MAX_USER_AGENT_LEN).collect())
+}
+
+/// Middleware recording every request it wraps, whatever its outcome.
+pub async fn record(State(state): State<AppState>, request: Request, next: Next) -> Response {
+    let headers = request.headers();
+    let request_id = request_id(headers);
+    let peer = request.extensions().get::<ConnectInfo<SocketAddr>>().map(|c| c.0);
+    let client_ip = client_ip(headers, peer, state.config.trust_forwarded_for);
+    let user_agent = user_agent(headers);
+    let action = action(request.method());
+    let path = request.uri().path().to_string();
+    let headers = headers.clone();
+
+    let (mut parts, body) = request.into_parts();
+    let params: Vec<(String, String)> = match RawPathParams::from_request_parts(&mut parts, &state).await {
+        Ok(params) => params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+        Err(_) => Vec::new(),
+    };
+
+    let mut response = next.run(Request::from_parts(parts, body)).await;
+
+    let record = match response.extensions_mut().remove::<AuditRecord>() {
+        // possibly partial, e.g. only the caller when the S3 authentication passed but the handler refused
+        Some(record) => {
+            let actor_id = record.actor_id;
+            record.or(route_record(&params, &headers, actor_id))
+        }
+        None => {
+            // the handler turned the request down, or never ran: who it was is resolved again from the credentials
+            let actor_id = state.auth.authenticate(&headers, &state.db).await.ok().map(|auth| auth.user_id);
+            route_record(&params, &headers, actor_id)
+        }
+    };
+    let event = audit_event::ActiveModel {
+        id: Set(Uuid::now_v7()),
+        occurred_at: Set(chrono::Utc::now().into()),
+        request_id: Set(request_id.clone()),
+        action: Set(action.to_string()),
+        path: Set(path),
+        actor_id: Set(record.actor_id),
+        owner_id: Set(record.owner_id),
+        bucket_id: Set(record.bucket_id),
+        file_path: Set(record.file_path),
+        version_id: Set(record.version_id),
+        bytes: Set(record.bytes),
+        client_ip: Set(client_ip),
+        user_agent: Set(user_agent),
+        status: Set(response.status().as_u16().into()),
+    };
+    // the operation already happened, a failed write is logged rather than hiding its result
+    if let Err(err) = event.insert(&state.db).await {
+        tracing::error!("Failed to record audit event of request {}: {:?}", request_id, err);
+    }
+
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Purges events older than `AUDIT_RETENTION_DAYS`, every hour.
+pub fn spawn_retention(state: AppState) {
+    let Some(days) = state.config.audit_retention_days else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(RETENTION_SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            let cutoff = chrono::Utc::now() - chrono::Duration::days(days);
+            match audit_event::Entity::delete_many()
+                .filter(audit_event::Column::OccurredAt.lt(cutoff))
+                .exec(&state.db)
+                .await
+            {
+                Ok(res) if res.rows_affected > 0 => {
+                    tracing::info!("Purged {} audit events older than {} days", res.rows_affected, days)
+                }
+                Ok(_) => {}
+                Err(err) => tracing::error!("Failed to purge audit events: {:?}", err),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request_id_reuses_client_value() {
+        let mut headers = HeaderMap::new();
+        assert!(Uuid::parse_str(&request_id(&headers)).is_ok());
+
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_static("req-42"));
+        assert_eq!(request_id(&headers), "req-42");
+
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_str(&"x".repeat(MAX_REQUEST_ID_LEN + 1)).unwrap());
+        assert_ne!(request_id(&headers), "x".repeat(MAX_REQUEST_ID_LEN + 1));
+    }
+
+    #[test]
+    fn client_ip_prefers_trusted_forwarded_for() {
+        let peer: SocketAddr = "10.0.0.2:4321".parse().unwrap();
+        let mut headers = HeaderMap::new();
+        assert_eq!(client_ip(&headers, Some(peer), true), Some("10.0.0.2".to_string()));
+        assert_eq!(client_ip(&headers, None, true), None);
+
+        headers.insert("x-forwarded-for", HeaderValue::from_static("203.0.113.7, 10.0.0.1"));
+        assert_eq!(client_ip(&headers, Some(peer), true), Some("203.0.113.7".to_string()));
+        assert_eq!(client_ip(&headers, Some(peer), false), Some("10.0.0.2".to_string()));
+    }
+
+    #[test]
+    fn route_record_names_the_caller_and_key() {
+        let (actor, owner) = (Uuid::now_v7(), Uuid::now_v7());
+        let key = vec![("key".to_string(), "docs/a.txt".to_string())];
+        let mut headers = HeaderMap::new();
+
+        let record = route_record(&key, &headers, Some(actor));
+        assert_eq!((record.actor_id, record.owner_id), (Some(actor), Some(actor)));
+        assert_eq!(record.file_path.as_deref(), Some("docs/a.txt"));
+
+        headers.insert(access::OWNER_HEADER, HeaderValue::from_str(&owner.to_string()).unwrap());
+        assert_eq!(route_record(&key, &headers, Some(actor)).owner_id, Some(owner));
+
+        let bucket = vec![("bucket".to_string(), "photos".to_string()), ("key".to_string(), "a.jpg".to_string())];
+        assert_eq!(route_record(&bucket, &HeaderMap::new(), Some(actor)).owner_id, None);
+        assert_eq!(route_record(&[], &HeaderMap::new(), None), AuditRecord::default());
+    }
+
+    #[test]
+    fn handler_record_overrides_the_route() {
+        let actor = Uuid::now_v7();
+        let handler = AuditRecord { actor_id: Some(actor), file_path: Some("docs/".to_string()), ..Default::default() };
+        let route = AuditRecord { owner_id: Some(actor), file_path: Some("docs".to_string()), ..Default::default() };
+
+        let record = handler.or(route);
+        assert_eq!((record.actor_id, record.owner_id), (Some(actor), Some(actor)));
+        assert_eq!(record.file_path.as_deref(), Some("docs/"));
+    }
+
+    #[test]
+    fn action_follows_method() {
+        assert_eq!(action(&Method::GET), "get");
+        assert_eq!(action(&Method::DELETE), "delete");
+        assert_eq!(action(&Method::PATCH), "other");
+    }
+}
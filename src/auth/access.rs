@@ -0,0 +1,221 @@
+use axum::http::HeaderMap;
+use sea_orm::{ColumnTrait, Condition, ConnectionTrait, EntityTrait, QueryFilter};
+use uuid::Uuid;
+
+use super::{grant, org, AuthUser, Scope};
+use crate::entities::grant::{self as grant_entity, Model as Grant};
+use crate::entities::{bucket, file};
+use crate::error::AppError;
+use crate::objects::Namespace;
+use crate::users;
+
+/// Header selecting whose objects a request works on (another user or an organization), the caller's own when absent.
+pub const OWNER_HEADER: &str = "x-owner-id";
+
+pub fn owner_from_headers(headers: &HeaderMap) -> Result<Option<Uuid>, AppError> {
+    headers
+        .get(OWNER_HEADER)
+        .map(|v| {
+            v.to_str()
+                .ok()
+                .and_then(|v| Uuid::parse_str(v).ok())
+                .ok_or_else(|| AppError::BadRequest(format!("Invalid {} header", OWNER_HEADER)))
+        })
+        .transpose()
+}
+
+fn no_access(scope: Scope, key: &str, owner_id: Uuid) -> AppError {
+    AppError::Forbidden(format!(
+        "No '{}' access to key '{}' of {}",
+        scope.as_str(),
+        key,
+        owner_id
+    ))
+}
+
+fn granted(grants: &[Grant], bucket_id: Option<Uuid>, scope: Scope, key: &str) -> bool {
+    grants.iter().any(|g| {
+        g.bucket_id == bucket_id
+            && grant::covers(&g.prefix, key)
+            && grant::Permission::parse(&g.permission).is_some_and(|p| p.allows(scope))
+    })
+}
+
+/// Checks that the caller may do `scope` on `key` of `owner_id` (their own objects when `None`),
+/// as a member of the organization owning them or through a grant.
+pub async fn authorize<C: ConnectionTrait>(
+    db: &C,
+    auth: &AuthUser,
+    owner_id: Option<Uuid>,
+    scope: Scope,
+    key: &str,
+) -> Result<Namespace, AppError> {
+    authorize_in(db, auth, owner_id, None, scope, key).await
+}
+
+/// Same as `authorize` on `key` in a bucket of the owner, the default one when `bucket_id` is `None`.
+/// Grants only cover the bucket they were given in.
+pub async fn authorize_in<C: ConnectionTrait>(
+    db: &C,
+    auth: &AuthUser,
+    owner_id: Option<Uuid>,
+    bucket_id: Option<Uuid>,
+    scope: Scope,
+    key: &str,
+) -> Result<Namespace, AppError> {
+    auth.require(scope, key)?;
+
+    let owner_id = match owner_id {
+        Some(owner_id) if owner_id != auth.user_id => owner_id,
+        _ => return Ok(Namespace::own(auth.user_id)),
+    };
+    let ns = Namespace::acting(owner_id, auth.user_id);
+    // the objects of a disabled owner are out of reach, through a grant or a membership alike
+    users::check_enabled(db, owner_id).await?;
+
+    if let Some(role) = org::member_role(db, owner_id, auth.user_id).await? {
+        return if role.allows(scope) {
+            Ok(ns)
+        } else {
+            Err(no_access(scope, key, owner_id))
+        };
+    }
+
+    let grants = grant::grants_from(db, owner_id, auth.user_id, bucket_id).await?;
+    if !granted(&grants, bucket_id, scope, key) {
+        return Err(no_access(scope, key, owner_id));
+    }
+    Ok(ns)
+}
+
+/// Filter on the files of `owner_id` in the bucket (the default one when `None`) listed to the caller,
+/// `None` when they can see all of them.
+pub async fn visible_files<C: ConnectionTrait>(
+    db: &C,
+    auth: &AuthUser,
+    owner_id: Uuid,
+    bucket_id: Option<Uuid>,
+) -> Result<Option<Condition>, AppError> {
+    visible_keys(db, auth, owner_id, bucket_id, file::Column::FilePath).await
+}
+
+/// Same as `visible_files` on the keys held in `column`.
+pub async fn visible_keys<C: ConnectionTrait, K: ColumnTrait>(
+    db: &C,
+    auth: &AuthUser,
+    owner_id: Uuid,
+    bucket_id: Option<Uuid>,
+    column: K,
+) -> Result<Option<Condition>, AppError> {
+    let grants = visible_grants(db, auth, owner_id, bucket_id).await?;
+    Ok(grants.map(|grants| grant::keys_condition(&grants, column)))
+}
+
+/// Grants through which the caller sees the objects of `owner_id` in the bucket, `None` when they can see
+/// all of them.
+pub async fn visible_grants<C: ConnectionTrait>(
+    db: &C,
+    auth: &AuthUser,
+    owner_id: Uuid,
+    bucket_id: Option<Uuid>,
+) -> Result<Option<Vec<Grant>>, AppError> {
+    if sees_everything(db, auth, owner_id).await? {
+        return Ok(None);
+    }
+
+    let grants = grant::grants_from(db, owner_id, auth.user_id, bucket_id).await?;
+    if grants.is_empty() {
+        return Err(AppError::Forbidden(format!("Nothing is shared by {}", owner_id)));
+    }
+    Ok(Some(grants))
+}
+
+/// Filter on the buckets of `owner_id` listed to the caller, the ones something is granted in,
+/// `None` when they can see all of them.
+pub async fn visible_buckets<C: ConnectionTrait>(
+    db: &C,
+    auth: &AuthUser,
+    owner_id: Uuid,
+) -> Result<Option<Condition>, AppError> {
+    if sees_everything(db, auth, owner_id).await? {
+        return Ok(None);
+    }
+
+    let grants = grant_entity::Entity::find()
+        .filter(grant_entity::Column::OwnerId.eq(owner_id))
+        .filter(grant_entity::Column::GranteeId.eq(auth.user_id))
+        .all(db)
+        .await?;
+    if grants.is_empty() {
+        return Err(AppError::Forbidden(format!("Nothing is shared by {}", owner_id)));
+    }
+    let bucket_ids: Vec<Uuid> = grants.into_iter().filter_map(|g| g.bucket_id).collect();
+    Ok(Some(Condition::all().add(bucket::Column::Id.is_in(bucket_ids))))
+}
+
+/// Owners see all of their objects, and members all the objects of their organization.
+/// Refuses the objects of a disabled owner to everyone else.
+async fn sees_everything<C: ConnectionTrait>(db: &C, auth: &AuthUser, owner_id: Uuid) -> Result<bool, AppError> {
+    if owner_id == auth.user_id {
+        return Ok(true);
+    }
+    users::check_enabled(db, owner_id).await?;
+    Ok(org::member_role(db, owner_id, auth.user_id).await?.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn grant(prefix: &str, permission: &str) -> Grant {
+        grant_in(None, prefix, permission)
+    }
+
+    fn grant_in(bucket_id: Option<Uuid>, prefix: &str, permission: &str) -> Grant {
+        Grant {
+            id: Uuid::now_v7(),
+            owner_id: Uuid::now_v7(),
+            grantee_id: Uuid::now_v7(),
+            bucket_id,
+            prefix: prefix.to_string(),
+            permission: permission.to_string(),
+            created_at: chrono::Utc::now().into(),
+        }
+    }
+
+    #[test]
+    fn granted_needs_matching_prefix_and_permission() {
+        let grants = vec![grant("team/", "read"), grant("team/shared/", "read_write")];
+
+        assert!(granted(&grants, None, Scope::Read, "team/notes.txt"));
+        assert!(!granted(&grants, None, Scope::Write, "team/notes.txt"));
+        assert!(granted(&grants, None, Scope::Write, "team/shared/notes.txt"));
+        assert!(!granted(&grants, None, Scope::Read, "private/notes.txt"));
+    }
+
+    #[test]
+    fn granted_only_in_the_bucket_of_the_grant() {
+        let bucket_id = Uuid::now_v7();
+        let grants = vec![grant("docs/", "read"), grant_in(Some(bucket_id), "images/", "read")];
+
+        assert!(granted(&grants, None, Scope::Read, "docs/a.txt"));
+        assert!(!granted(&grants, Some(bucket_id), Scope::Read, "docs/a.txt"));
+        assert!(!granted(&grants, Some(Uuid::now_v7()), Scope::Read, "docs/a.txt"));
+        assert!(granted(&grants, Some(bucket_id), Scope::Read, "images/a.png"));
+        assert!(!granted(&grants, None, Scope::Read, "images/a.png"));
+    }
+
+    #[test]
+    fn owner_from_headers_parses_uuid() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(owner_from_headers(&headers).unwrap(), None);
+
+        let owner = Uuid::now_v7();
+        headers.insert(OWNER_HEADER, HeaderValue::from_str(&owner.to_string()).unwrap());
+        assert_eq!(owner_from_headers(&headers).unwrap(), Some(owner));
+
+        headers.insert(OWNER_HEADER, HeaderValue::from_static("nope"));
+        assert!(matches!(owner_from_headers(&headers), Err(AppError::BadRequest(_))));
+    }
+}
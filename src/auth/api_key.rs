@@ -0,0 +1,94 @@
+use rand::RngCore;
+use sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use sha2::{Digest, Sha256};
+
+use super::scope::parse_scopes;
+use super::AuthUser;
+use crate::entities::api_key;
+use crate::error::AppError;
+
+pub const TOKEN_PREFIX: &str = "rose_";
+
+// avoid a write on every single request of a busy script
+const LAST_USED_RESOLUTION_SECS: i64 = 60;
+
+/// New random key, only ever shown once to its owner.
+pub fn generate_token() -> String {
+    let mut secret = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut secret);
+    format!("{}{}", TOKEN_PREFIX, hex::encode(secret))
+}
+
+/// Keys are random 256-bit secrets, a plain SHA-256 is enough to store them.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+pub fn is_api_key(token: &str) -> bool {
+    token.starts_with(TOKEN_PREFIX)
+}
+
+pub async fn authenticate(db: &DatabaseConnection, token: &str) -> Result<AuthUser, AppError> {
+    let key = api_key::Entity::find()
+        .filter(api_key::Column::KeyHash.eq(hash_token(token)))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::Unauthorized("Invalid API key".to_string()))?;
+
+    let now = chrono::Utc::now();
+    if key.revoked_at.is_some() {
+        return Err(AppError::Unauthorized("API key revoked".to_string()));
+    }
+    if key.expires_at.is_some_and(|exp| exp <= now) {
+        return Err(AppError::Unauthorized("API key expired".to_string()));
+    }
+
+    let scopes = parse_scopes(&key.scopes)?;
+    let stale = key
+        .last_used_at
+        .is_none_or(|used| (now - used.to_utc()).num_seconds() >= LAST_USED_RESOLUTION_SECS);
+
+    let auth = AuthUser {
+        user_id: key.user_id,
+        scopes,
+        path_prefix: key.path_prefix.clone(),
+    };
+
+    if stale {
+        let mut active: api_key::ActiveModel = key.into();
+        active.last_used_at = Set(Some(now.into()));
+        active.update(db).await?;
+    }
+
+    Ok(auth)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_token_is_prefixed_and_unique() {
+        let a = generate_token();
+        let b = generate_token();
+
+        assert!(is_api_key(&a));
+        assert_eq!(a.len(), TOKEN_PREFIX.len() + 64);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn hash_token_is_stable_sha256() {
+        assert_eq!(
+            hash_token("rose_abc"),
+            hash_token("rose_abc")
+        );
+        assert_ne!(hash_token("rose_abc"), hash_token("rose_abd"));
+        assert_eq!(hash_token("rose_abc").len(), 64);
+    }
+
+    #[test]
+    fn jwt_is_not_an_api_key() {
+        assert!(!is_api_key("eyJhbGciOiJIUzI1NiJ9.e30.sig"));
+    }
+}
@@ -0,0 +1,156 @@
+use sea_orm::{ColumnTrait, Condition, ConnectionTrait, EntityTrait, QueryFilter};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::Scope;
+use crate::entities::grant;
+use crate::error::AppError;
+use crate::objects;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Permission {
+    Read,
+    ReadWrite,
+}
+
+impl Permission {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Permission::Read => "read",
+            Permission::ReadWrite => "read_write",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Permission> {
+        [Permission::Read, Permission::ReadWrite]
+            .into_iter()
+            .find(|p| p.as_str() == value)
+    }
+
+    /// Permission needed to hand out `self` on someone else's behalf.
+    pub fn required_scope(&self) -> Scope {
+        match self {
+            Permission::Read => Scope::Read,
+            Permission::ReadWrite => Scope::Write,
+        }
+    }
+
+    /// Admin is never granted, it only applies to one's own objects.
+    pub fn allows(&self, scope: Scope) -> bool {
+        match scope {
+            Scope::Read => true,
+            Scope::Write | Scope::Delete => *self == Permission::ReadWrite,
+            Scope::Admin => false,
+        }
+    }
+}
+
+/// An empty prefix or one ending with `/` covers every key under it, anything else a single key.
+pub fn covers(prefix: &str, key: &str) -> bool {
+    if prefix.is_empty() || prefix.ends_with('/') {
+        key.starts_with(prefix)
+    } else {
+        key == prefix
+    }
+}
+
+/// Rows of the owner whose key in `column` is visible through `grants`.
+pub fn keys_condition<C: ColumnTrait>(grants: &[grant::Model], column: C) -> Condition {
+    grants.iter().fold(Condition::any(), |cond, grant| {
+        if grant.prefix.is_empty() || grant.prefix.ends_with('/') {
+            cond.add(objects::starts_with(column, &grant.prefix))
+        } else {
+            cond.add(column.eq(grant.prefix.as_str()))
+        }
+    })
+}
+
+/// Grants given in the bucket, the default one when `None`.
+pub fn bucket_condition(bucket_id: Option<Uuid>) -> Condition {
+    match bucket_id {
+        Some(id) => Condition::all().add(grant::Column::BucketId.eq(id)),
+        None => Condition::all().add(grant::Column::BucketId.is_null()),
+    }
+}
+
+/// Grants given by `owner_id` to `grantee_id` in one of their buckets, the default one when `None`.
+pub async fn grants_from<C: ConnectionTrait>(
+    db: &C,
+    owner_id: Uuid,
+    grantee_id: Uuid,
+    bucket_id: Option<Uuid>,
+) -> Result<Vec<grant::Model>, AppError> {
+    Ok(grant::Entity::find()
+        .filter(grant::Column::OwnerId.eq(owner_id))
+        .filter(grant::Column::GranteeId.eq(grantee_id))
+        .filter(bucket_condition(bucket_id))
+        .all(db)
+        .await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn covers_prefix_or_exact_key() {
+        assert!(covers("", "anything"));
+        assert!(covers("team/", "team/report.pdf"));
+        assert!(covers("team/", "team/sub/notes.txt"));
+        assert!(!covers("team/", "teammate/notes.txt"));
+        assert!(covers("team/report.pdf", "team/report.pdf"));
+        assert!(!covers("team/report", "team/report.pdf"));
+    }
+
+    #[test]
+    fn keys_condition_takes_wildcards_literally() {
+        let grant = grant::Model {
+            id: Uuid::nil(),
+            owner_id: Uuid::nil(),
+            grantee_id: Uuid::nil(),
+            bucket_id: None,
+            prefix: "team_a/".to_string(),
+            permission: Permission::Read.as_str().to_string(),
+            created_at: chrono::Utc::now().into(),
+        };
+        use sea_orm::QueryTrait;
+        let sql = crate::entities::file::Entity::find()
+            .filter(keys_condition(&[grant], crate::entities::file::Column::FilePath))
+            .build(sea_orm::DbBackend::Postgres)
+            .to_string();
+        assert!(sql.contains(r#""file_path" LIKE E'team\\_a/%' ESCAPE E'\\'"#), "{}", sql);
+    }
+
+    #[test]
+    fn bucket_condition_keeps_grants_to_their_bucket() {
+        use sea_orm::QueryTrait;
+        let sql = |bucket_id| {
+            grant::Entity::find()
+                .filter(bucket_condition(bucket_id))
+                .build(sea_orm::DbBackend::Postgres)
+                .to_string()
+        };
+        assert!(sql(None).contains(r#""bucket_id" IS NULL"#));
+        let bucket_id = Uuid::now_v7();
+        assert!(sql(Some(bucket_id)).contains(&format!(r#""bucket_id" = '{}'"#, bucket_id)));
+    }
+
+    #[test]
+    fn permission_allows_scopes() {
+        assert!(Permission::Read.allows(Scope::Read));
+        assert!(!Permission::Read.allows(Scope::Write));
+        assert!(!Permission::Read.allows(Scope::Delete));
+        assert!(Permission::ReadWrite.allows(Scope::Write));
+        assert!(Permission::ReadWrite.allows(Scope::Delete));
+        assert!(!Permission::ReadWrite.allows(Scope::Admin));
+    }
+
+    #[test]
+    fn permission_roundtrip() {
+        for permission in [Permission::Read, Permission::ReadWrite] {
+            assert_eq!(Permission::parse(permission.as_str()), Some(permission));
+        }
+        assert_eq!(Permission::parse("write"), None);
+    }
+}
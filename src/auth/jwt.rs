@@ -0,0 +1,256 @@
+use anyhow::Context;
+use jsonwebtoken::jwk::{AlgorithmParameters, JwkSet, KeyAlgorithm};
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::error::AppError;
+
+#[derive(Clone)]
+struct VerificationKey {
+    kid: Option<String>,
+    algorithm: Algorithm,
+    key: DecodingKey,
+}
+
+/// Validates bearer JWTs against the keys configured for this deployment
+/// and maps the configured claim to a Rose user id.
+#[derive(Clone)]
+pub struct JwtVerifier {
+    keys: Vec<VerificationKey>,
+    user_claim: String,
+    issuer: Option<String>,
+    audience: Option<String>,
+}
+
+fn jwk_algorithm(key_algorithm: Option<KeyAlgorithm>, params: &AlgorithmParameters) -> Option<Algorithm> {
+    match key_algorithm {
+        Some(KeyAlgorithm::HS256) => Some(Algorithm::HS256),
+        Some(KeyAlgorithm::RS256) => Some(Algorithm::RS256),
+        Some(KeyAlgorithm::ES256) => Some(Algorithm::ES256),
+        Some(_) => None,
+        // no "alg" on the key, guess it from the key type
+        None => match params {
+            AlgorithmParameters::OctetKey(_) => Some(Algorithm::HS256),
+            AlgorithmParameters::RSA(_) => Some(Algorithm::RS256),
+            AlgorithmParameters::EllipticCurve(_) => Some(Algorithm::ES256),
+            AlgorithmParameters::OctetKeyPair(_) => None,
+        },
+    }
+}
+
+impl JwtVerifier {
+    pub fn new(user_claim: String, issuer: Option<String>, audience: Option<String>) -> Self {
+        Self {
+            keys: Vec::new(),
+            user_claim,
+            issuer,
+            audience,
+        }
+    }
+
+    pub fn from_config(config: &Config) -> anyhow::Result<Self> {
+        let mut verifier = Self::new(
+            config.jwt_user_claim.clone(),
+            config.jwt_issuer.clone(),
+            config.jwt_audience.clone(),
+        );
+
+        if let Some(ref secret) = config.jwt_hs256_secret {
+            verifier.add_key(None, Algorithm::HS256, DecodingKey::from_secret(secret.as_bytes()));
+        }
+
+        if let Some(ref path) = config.jwt_rsa_public_key_path {
+            let pem = std::fs::read(path).with_context(|| format!("Cannot read RSA key {}", path))?;
+            let key = DecodingKey::from_rsa_pem(&pem).context("Invalid RSA public key")?;
+            verifier.add_key(None, Algorithm::RS256, key);
+        }
+
+        if let Some(ref path) = config.jwt_ec_public_key_path {
+            let pem = std::fs::read(path).with_context(|| format!("Cannot read EC key {}", path))?;
+            let key = DecodingKey::from_ec_pem(&pem).context("Invalid EC public key")?;
+            verifier.add_key(None, Algorithm::ES256, key);
+        }
+
+        if let Some(ref path) = config.jwt_jwks_path {
+            let raw = std::fs::read(path).with_context(|| format!("Cannot read JWKS {}", path))?;
+            let jwks: JwkSet = serde_json::from_slice(&raw).context("Invalid JWKS file")?;
+            for jwk in &jwks.keys {
+                let Some(algorithm) = jwk_algorithm(jwk.common.key_algorithm, &jwk.algorithm) else {
+                    tracing::warn!("Skipping JWKS key {:?}: unsupported algorithm", jwk.common.key_id);
+                    continue;
+                };
+                let key = DecodingKey::from_jwk(jwk).context("Invalid JWKS key")?;
+                verifier.add_key(jwk.common.key_id.clone(), algorithm, key);
+            }
+        }
+
+        Ok(verifier)
+    }
+
+    pub fn add_key(&mut self, kid: Option<String>, algorithm: Algorithm, key: DecodingKey) {
+        self.keys.push(VerificationKey { kid, algorithm, key });
+    }
+
+    pub fn has_keys(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    fn validation(&self, algorithm: Algorithm) -> Validation {
+        let mut validation = Validation::new(algorithm);
+        match self.audience {
+            Some(ref aud) => validation.set_audience(&[aud]),
+            None => validation.validate_aud = false,
+        }
+        if let Some(ref iss) = self.issuer {
+            validation.set_issuer(&[iss]);
+        }
+        validation
+    }
+
+    fn user_id_from_claims(&self, claims: &Map<String, Value>) -> Result<Uuid, AppError> {
+        claims
+            .get(&self.user_claim)
+            .and_then(|v| v.as_str())
+            .and_then(|v| Uuid::parse_str(v).ok())
+            .ok_or_else(|| {
+                AppError::Unauthorized(format!(
+                    "Token claim '{}' is missing or not a user id",
+                    self.user_claim
+                ))
+            })
+    }
+
+    pub fn verify(&self, token: &str) -> Result<Uuid, AppError> {
+        let header = decode_header(token)
+            .map_err(|_| AppError::Unauthorized("Malformed bearer token".to_string()))?;
+
+        // only try keys of the announced algorithm, and the announced key id when both sides have one
+        let candidates = self.keys.iter().filter(|k| {
+            k.algorithm == header.alg
+                && match (&k.kid, &header.kid) {
+                    (Some(kid), Some(token_kid)) => kid == token_kid,
+                    _ => true,
+                }
+        });
+
+        for candidate in candidates {
+            match decode::<Map<String, Value>>(token, &candidate.key, &self.validation(candidate.algorithm)) {
+                Ok(data) => return self.user_id_from_claims(&data.claims),
+                Err(err) => tracing::debug!("Bearer token rejected by key {:?}: {}", candidate.kid, err),
+            }
+        }
+
+        Err(AppError::Unauthorized("Invalid bearer token".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jsonwebtoken::{encode, EncodingKey, Header};
+    use serde_json::json;
+
+    const SECRET: &[u8] = b"test-secret";
+
+    fn verifier(audience: Option<&str>) -> JwtVerifier {
+        let mut verifier = JwtVerifier::new(
+            "sub".to_string(),
+            Some("https://issuer.test".to_string()),
+            audience.map(str::to_string),
+        );
+        verifier.add_key(None, Algorithm::HS256, DecodingKey::from_secret(SECRET));
+        verifier
+    }
+
+    fn token(claims: Value, secret: &[u8]) -> String {
+        encode(&Header::new(Algorithm::HS256), &claims, &EncodingKey::from_secret(secret)).unwrap()
+    }
+
+    fn exp() -> i64 {
+        chrono::Utc::now().timestamp() + 3600
+    }
+
+    #[test]
+    fn verify_valid_hs256_token() {
+        let user_id = Uuid::now_v7();
+        let jwt = token(
+            json!({"sub": user_id.to_string(), "iss": "https://issuer.test", "exp": exp()}),
+            SECRET,
+        );
+
+        assert_eq!(verifier(None).verify(&jwt).unwrap(), user_id);
+    }
+
+    #[test]
+    fn verify_uses_configured_claim() {
+        let user_id = Uuid::now_v7();
+        let mut verifier = verifier(None);
+        verifier.user_claim = "rose_user".to_string();
+        let jwt = token(
+            json!({"sub": "someone", "rose_user": user_id.to_string(), "iss": "https://issuer.test", "exp": exp()}),
+            SECRET,
+        );
+
+        assert_eq!(verifier.verify(&jwt).unwrap(), user_id);
+    }
+
+    #[test]
+    fn verify_rejects_wrong_signature() {
+        let jwt = token(
+            json!({"sub": Uuid::now_v7().to_string(), "iss": "https://issuer.test", "exp": exp()}),
+            b"another-secret",
+        );
+
+        assert!(matches!(verifier(None).verify(&jwt), Err(AppError::Unauthorized(_))));
+    }
+
+    #[test]
+    fn verify_rejects_expired_token() {
+        let jwt = token(
+            json!({"sub": Uuid::now_v7().to_string(), "iss": "https://issuer.test", "exp": 1}),
+            SECRET,
+        );
+
+        assert!(matches!(verifier(None).verify(&jwt), Err(AppError::Unauthorized(_))));
+    }
+
+    #[test]
+    fn verify_checks_issuer_and_audience() {
+        let sub = Uuid::now_v7().to_string();
+        let wrong_issuer = token(json!({"sub": sub, "iss": "https://evil.test", "exp": exp()}), SECRET);
+        assert!(verifier(None).verify(&wrong_issuer).is_err());
+
+        let wrong_audience = token(
+            json!({"sub": sub, "iss": "https://issuer.test", "aud": "other", "exp": exp()}),
+            SECRET,
+        );
+        assert!(verifier(Some("rose")).verify(&wrong_audience).is_err());
+    }
+
+    #[test]
+    fn verify_rejects_non_uuid_subject() {
+        let jwt = token(json!({"sub": "alice", "iss": "https://issuer.test", "exp": exp()}), SECRET);
+
+        match verifier(None).verify(&jwt) {
+            Err(AppError::Unauthorized(msg)) => assert!(msg.contains("sub")),
+            other => panic!("expected Unauthorized, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn jwk_algorithm_guesses_from_key_type() {
+        let jwks: JwkSet = serde_json::from_value(json!({
+            "keys": [{"kty": "oct", "k": "c2VjcmV0", "kid": "k1"}]
+        }))
+        .unwrap();
+        let jwk = &jwks.keys[0];
+
+        assert_eq!(jwk_algorithm(jwk.common.key_algorithm, &jwk.algorithm), Some(Algorithm::HS256));
+        assert_eq!(
+            jwk_algorithm(Some(KeyAlgorithm::PS256), &jwk.algorithm),
+            None
+        );
+    }
+}
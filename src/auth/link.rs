@@ -0,0 +1,59 @@
+use argon2::password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rand::RngCore;
+
+use crate::error::AppError;
+
+/// Unguessable token of a public link, short enough to sit in a URL.
+pub fn generate_token() -> String {
+    let mut secret = [0u8; 32];
+    rand::rngs::OsRng.fill_bytes(&mut secret);
+    URL_SAFE_NO_PAD.encode(secret)
+}
+
+/// Link passwords are chosen by people, unlike keys they need a slow hash.
+pub fn hash_password(password: &str) -> Result<String, AppError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AppError::InternalError(format!("Failed to hash password: {}", e)))
+}
+
+pub fn verify_password(password: &str, hash: &str) -> bool {
+    PasswordHash::new(hash)
+        .is_ok_and(|parsed| Argon2::default().verify_password(password.as_bytes(), &parsed).is_ok())
+}
+
+/// Password check of a protected link, links without password accept anyone.
+pub fn check_password(hash: Option<&str>, given: Option<&str>) -> Result<(), AppError> {
+    match (hash, given) {
+        (None, _) => Ok(()),
+        (Some(hash), Some(given)) if verify_password(given, hash) => Ok(()),
+        (Some(_), _) => Err(AppError::Unauthorized("Invalid or missing link password".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generate_token_is_url_safe_and_unique() {
+        let token = generate_token();
+        assert_eq!(token.len(), 43);
+        assert!(token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_ne!(token, generate_token());
+    }
+
+    #[test]
+    fn check_password_verifies_hash() {
+        let hash = hash_password("hunter2").unwrap();
+
+        assert!(check_password(Some(&hash), Some("hunter2")).is_ok());
+        assert!(matches!(check_password(Some(&hash), Some("hunter3")), Err(AppError::Unauthorized(_))));
+        assert!(matches!(check_password(Some(&hash), None), Err(AppError::Unauthorized(_))));
+        assert!(check_password(None, None).is_ok());
+    }
+}
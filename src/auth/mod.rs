@@ -0,0 +1,292 @@
+pub mod access;
+pub mod api_key;
+pub mod grant;
+pub mod jwt;
+pub mod link;
+pub mod org;
+pub mod scope;
+
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts, HeaderMap},
+};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::error::AppError;
+use crate::users;
+use crate::AppState;
+pub use jwt::JwtVerifier;
+pub use scope::Scope;
+
+pub struct Authenticator {
+    jwt: JwtVerifier,
+    allow_legacy_user_header: bool,
+}
+
+/// Caller authenticated by a bearer token (or by the legacy `x-user-id` header when enabled).
+///
+/// User sessions hold every scope, API keys only the scopes and path prefix they were created with.
+#[derive(Debug, Clone)]
+pub struct AuthUser {
+    pub user_id: Uuid,
+    pub scopes: Vec<Scope>,
+    pub path_prefix: Option<String>,
+}
+
+impl AuthUser {
+    pub fn user(user_id: Uuid) -> Self {
+        Self {
+            user_id,
+            scopes: Scope::ALL.to_vec(),
+            path_prefix: None,
+        }
+    }
+
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        self.scopes.contains(&Scope::Admin) || self.scopes.contains(&scope)
+    }
+
+    pub fn allows_path(&self, key: &str) -> bool {
+        self.path_prefix
+            .as_deref()
+            .is_none_or(|prefix| key.starts_with(prefix))
+    }
+
+    pub fn require_scope(&self, scope: Scope) -> Result<(), AppError> {
+        if self.has_scope(scope) {
+            Ok(())
+        } else {
+            Err(AppError::Forbidden(format!(
+                "Missing '{}' scope",
+                scope.as_str()
+            )))
+        }
+    }
+
+    /// Instance-wide operations need the admin scope and a user listed in `ADMIN_USER_IDS`.
+    pub fn require_instance_admin(&self, admin_user_ids: &[Uuid]) -> Result<(), AppError> {
+        self.require_scope(Scope::Admin)?;
+        if !admin_user_ids.contains(&self.user_id) {
+            return Err(AppError::Forbidden("Reserved to instance administrators".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Scope and path prefix check for an operation on `key`.
+    pub fn require(&self, scope: Scope, key: &str) -> Result<(), AppError> {
+        self.require_scope(scope)?;
+        if !self.allows_path(key) {
+            return Err(AppError::Forbidden(format!(
+                "Key '{}' is outside of this credential's prefix",
+                key
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn bearer_token(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .map(str::trim)
+}
+
+fn legacy_user_id(headers: &HeaderMap) -> Result<Uuid, AppError> {
+    headers
+        .get("x-user-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| Uuid::parse_str(v).ok())
+        .ok_or(AppError::BadRequest(
+            "Missing or invalid x-user-id header".to_string(),
+        ))
+}
+
+impl Authenticator {
+    pub fn new(jwt: JwtVerifier, allow_legacy_user_header: bool) -> Self {
+        Self {
+            jwt,
+            allow_legacy_user_header,
+        }
+    }
+
+    pub fn from_config(config: &Config) -> anyhow::Result<Self> {
+        let jwt = JwtVerifier::from_config(config)?;
+        if !jwt.has_keys() && !config.allow_legacy_user_header {
+            tracing::warn!("No JWT key configured and legacy x-user-id header disabled: every request will be rejected");
+        }
+        if config.allow_legacy_user_header {
+            tracing::warn!("Legacy x-user-id header is trusted, only use this behind a trusted gateway");
+        }
+        Ok(Self::new(jwt, config.allow_legacy_user_header))
+    }
+
+    pub async fn authenticate(
+        &self,
+        headers: &HeaderMap,
+        db: &DatabaseConnection,
+    ) -> Result<AuthUser, AppError> {
+        if let Some(token) = bearer_token(headers) {
+            if api_key::is_api_key(token) {
+                return api_key::authenticate(db, token).await;
+            }
+            let user_id = self.jwt.verify(token)?;
+            return Ok(AuthUser::user(user_id));
+        }
+
+        if self.allow_legacy_user_header {
+            let user_id = legacy_user_id(headers)?;
+            return Ok(AuthUser::user(user_id));
+        }
+
+        Err(AppError::Unauthorized("Missing bearer token".to_string()))
+    }
+}
+
+impl FromRequestParts<AppState> for AuthUser {
+    type Rejection = AppError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &AppState) -> Result<Self, Self::Rejection> {
+        let user = state.auth.authenticate(&parts.headers, &state.db).await?;
+        users::check_enabled(&state.db, user.user_id).await?;
+        Ok(user)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use jsonwebtoken::{encode, Algorithm, DecodingKey, EncodingKey, Header};
+    use serde_json::json;
+
+    fn authenticator(allow_legacy_user_header: bool) -> Authenticator {
+        let mut jwt = JwtVerifier::new("sub".to_string(), None, None);
+        jwt.add_key(None, Algorithm::HS256, DecodingKey::from_secret(b"secret"));
+        Authenticator::new(jwt, allow_legacy_user_header)
+    }
+
+    #[test]
+    fn bearer_token_is_extracted() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(bearer_token(&headers), None);
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Bearer abc.def.ghi"));
+        assert_eq!(bearer_token(&headers), Some("abc.def.ghi"));
+
+        headers.insert(header::AUTHORIZATION, HeaderValue::from_static("Basic Zm9vOmJhcg=="));
+        assert_eq!(bearer_token(&headers), None);
+    }
+
+    #[test]
+    fn legacy_user_id_missing_is_bad_request() {
+        let headers = HeaderMap::new();
+        let err = legacy_user_id(&headers).unwrap_err();
+
+        match err {
+            AppError::BadRequest(msg) => assert!(msg.contains("x-user-id")),
+            other => panic!("expected BadRequest, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn legacy_user_id_invalid_is_bad_request() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-user-id", HeaderValue::from_static("not-a-uuid"));
+
+        let err = legacy_user_id(&headers).unwrap_err();
+        match err {
+            AppError::BadRequest(msg) => assert!(msg.contains("invalid")),
+            other => panic!("expected BadRequest, got: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn legacy_user_id_valid() {
+        let mut headers = HeaderMap::new();
+        let u = Uuid::now_v7();
+        headers.insert("x-user-id", HeaderValue::from_str(&u.to_string()).unwrap());
+
+        let parsed = legacy_user_id(&headers).unwrap();
+        assert_eq!(parsed, u);
+    }
+
+    #[tokio::test]
+    async fn authenticate_ignores_legacy_header_unless_enabled() {
+        let db = DatabaseConnection::Disconnected;
+        let mut headers = HeaderMap::new();
+        let u = Uuid::now_v7();
+        headers.insert("x-user-id", HeaderValue::from_str(&u.to_string()).unwrap());
+
+        assert!(matches!(
+            authenticator(false).authenticate(&headers, &db).await,
+            Err(AppError::Unauthorized(_))
+        ));
+        assert_eq!(authenticator(true).authenticate(&headers, &db).await.unwrap().user_id, u);
+    }
+
+    #[tokio::test]
+    async fn authenticate_prefers_bearer_token() {
+        let token_user = Uuid::now_v7();
+        let jwt = encode(
+            &Header::new(Algorithm::HS256),
+            &json!({"sub": token_user.to_string(), "exp": chrono::Utc::now().timestamp() + 60}),
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap();
+
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::AUTHORIZATION,
+            HeaderValue::from_str(&format!("Bearer {}", jwt)).unwrap(),
+        );
+        headers.insert("x-user-id", HeaderValue::from_str(&Uuid::now_v7().to_string()).unwrap());
+
+        let auth = authenticator(true)
+            .authenticate(&headers, &DatabaseConnection::Disconnected)
+            .await
+            .unwrap();
+        assert_eq!(auth.user_id, token_user);
+        assert!(auth.has_scope(Scope::Admin));
+    }
+
+    #[test]
+    fn require_checks_scope_and_prefix() {
+        let auth = AuthUser {
+            user_id: Uuid::now_v7(),
+            scopes: vec![Scope::Read],
+            path_prefix: Some("backups/".to_string()),
+        };
+
+        assert!(auth.require(Scope::Read, "backups/db.tar").is_ok());
+        assert!(matches!(auth.require(Scope::Write, "backups/db.tar"), Err(AppError::Forbidden(_))));
+        assert!(matches!(auth.require(Scope::Read, "photos/cat.png"), Err(AppError::Forbidden(_))));
+    }
+
+    #[test]
+    fn instance_admin_needs_listed_user_and_admin_scope() {
+        let admin = AuthUser::user(Uuid::now_v7());
+        let admins = vec![admin.user_id];
+
+        assert!(admin.require_instance_admin(&admins).is_ok());
+        assert!(AuthUser::user(Uuid::now_v7()).require_instance_admin(&admins).is_err());
+
+        let read_only_key = AuthUser { scopes: vec![Scope::Read], ..admin };
+        assert!(read_only_key.require_instance_admin(&admins).is_err());
+    }
+
+    #[test]
+    fn admin_scope_implies_every_scope() {
+        let auth = AuthUser {
+            user_id: Uuid::now_v7(),
+            scopes: vec![Scope::Admin],
+            path_prefix: None,
+        };
+
+        assert!(auth.has_scope(Scope::Delete));
+        assert!(auth.require(Scope::Write, "anything").is_ok());
+    }
+}
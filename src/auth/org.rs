@@ -0,0 +1,88 @@
+use sea_orm::{ConnectionTrait, EntityTrait};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::Scope;
+use crate::entities::org_member;
+use crate::error::AppError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    Viewer,
+    Member,
+    Admin,
+    Owner,
+}
+
+impl Role {
+    pub const ALL: [Role; 4] = [Role::Viewer, Role::Member, Role::Admin, Role::Owner];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Role::Viewer => "viewer",
+            Role::Member => "member",
+            Role::Admin => "admin",
+            Role::Owner => "owner",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Role> {
+        Role::ALL.into_iter().find(|r| r.as_str() == value)
+    }
+
+    /// Scopes a member holds on the objects of the organization.
+    pub fn allows(&self, scope: Scope) -> bool {
+        match self {
+            Role::Viewer => scope == Scope::Read,
+            Role::Member => scope != Scope::Admin,
+            Role::Admin | Role::Owner => true,
+        }
+    }
+
+    /// Admins manage members up to their own role, only owners appoint other owners.
+    pub fn can_assign(&self, role: Role) -> bool {
+        *self >= Role::Admin && *self >= role
+    }
+}
+
+/// Role of `user_id` in the organization `org_id`, `None` when not a member (or not an organization).
+pub async fn member_role<C: ConnectionTrait>(
+    db: &C,
+    org_id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<Role>, AppError> {
+    let member = org_member::Entity::find_by_id((org_id, user_id)).one(db).await?;
+    Ok(member.and_then(|m| Role::parse(&m.role)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn role_scopes() {
+        assert!(Role::Viewer.allows(Scope::Read));
+        assert!(!Role::Viewer.allows(Scope::Write));
+        assert!(Role::Member.allows(Scope::Delete));
+        assert!(!Role::Member.allows(Scope::Admin));
+        assert!(Role::Admin.allows(Scope::Admin));
+    }
+
+    #[test]
+    fn role_assignment() {
+        assert!(Role::Owner.can_assign(Role::Owner));
+        assert!(Role::Admin.can_assign(Role::Admin));
+        assert!(Role::Admin.can_assign(Role::Viewer));
+        assert!(!Role::Admin.can_assign(Role::Owner));
+        assert!(!Role::Member.can_assign(Role::Viewer));
+    }
+
+    #[test]
+    fn role_roundtrip() {
+        for role in Role::ALL {
+            assert_eq!(Role::parse(role.as_str()), Some(role));
+        }
+        assert_eq!(Role::parse("guest"), None);
+    }
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    Read,
+    Write,
+    Delete,
+    Admin,
+}
+
+impl Scope {
+    pub const ALL: [Scope; 4] = [Scope::Read, Scope::Write, Scope::Delete, Scope::Admin];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Read => "read",
+            Scope::Write => "write",
+            Scope::Delete => "delete",
+            Scope::Admin => "admin",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Scope> {
+        Scope::ALL.into_iter().find(|s| s.as_str() == value)
+    }
+}
+
+/// Scopes are stored as a comma separated list, e.g. `read,write`.
+pub fn parse_scopes(value: &str) -> Result<Vec<Scope>, AppError> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(|s| Scope::parse(s).ok_or_else(|| AppError::BadRequest(format!("Unknown scope '{}'", s))))
+        .collect()
+}
+
+pub fn join_scopes(scopes: &[Scope]) -> String {
+    scopes.iter().map(Scope::as_str).collect::<Vec<_>>().join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_scopes_roundtrip() {
+        let scopes = parse_scopes("read, write,delete").unwrap();
+        assert_eq!(scopes, vec![Scope::Read, Scope::Write, Scope::Delete]);
+        assert_eq!(join_scopes(&scopes), "read,write,delete");
+    }
+
+    #[test]
+    fn parse_scopes_rejects_unknown_scope() {
+        match parse_scopes("read,root") {
+            Err(AppError::BadRequest(msg)) => assert!(msg.contains("root")),
+            other => panic!("expected BadRequest, got: {:?}", other),
+        }
+    }
+}
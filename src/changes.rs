@@ -0,0 +1,87 @@
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, QuerySelect,
+};
+use uuid::Uuid;
+
+use crate::entities::{change, file, user};
+use crate::objects::Namespace;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// A new latest version of the key.
+    Create,
+    /// One version of the key removed.
+    Delete,
+    /// Every version of the key renamed, `from_path` holds the previous key.
+    Move,
+}
+
+impl ChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Create => "create",
+            ChangeKind::Delete => "delete",
+            ChangeKind::Move => "move",
+        }
+    }
+}
+
+/// Appends a change to the feed of the namespace owner, to be called in the transaction of the change.
+///
+/// The sequence number comes from the owner's users row, whose lock orders the writers until they commit:
+/// readers never see a change before an earlier one of the same owner.
+pub async fn record<C: ConnectionTrait>(
+    db: &C,
+    ns: &Namespace,
+    kind: ChangeKind,
+    file: &file::Model,
+    from_path: Option<String>,
+) -> Result<(), DbErr> {
+    let owner = user::Entity::update_many()
+        .col_expr(user::Column::ChangeSeq, Expr::col(user::Column::ChangeSeq).add(1))
+        .filter(user::Column::UserId.eq(file.user_id))
+        .exec_with_returning(db)
+        .await?
+        .pop()
+        .ok_or_else(|| DbErr::RecordNotFound(format!("User {} not found", file.user_id)))?;
+
+    change::ActiveModel::new(
+        file.user_id,
+        owner.change_seq,
+        kind.as_str().to_string(),
+        file.bucket_id,
+        file.file_path.clone(),
+        from_path,
+        file.s3_version_id.clone(),
+        file.content_size,
+        ns.actor_id,
+    )
+    .insert(db)
+    .await?;
+    Ok(())
+}
+
+/// Sequence number of the last change of `owner_id`, 0 when nothing happened yet.
+pub async fn latest_seq<C: ConnectionTrait>(db: &C, owner_id: Uuid) -> Result<i64, DbErr> {
+    let owner = user::Entity::find_by_id(owner_id).one(db).await?;
+    Ok(owner.map(|u| u.change_seq).unwrap_or_default())
+}
+
+/// Up to `limit` changes of `owner_id` after `cursor` matching `filter`, oldest first.
+pub async fn since<C: ConnectionTrait>(
+    db: &C,
+    owner_id: Uuid,
+    cursor: i64,
+    filter: Condition,
+    limit: u64,
+) -> Result<Vec<change::Model>, DbErr> {
+    change::Entity::find()
+        .filter(change::Column::OwnerId.eq(owner_id))
+        .filter(change::Column::Seq.gt(cursor))
+        .filter(filter)
+        .order_by_asc(change::Column::Seq)
+        .limit(limit)
+        .all(db)
+        .await
+}
@@ -0,0 +1,182 @@
+use axum::http::HeaderMap;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use crc_fast::CrcAlgorithm;
+use md5::Md5;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use crate::error::AppError;
+
+/// Digests of an upload body, computed once and checked against whatever the client sent along.
+#[derive(Debug, Clone)]
+pub struct Checksums {
+    pub md5: [u8; 16],
+    pub sha256: [u8; 32],
+    pub crc32: u32,
+    pub crc32c: u32,
+}
+
+impl Checksums {
+    pub fn compute(body: &[u8]) -> Self {
+        Self {
+            md5: Md5::digest(body).into(),
+            sha256: Sha256::digest(body).into(),
+            crc32: crc_fast::checksum(CrcAlgorithm::Crc32IsoHdlc, body) as u32,
+            crc32c: crc_fast::checksum(CrcAlgorithm::Crc32Iscsi, body) as u32,
+        }
+    }
+
+    pub fn md5_base64(&self) -> String {
+        STANDARD.encode(self.md5)
+    }
+
+    pub fn sha256_base64(&self) -> String {
+        STANDARD.encode(self.sha256)
+    }
+
+    /// The form stored in `files.checksum_sha256`.
+    pub fn sha256_hex(&self) -> String {
+        hex::encode(self.sha256)
+    }
+
+    pub fn crc32_base64(&self) -> String {
+        STANDARD.encode(self.crc32.to_be_bytes())
+    }
+
+    pub fn crc32c_base64(&self) -> String {
+        STANDARD.encode(self.crc32c.to_be_bytes())
+    }
+
+    /// Rejects the upload when `Content-MD5` or an `x-amz-checksum-*` header disagrees with the body, and when
+    /// the checksum algorithm is not one of `SUPPORTED_ALGORITHMS`.
+    pub fn verify(&self, headers: &HeaderMap) -> Result<(), AppError> {
+        check_algorithms(headers)?;
+        check(headers, "content-md5", &self.md5)?;
+        check(headers, "x-amz-checksum-sha256", &self.sha256)?;
+        check(headers, "x-amz-checksum-crc32", &self.crc32.to_be_bytes())?;
+        check(headers, "x-amz-checksum-crc32c", &self.crc32c.to_be_bytes())
+    }
+
+    pub fn to_json(&self) -> Value {
+        json!({
+            "content_md5": self.md5_base64(),
+            "sha256": self.sha256_base64(),
+            "crc32": self.crc32_base64(),
+            "crc32c": self.crc32c_base64(),
+        })
+    }
+}
+
+/// Computes the checksums of `body` and verifies the ones sent in `headers`, before anything is stored.
+pub fn verify_upload(headers: &HeaderMap, body: &[u8]) -> Result<Checksums, AppError> {
+    let checksums = Checksums::compute(body);
+    checksums.verify(headers)?;
+    Ok(checksums)
+}
+
+const CHECKSUM_PREFIX: &str = "x-amz-checksum-";
+const SUPPORTED_ALGORITHMS: [&str; 3] = ["sha256", "crc32", "crc32c"];
+/// `x-amz-checksum-*` headers that describe the checksum rather than carry one.
+const CHECKSUM_SETTINGS: [&str; 2] = ["type", "algorithm"];
+
+/// A checksum that cannot be verified (SHA-1, CRC-64/NVME) is refused rather than silently accepted.
+fn check_algorithms(headers: &HeaderMap) -> Result<(), AppError> {
+    for name in headers.keys() {
+        let Some(algorithm) = name.as_str().strip_prefix(CHECKSUM_PREFIX) else {
+            continue;
+        };
+        if !SUPPORTED_ALGORITHMS.contains(&algorithm) && !CHECKSUM_SETTINGS.contains(&algorithm) {
+            return Err(AppError::BadRequest(format!(
+                "Checksum algorithm '{}' is not supported, use one of {}",
+                algorithm,
+                SUPPORTED_ALGORITHMS.join(", ")
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn check(headers: &HeaderMap, name: &str, computed: &[u8]) -> Result<(), AppError> {
+    let Some(value) = headers.get(name) else {
+        return Ok(());
+    };
+    let expected = value
+        .to_str()
+        .ok()
+        .and_then(|v| STANDARD.decode(v.trim()).ok())
+        .filter(|digest| digest.len() == computed.len())
+        .ok_or_else(|| AppError::BadRequest(format!("Invalid {} header", name)))?;
+
+    if expected != computed {
+        return Err(AppError::BadRequest(format!(
+            "The {} you specified did not match the received content",
+            name
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn compute_matches_known_digests() {
+        let checksums = Checksums::compute(b"hello world");
+
+        assert_eq!(checksums.md5_base64(), "XrY7u+Ae7tCTyyK7j1rNww==");
+        assert_eq!(
+            checksums.sha256_hex(),
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+        assert_eq!(checksums.crc32, 0x0d4a_1185);
+        assert_eq!(checksums.crc32c, 0xc994_65aa);
+    }
+
+    #[test]
+    fn verify_accepts_matching_and_missing_headers() {
+        let checksums = Checksums::compute(b"hello world");
+        assert!(checksums.verify(&HeaderMap::new()).is_ok());
+
+        let mut headers = HeaderMap::new();
+        headers.insert("content-md5", HeaderValue::from_str(&checksums.md5_base64()).unwrap());
+        headers.insert("x-amz-checksum-sha256", HeaderValue::from_str(&checksums.sha256_base64()).unwrap());
+        headers.insert("x-amz-checksum-crc32", HeaderValue::from_str(&checksums.crc32_base64()).unwrap());
+        headers.insert("x-amz-checksum-crc32c", HeaderValue::from_str(&checksums.crc32c_base64()).unwrap());
+        headers.insert("x-amz-checksum-type", HeaderValue::from_static("FULL_OBJECT"));
+        assert!(checksums.verify(&headers).is_ok());
+    }
+
+    #[test]
+    fn verify_rejects_unsupported_algorithms() {
+        let checksums = Checksums::compute(b"hello world");
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-amz-checksum-crc32", HeaderValue::from_str(&Checksums::compute(b"x").crc32_base64()).unwrap());
+        assert!(matches!(checksums.verify(&headers), Err(AppError::BadRequest(_))));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-amz-checksum-sha1", HeaderValue::from_static("Kq5sNclPz7QV2+lfQIuc6R7oRu0="));
+        assert!(matches!(checksums.verify(&headers), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn verify_rejects_mismatches_and_malformed_values() {
+        let checksums = Checksums::compute(b"hello world");
+        let other = Checksums::compute(b"hello there");
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-amz-checksum-crc32c", HeaderValue::from_str(&other.crc32c_base64()).unwrap());
+        assert!(matches!(checksums.verify(&headers), Err(AppError::BadRequest(_))));
+
+        let mut headers = HeaderMap::new();
+        headers.insert("content-md5", HeaderValue::from_static("not base64!"));
+        assert!(matches!(checksums.verify(&headers), Err(AppError::BadRequest(_))));
+
+        // a SHA-256 sent as Content-MD5 has the wrong length
+        let mut headers = HeaderMap::new();
+        headers.insert("content-md5", HeaderValue::from_str(&checksums.sha256_base64()).unwrap());
+        assert!(matches!(checksums.verify(&headers), Err(AppError::BadRequest(_))));
+    }
+}
@@ -0,0 +1,165 @@
+use std::time::Duration;
+
+use aws_sdk_s3::operation::list_object_versions::ListObjectVersionsOutput;
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, ModelTrait, PaginatorTrait, QueryFilter};
+use uuid::Uuid;
+
+use crate::entities::{file, multipart_upload, pending_upload, presigned_upload};
+use crate::error::AppError;
+use crate::AppState;
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(600);
+/// Multipart uploads get as long as the lifecycle policy gives them before it aborts them.
+const MULTIPART_TIMEOUT_DAYS: i64 = 7;
+
+/// Removes from the store the objects of uploads that never got committed: failed PUTs,
+/// expired presigned uploads and abandoned multipart uploads.
+pub fn spawn_sweeper(state: AppState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(err) = sweep(&state).await {
+                tracing::error!("Failed to sweep unfinished uploads: {:?}", err);
+            }
+        }
+    });
+}
+
+async fn sweep(state: &AppState) -> Result<(), AppError> {
+    let now = chrono::Utc::now();
+    let cutoff = now - chrono::Duration::seconds(state.config.pending_upload_timeout_secs);
+
+    let pending = pending_upload::Entity::find()
+        .filter(pending_upload::Column::CreatedAt.lt(cutoff))
+        .all(&state.db)
+        .await?;
+    for upload in pending {
+        let file_key = upload.file_key;
+        if let Err(err) = remove_pending_upload(state, upload).await {
+            tracing::error!("Failed to remove pending upload {}: {:?}", file_key, err);
+        }
+    }
+
+    // a PUT started right before the expiry can still be running, they get the same timeout
+    let expired = presigned_upload::Entity::find()
+        .filter(presigned_upload::Column::ExpiresAt.lt(cutoff))
+        .all(&state.db)
+        .await?;
+    for upload in expired {
+        let id = upload.id;
+        if let Err(err) = remove_presigned_upload(state, upload).await {
+            tracing::error!("Failed to remove presigned upload {}: {:?}", id, err);
+        }
+    }
+
+    let stale = multipart_upload::Entity::find()
+        .filter(multipart_upload::Column::CreatedAt.lt(now - chrono::Duration::days(MULTIPART_TIMEOUT_DAYS)))
+        .all(&state.db)
+        .await?;
+    for upload in stale {
+        let id = upload.id;
+        if let Err(err) = remove_multipart_upload(state, upload).await {
+            tracing::error!("Failed to remove multipart upload {}: {:?}", id, err);
+        }
+    }
+
+    Ok(())
+}
+
+pub async fn remove_pending_upload(state: &AppState, upload: pending_upload::Model) -> Result<(), AppError> {
+    purge_object(state, upload.file_key).await?;
+    tracing::info!("Removed pending upload {} of {} for key {}", upload.file_key, upload.owner_id, upload.file_path);
+    upload.delete(&state.db).await?;
+    Ok(())
+}
+
+/// The row is claimed first: a commit of the upload racing with the removal fails instead of referencing
+/// an object about to be purged.
+pub async fn remove_presigned_upload(state: &AppState, upload: presigned_upload::Model) -> Result<(), AppError> {
+    let claimed = presigned_upload::Entity::delete_by_id(upload.id).exec(&state.db).await?;
+    if claimed.rows_affected == 0 {
+        // committed or removed meanwhile
+        return Ok(());
+    }
+    if let Err(err) = purge_object(state, upload.file_key).await {
+        // tracked again as a pending upload, so that a later sweep retries
+        let owner_id = upload.owner_id.unwrap_or(upload.user_id);
+        pending_upload::ActiveModel::new(upload.file_key, owner_id, upload.file_path)
+            .insert(&state.db)
+            .await?;
+        return Err(err);
+    }
+    tracing::info!("Removed presigned upload {} of user {}", upload.id, upload.user_id);
+    Ok(())
+}
+
+pub async fn remove_multipart_upload(state: &AppState, upload: multipart_upload::Model) -> Result<(), AppError> {
+    // already completed or aborted by the lifecycle policy when this fails
+    let _ = state
+        .store_client
+        .abort_multipart_upload(&upload.file_key.to_string(), &upload.backend_upload_id)
+        .await;
+    purge_object(state, upload.file_key).await?;
+    tracing::info!("Removed multipart upload {} of user {}", upload.id, upload.user_id);
+    upload.delete(&state.db).await?;
+    Ok(())
+}
+
+/// Removes every version of the object at `file_key`, unless a file version references it
+/// (the upload got committed but its tracking row was not removed).
+async fn purge_object(state: &AppState, file_key: Uuid) -> Result<(), AppError> {
+    let referenced = file::Entity::find()
+        .filter(file::Column::FileKey.eq(file_key))
+        .count(&state.db)
+        .await?;
+    if referenced > 0 {
+        return Ok(());
+    }
+
+    let key = file_key.to_string();
+    let (mut key_marker, mut version_id_marker) = (None, None);
+    loop {
+        let page = state
+            .store_client
+            .list_versions(Some(&key), key_marker.as_deref(), version_id_marker.as_deref())
+            .await?;
+        for version_id in versions_of(&page, &key) {
+            state.store_client.delete(&key, Some(&version_id)).await?;
+        }
+        if !page.is_truncated.unwrap_or(false) {
+            return Ok(());
+        }
+        key_marker = page.next_key_marker;
+        version_id_marker = page.next_version_id_marker;
+    }
+}
+
+/// Versions and delete markers of exactly `key` in a listing by prefix.
+fn versions_of(page: &ListObjectVersionsOutput, key: &str) -> Vec<String> {
+    let versions = page.versions().iter().map(|v| (v.key(), v.version_id()));
+    let markers = page.delete_markers().iter().map(|m| (m.key(), m.version_id()));
+    versions
+        .chain(markers)
+        .filter(|(k, _)| *k == Some(key))
+        .map(|(_, version_id)| version_id.unwrap_or("null").to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_s3::types::{DeleteMarkerEntry, ObjectVersion};
+
+    #[test]
+    fn versions_of_keeps_the_exact_key() {
+        let page = ListObjectVersionsOutput::builder()
+            .versions(ObjectVersion::builder().key("abc").version_id("v1").build())
+            .versions(ObjectVersion::builder().key("abcd").version_id("v2").build())
+            .versions(ObjectVersion::builder().key("abc").build())
+            .delete_markers(DeleteMarkerEntry::builder().key("abc").version_id("m1").build())
+            .build();
+
+        assert_eq!(versions_of(&page, "abc"), vec!["v1", "null", "m1"]);
+    }
+}
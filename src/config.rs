@@ -6,6 +6,35 @@ pub struct Config {
     pub server_host: String,
     pub server_port: u16,
     pub db_url: String,
+    pub jwt_hs256_secret: Option<String>,
+    pub jwt_rsa_public_key_path: Option<String>,
+    pub jwt_ec_public_key_path: Option<String>,
+    pub jwt_jwks_path: Option<String>,
+    pub jwt_user_claim: String,
+    pub jwt_issuer: Option<String>,
+    pub jwt_audience: Option<String>,
+    pub allow_legacy_user_header: bool,
+    pub s3_api_port: Option<u16>,
+    pub s3_api_bucket: String,
+    pub s3_api_max_body_size: usize,
+    pub default_quota_bytes: Option<i64>,
+    pub default_org_quota_bytes: Option<i64>,
+    pub auto_provision_users: bool,
+    pub admin_user_ids: Vec<uuid::Uuid>,
+    pub audit_retention_days: Option<i64>,
+    pub trust_forwarded_for: bool,
+    pub event_file_path: Option<String>,
+    pub webhook_url: Option<String>,
+    pub webhook_secret: Option<String>,
+    pub pending_upload_timeout_secs: i64,
+    pub scrub_bytes_per_sec: Option<u64>,
+    pub archive_max_bytes: u64,
+    pub extract_max_bytes: u64,
+}
+
+// unset and empty variables are both treated as missing
+fn optional_var(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|v| !v.is_empty())
 }
 
 impl Config {
@@ -15,6 +44,32 @@ impl Config {
             server_host: std::env::var("SERVER_HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
             server_port: std::env::var("SERVER_PORT").unwrap_or_else(|_| "12055".to_string()).parse().expect("SERVER_PORT must be a valid port number"),
             db_url: std::env::var("DB_URL").expect("DB_URL must be set"),
+            jwt_hs256_secret: optional_var("JWT_HS256_SECRET"),
+            jwt_rsa_public_key_path: optional_var("JWT_RSA_PUBLIC_KEY_PATH"),
+            jwt_ec_public_key_path: optional_var("JWT_EC_PUBLIC_KEY_PATH"),
+            jwt_jwks_path: optional_var("JWT_JWKS_PATH"),
+            jwt_user_claim: optional_var("JWT_USER_CLAIM").unwrap_or_else(|| "sub".to_string()),
+            jwt_issuer: optional_var("JWT_ISSUER"),
+            jwt_audience: optional_var("JWT_AUDIENCE"),
+            allow_legacy_user_header: optional_var("ALLOW_LEGACY_USER_HEADER").map(|v| v == "true").unwrap_or(false),
+            s3_api_port: optional_var("S3_API_PORT").map(|v| v.parse().expect("S3_API_PORT must be a valid port number")),
+            s3_api_bucket: optional_var("S3_API_BUCKET").unwrap_or_else(|| "rose".to_string()),
+            s3_api_max_body_size: optional_var("S3_API_MAX_BODY_SIZE").map(|v| v.parse().expect("S3_API_MAX_BODY_SIZE must be a number of bytes")).unwrap_or(64 * 1024 * 1024),
+            default_quota_bytes: optional_var("DEFAULT_QUOTA_BYTES").map(|v| v.parse().expect("DEFAULT_QUOTA_BYTES must be a number of bytes")),
+            default_org_quota_bytes: optional_var("DEFAULT_ORG_QUOTA_BYTES").map(|v| v.parse().expect("DEFAULT_ORG_QUOTA_BYTES must be a number of bytes")),
+            auto_provision_users: optional_var("AUTO_PROVISION_USERS").map(|v| v == "true").unwrap_or(true),
+            admin_user_ids: optional_var("ADMIN_USER_IDS")
+                .map(|v| v.split(',').map(|id| id.trim().parse().expect("ADMIN_USER_IDS must be a comma separated list of UUIDs")).collect())
+                .unwrap_or_default(),
+            audit_retention_days: optional_var("AUDIT_RETENTION_DAYS").map(|v| v.parse().expect("AUDIT_RETENTION_DAYS must be a number of days")),
+            trust_forwarded_for: optional_var("TRUST_FORWARDED_FOR").map(|v| v == "true").unwrap_or(false),
+            event_file_path: optional_var("EVENT_FILE_PATH"),
+            webhook_url: optional_var("WEBHOOK_URL"),
+            webhook_secret: optional_var("WEBHOOK_SECRET"),
+            pending_upload_timeout_secs: optional_var("PENDING_UPLOAD_TIMEOUT_SECS").map(|v| v.parse().expect("PENDING_UPLOAD_TIMEOUT_SECS must be a number of seconds")).unwrap_or(3600),
+            scrub_bytes_per_sec: optional_var("SCRUB_BYTES_PER_SEC").map(|v| v.parse().expect("SCRUB_BYTES_PER_SEC must be a number of bytes")),
+            archive_max_bytes: optional_var("ARCHIVE_MAX_BYTES").map(|v| v.parse().expect("ARCHIVE_MAX_BYTES must be a number of bytes")).unwrap_or(10 * 1024 * 1024 * 1024),
+            extract_max_bytes: optional_var("EXTRACT_MAX_BYTES").map(|v| v.parse().expect("EXTRACT_MAX_BYTES must be a number of bytes")).unwrap_or(256 * 1024 * 1024),
         })
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+use aws_sdk_s3::operation::delete_objects::DeleteObjectsOutput;
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, Condition, EntityTrait, QueryFilter, QueryOrder, QuerySelect, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::cleanup;
+use crate::entities::{account_deletion, export, file, multipart_upload, pending_upload, presigned_upload, user};
+use crate::error::AppError;
+use crate::export as exports;
+use crate::transaction;
+use crate::AppState;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// Most keys a DeleteObjects request takes.
+const BATCH_SIZE: u64 = 1000;
+
+/// Carries on the account deletions not completed yet, including the ones a restart interrupted.
+pub fn spawn_worker(state: AppState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(err) = run_pending(&state).await {
+                tracing::error!("Failed to look for account deletions: {:?}", err);
+            }
+        }
+    });
+}
+
+async fn run_pending(state: &AppState) -> Result<(), AppError> {
+    let pending = account_deletion::Entity::find()
+        .filter(account_deletion::Column::CompletedAt.is_null())
+        .order_by_asc(account_deletion::Column::CreatedAt)
+        .all(&state.db)
+        .await?;
+
+    for job in pending {
+        if let Err(err) = delete_account(state, &job).await {
+            tracing::error!("Deletion {} of user {} stopped: {:?}", job.id, job.user_id, err);
+            account_deletion::Entity::update_many()
+                .col_expr(account_deletion::Column::LastError, Expr::value(format!("{:?}", err)))
+                .col_expr(account_deletion::Column::UpdatedAt, Expr::value(chrono::Utc::now()))
+                .filter(account_deletion::Column::Id.eq(job.id))
+                .exec(&state.db)
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Every step can run again: objects go from the store before their rows, and the users row goes last.
+async fn delete_account(state: &AppState, job: &account_deletion::Model) -> Result<(), AppError> {
+    tracing::info!("Deleting the account of user {} ({})", job.user_id, job.id);
+
+    while delete_batch(state, job).await? > 0 {}
+
+    let pending = pending_upload::Entity::find()
+        .filter(pending_upload::Column::OwnerId.eq(job.user_id))
+        .all(&state.db)
+        .await?;
+    for upload in pending {
+        cleanup::remove_pending_upload(state, upload).await?;
+    }
+    let presigned = presigned_upload::Entity::find()
+        .filter(
+            Condition::any()
+                .add(presigned_upload::Column::UserId.eq(job.user_id))
+                .add(presigned_upload::Column::OwnerId.eq(job.user_id)),
+        )
+        .all(&state.db)
+        .await?;
+    for upload in presigned {
+        cleanup::remove_presigned_upload(state, upload).await?;
+    }
+    let multipart = multipart_upload::Entity::find()
+        .filter(multipart_upload::Column::UserId.eq(job.user_id))
+        .all(&state.db)
+        .await?;
+    for upload in multipart {
+        cleanup::remove_multipart_upload(state, upload).await?;
+    }
+    let archives = export::Entity::find()
+        .filter(export::Column::UserId.eq(job.user_id))
+        .all(&state.db)
+        .await?;
+    for archive in archives {
+        exports::remove(state, archive).await?;
+    }
+
+    // keys, buckets, grants, shares, links and the change feed go with the users row
+    transaction::retry(|| async move {
+        let txn = state.db.begin().await?;
+        user::Entity::delete_by_id(job.user_id).exec(&txn).await?;
+        account_deletion::Entity::update_many()
+            .col_expr(account_deletion::Column::LastError, Expr::value(None::<String>))
+            .col_expr(account_deletion::Column::UpdatedAt, Expr::value(chrono::Utc::now()))
+            .col_expr(account_deletion::Column::CompletedAt, Expr::value(chrono::Utc::now()))
+            .filter(account_deletion::Column::Id.eq(job.id))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(())
+    })
+    .await?;
+
+    tracing::info!("Deleted the account of user {} ({})", job.user_id, job.id);
+    Ok(())
+}
+
+/// Removes the next batch of versions of the user, from the store then from the files table.
+async fn delete_batch(state: &AppState, job: &account_deletion::Model) -> Result<usize, AppError> {
+    let batch = file::Entity::find()
+        .filter(file::Column::UserId.eq(job.user_id))
+        .order_by_asc(file::Column::Id)
+        .limit(BATCH_SIZE)
+        .all(&state.db)
+        .await?;
+    if batch.is_empty() {
+        return Ok(0);
+    }
+
+    let versions: Vec<(String, String)> = batch
+        .iter()
+        .map(|f| (f.file_key.to_string(), f.s3_version_id.clone()))
+        .collect();
+    let output = state.store_client.delete_many(&versions).await?;
+    let failures = failed_deletions(&output);
+    if let Some(first) = failures.first() {
+        return Err(AppError::InternalError(format!(
+            "{} objects could not be deleted from the store, first one: {}",
+            failures.len(),
+            first
+        )));
+    }
+
+    let ids: Vec<Uuid> = batch.iter().map(|f| f.id).collect();
+    let bytes: i64 = batch.iter().map(|f| f.content_size).sum();
+    let (ids, count) = (&ids, batch.len() as i64);
+    transaction::retry(|| async move {
+        let txn = state.db.begin().await?;
+        file::Entity::delete_many()
+            .filter(file::Column::Id.is_in(ids.iter().copied()))
+            .exec(&txn)
+            .await?;
+        account_deletion::Entity::update_many()
+            .col_expr(
+                account_deletion::Column::VersionsDeleted,
+                Expr::col(account_deletion::Column::VersionsDeleted).add(count),
+            )
+            .col_expr(
+                account_deletion::Column::BytesDeleted,
+                Expr::col(account_deletion::Column::BytesDeleted).add(bytes),
+            )
+            .col_expr(account_deletion::Column::UpdatedAt, Expr::value(chrono::Utc::now()))
+            .filter(account_deletion::Column::Id.eq(job.id))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(())
+    })
+    .await?;
+
+    Ok(batch.len())
+}
+
+/// Keys the store failed to delete, versions already gone count as deleted.
+fn failed_deletions(output: &DeleteObjectsOutput) -> Vec<String> {
+    output
+        .errors()
+        .iter()
+        .filter(|err| !matches!(err.code(), Some("NoSuchKey" | "NoSuchVersion")))
+        .map(|err| {
+            format!(
+                "{}:{} ({})",
+                err.key().unwrap_or_default(),
+                err.version_id().unwrap_or_default(),
+                err.code().unwrap_or("unknown error")
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_s3::types::Error as DeleteError;
+
+    #[test]
+    fn failed_deletions_ignores_versions_already_gone() {
+        let output = DeleteObjectsOutput::builder()
+            .errors(DeleteError::builder().key("a").version_id("v1").code("NoSuchVersion").build())
+            .errors(DeleteError::builder().key("b").version_id("v2").code("AccessDenied").build())
+            .build();
+
+        assert_eq!(failed_deletions(&output), vec!["b:v2 (AccessDenied)"]);
+    }
+}
@@ -0,0 +1,45 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+/// Deletion of a user and of everything they stored, carried on by the deletion worker until `completed_at` is set.
+///
+/// No foreign key: the row outlives the user and serves as the receipt of the deletion.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "account_deletions")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(indexed)]
+    pub user_id: Uuid,
+    pub requested_by: Uuid,
+    pub versions_deleted: i64,
+    pub bytes_deleted: i64,
+    /// Last failure of the worker, the deletion is resumed from where it stopped.
+    pub last_error: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    pub completed_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl ActiveModel {
+    pub fn new(user_id: Uuid, requested_by: Uuid) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            id: Set(Uuid::now_v7()),
+            user_id: Set(user_id),
+            requested_by: Set(requested_by),
+            versions_deleted: Set(0),
+            bytes_deleted: Set(0),
+            last_error: Set(None),
+            created_at: Set(now.into()),
+            updated_at: Set(now.into()),
+            completed_at: Set(None),
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "api_keys")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(indexed)]
+    pub user_id: Uuid,
+    pub name: String,
+    #[sea_orm(unique)]
+    pub key_hash: String,
+    pub scopes: String,
+    pub path_prefix: Option<String>,
+    pub expires_at: Option<DateTimeWithTimeZone>,
+    pub last_used_at: Option<DateTimeWithTimeZone>,
+    pub created_at: DateTimeWithTimeZone,
+    pub revoked_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::UserId",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl ActiveModel {
+    pub fn new(
+        user_id: Uuid,
+        name: String,
+        key_hash: String,
+        scopes: String,
+        path_prefix: Option<String>,
+        expires_at: Option<DateTimeWithTimeZone>,
+    ) -> Self {
+        Self {
+            id: Set(Uuid::now_v7()),
+            user_id: Set(user_id),
+            name: Set(name),
+            key_hash: Set(key_hash),
+            scopes: Set(scopes),
+            path_prefix: Set(path_prefix),
+            expires_at: Set(expires_at),
+            last_used_at: Set(None),
+            created_at: Set(chrono::Utc::now().into()),
+            revoked_at: Set(None),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// One object operation, rows are only ever inserted (and purged past the retention).
+///
+/// No foreign keys: the trail outlives the users, buckets and files it mentions.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "audit_events")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(indexed)]
+    pub occurred_at: DateTimeWithTimeZone,
+    pub request_id: String,
+    /// `get`, `head`, `put`, `delete` or `post`
+    pub action: String,
+    /// Request path, also set when the operation failed before reaching an object.
+    pub path: String,
+    /// Authenticated caller, empty for anonymous or rejected requests.
+    pub actor_id: Option<Uuid>,
+    /// Owner of the namespace the object belongs to.
+    pub owner_id: Option<Uuid>,
+    pub bucket_id: Option<Uuid>,
+    pub file_path: Option<String>,
+    pub version_id: Option<String>,
+    pub bytes: Option<i64>,
+    pub client_ip: Option<String>,
+    pub user_agent: Option<String>,
+    pub status: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
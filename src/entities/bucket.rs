@@ -0,0 +1,64 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+/// Named container of objects with its own settings, objects outside of any bucket are in the owner's default bucket.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "buckets")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    /// Globally unique, so public buckets can be addressed without knowing their owner.
+    #[sea_orm(unique)]
+    pub name: String,
+    #[sea_orm(indexed)]
+    pub owner_id: Uuid,
+    /// When off, writing a key replaces its previous version instead of keeping it.
+    pub versioning: bool,
+    /// Versions written in the bucket cannot be deleted for this many days.
+    pub default_retention_days: Option<i32>,
+    /// Anyone can read the objects of a public bucket, without credentials.
+    pub public: bool,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::OwnerId",
+        to = "super::user::Column::UserId",
+        on_delete = "Cascade"
+    )]
+    Owner,
+    #[sea_orm(has_many = "super::file::Entity")]
+    Files,
+}
+
+impl Related<super::file::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Files.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl ActiveModel {
+    pub fn new(
+        name: String,
+        owner_id: Uuid,
+        versioning: bool,
+        default_retention_days: Option<i32>,
+        public: bool,
+    ) -> Self {
+        Self {
+            id: Set(Uuid::now_v7()),
+            name: Set(name),
+            owner_id: Set(owner_id),
+            versioning: Set(versioning),
+            default_retention_days: Set(default_retention_days),
+            public: Set(public),
+            created_at: Set(chrono::Utc::now().into()),
+        }
+    }
+}
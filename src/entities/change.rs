@@ -0,0 +1,66 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+/// Entry of the change feed of a namespace, written in the transaction of the `files` mutation.
+///
+/// `seq` increases by one for each change of the owner, in commit order.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "changes")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub owner_id: Uuid,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub seq: i64,
+    /// `create`, `delete` or `move`
+    pub kind: String,
+    pub bucket_id: Option<Uuid>,
+    pub file_path: String,
+    /// Previous key of a moved object.
+    pub from_path: Option<String>,
+    pub version_id: String,
+    pub content_size: i64,
+    pub actor_id: Uuid,
+    pub occurred_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::OwnerId",
+        to = "super::user::Column::UserId",
+        on_delete = "Cascade"
+    )]
+    Owner,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl ActiveModel {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        owner_id: Uuid,
+        seq: i64,
+        kind: String,
+        bucket_id: Option<Uuid>,
+        file_path: String,
+        from_path: Option<String>,
+        version_id: String,
+        content_size: i64,
+        actor_id: Uuid,
+    ) -> Self {
+        Self {
+            owner_id: Set(owner_id),
+            seq: Set(seq),
+            kind: Set(kind),
+            bucket_id: Set(bucket_id),
+            file_path: Set(file_path),
+            from_path: Set(from_path),
+            version_id: Set(version_id),
+            content_size: Set(content_size),
+            actor_id: Set(actor_id),
+            occurred_at: Set(chrono::Utc::now().into()),
+        }
+    }
+}
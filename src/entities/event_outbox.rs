@@ -0,0 +1,42 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+/// Event waiting for (or done with) its webhook delivery.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "event_outbox")]
+pub struct Model {
+    /// Same as the id of the event.
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub payload: Json,
+    pub attempts: i32,
+    #[sea_orm(indexed)]
+    pub next_attempt_at: DateTimeWithTimeZone,
+    pub delivered_at: Option<DateTimeWithTimeZone>,
+    /// Set once the delivery is given up.
+    pub failed_at: Option<DateTimeWithTimeZone>,
+    pub last_error: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl ActiveModel {
+    pub fn new(id: Uuid, payload: Json) -> Self {
+        let now = chrono::Utc::now();
+        Self {
+            id: Set(id),
+            payload: Set(payload),
+            attempts: Set(0),
+            next_attempt_at: Set(now.into()),
+            delivered_at: Set(None),
+            failed_at: Set(None),
+            last_error: Set(None),
+            created_at: Set(now.into()),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+/// Archive of the data of a user, built in the store by the export worker.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "exports")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(indexed)]
+    pub user_id: Uuid,
+    pub requested_by: Uuid,
+    /// Older versions are included too, not only the latest one of each key.
+    pub all_versions: bool,
+    /// Key of the archive in the store.
+    pub object_key: String,
+    /// Multipart upload being written, aborted when a restart interrupts the export.
+    pub backend_upload_id: Option<String>,
+    pub object_version_id: Option<String>,
+    pub size_bytes: Option<i64>,
+    pub file_count: Option<i64>,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub created_at: DateTimeWithTimeZone,
+    pub updated_at: DateTimeWithTimeZone,
+    pub completed_at: Option<DateTimeWithTimeZone>,
+    /// Set once the export is given up.
+    pub failed_at: Option<DateTimeWithTimeZone>,
+    /// The archive is removed from the store after this date.
+    pub expires_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl ActiveModel {
+    pub fn new(user_id: Uuid, requested_by: Uuid, all_versions: bool) -> Self {
+        let id = Uuid::now_v7();
+        let now = chrono::Utc::now();
+        Self {
+            id: Set(id),
+            user_id: Set(user_id),
+            requested_by: Set(requested_by),
+            all_versions: Set(all_versions),
+            object_key: Set(format!("exports/{}.tar", id)),
+            backend_upload_id: Set(None),
+            object_version_id: Set(None),
+            size_bytes: Set(None),
+            file_count: Set(None),
+            attempts: Set(0),
+            last_error: Set(None),
+            created_at: Set(now.into()),
+            updated_at: Set(now.into()),
+            completed_at: Set(None),
+            failed_at: Set(None),
+            expires_at: Set(None),
+        }
+    }
+}
@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub id: Uuid,
+    #[sea_orm(indexed)]
     pub file_key: Uuid,
     #[sea_orm(indexed)]
     pub user_id: Uuid,
@@ -17,6 +18,20 @@ pub struct Model {
     pub s3_version_id: String,
     pub is_latest: bool,
     pub added_at: DateTimeWithTimeZone,
+    /// User who wrote this version, differs from `user_id` for writes made through a grant.
+    pub uploaded_by: Option<Uuid>,
+    /// Bucket holding this version, the owner's default bucket when empty.
+    #[sea_orm(indexed)]
+    pub bucket_id: Option<Uuid>,
+    /// The version cannot be deleted before this date.
+    pub retain_until: Option<DateTimeWithTimeZone>,
+    /// Hex SHA-256 of the content, computed when it was written, or at its first scrub.
+    pub checksum_sha256: Option<String>,
+    /// Last time the scrubber read the content back and found the checksum.
+    #[sea_orm(indexed)]
+    pub verified_at: Option<DateTimeWithTimeZone>,
+    /// Set once the content read back no longer matches the checksum.
+    pub corrupted_at: Option<DateTimeWithTimeZone>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -28,6 +43,13 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     User,
+    #[sea_orm(
+        belongs_to = "super::bucket::Entity",
+        from = "Column::BucketId",
+        to = "super::bucket::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Bucket,
 }
 
 impl Related<super::user::Entity> for Entity {
@@ -36,6 +58,12 @@ impl Related<super::user::Entity> for Entity {
     }
 }
 
+impl Related<super::bucket::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Bucket.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
 
 impl ActiveModel {
@@ -59,6 +87,12 @@ impl ActiveModel {
             s3_version_id: Set(s3_version_id),
             is_latest: Set(true),
             added_at: Set(chrono::Utc::now().into()),
+            uploaded_by: Set(Some(user_id)),
+            bucket_id: Set(None),
+            retain_until: Set(None),
+            checksum_sha256: Set(None),
+            verified_at: Set(None),
+            corrupted_at: Set(None),
         }
     }
 }
\ No newline at end of file
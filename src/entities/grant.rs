@@ -0,0 +1,63 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+/// Access given by an owner to another user on one key, or on every key under a prefix ending with `/`,
+/// of one of their buckets.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "grants")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(indexed)]
+    pub owner_id: Uuid,
+    #[sea_orm(indexed)]
+    pub grantee_id: Uuid,
+    /// `None` for the default bucket of the owner.
+    pub bucket_id: Option<Uuid>,
+    pub prefix: String,
+    /// `read` or `read_write`
+    pub permission: String,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::OwnerId",
+        to = "super::user::Column::UserId",
+        on_delete = "Cascade"
+    )]
+    Owner,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::GranteeId",
+        to = "super::user::Column::UserId",
+        on_delete = "Cascade"
+    )]
+    Grantee,
+    #[sea_orm(
+        belongs_to = "super::bucket::Entity",
+        from = "Column::BucketId",
+        to = "super::bucket::Column::Id",
+        on_delete = "Cascade"
+    )]
+    Bucket,
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl ActiveModel {
+    pub fn new(owner_id: Uuid, grantee_id: Uuid, bucket_id: Option<Uuid>, prefix: String, permission: String) -> Self {
+        Self {
+            id: Set(Uuid::now_v7()),
+            owner_id: Set(owner_id),
+            grantee_id: Set(grantee_id),
+            bucket_id: Set(bucket_id),
+            prefix: Set(prefix),
+            permission: Set(permission),
+            created_at: Set(chrono::Utc::now().into()),
+        }
+    }
+}
@@ -1,2 +1,18 @@
 pub mod user;
-pub mod file;
\ No newline at end of file
+pub mod file;
+pub mod api_key;
+pub mod s3_credential;
+pub mod multipart_upload;
+pub mod presigned_upload;
+pub mod share;
+pub mod upload_link;pub mod grant;
+pub mod organization;
+pub mod org_member;
+pub mod bucket;
+pub mod audit_event;
+pub mod event_outbox;
+pub mod change;
+pub mod pending_upload;
+
+pub mod account_deletion;
+pub mod export;
\ No newline at end of file
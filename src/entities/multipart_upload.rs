@@ -0,0 +1,57 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+/// Multipart upload started through the S3 front end, `id` is the upload id given to clients.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "multipart_uploads")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(indexed)]
+    pub user_id: Uuid,
+    pub file_path: String,
+    pub file_key: Uuid,
+    pub backend_upload_id: String,
+    pub content_type: String,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::UserId",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl ActiveModel {
+    pub fn new(
+        user_id: Uuid,
+        file_path: String,
+        file_key: Uuid,
+        backend_upload_id: String,
+        content_type: String,
+    ) -> Self {
+        Self {
+            id: Set(Uuid::now_v7()),
+            user_id: Set(user_id),
+            file_path: Set(file_path),
+            file_key: Set(file_key),
+            backend_upload_id: Set(backend_upload_id),
+            content_type: Set(content_type),
+            created_at: Set(chrono::Utc::now().into()),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+/// Team owning a namespace of its own.
+///
+/// Its id is also a `users` row holding the objects, quota and usage of the organization.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "organizations")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    pub name: String,
+    pub created_by: Uuid,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::Id",
+        to = "super::user::Column::UserId",
+        on_delete = "Cascade"
+    )]
+    Namespace,
+    #[sea_orm(has_many = "super::org_member::Entity")]
+    Members,
+}
+
+impl Related<super::org_member::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Members.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl ActiveModel {
+    pub fn new(id: Uuid, name: String, created_by: Uuid) -> Self {
+        Self {
+            id: Set(id),
+            name: Set(name),
+            created_by: Set(created_by),
+            created_at: Set(chrono::Utc::now().into()),
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+/// Object being written to the store by a PUT, removed in the transaction recording its file version.
+///
+/// A row left behind means the upload or its commit failed, the sweeper then removes the object.
+/// No foreign key: the object must be cleaned up even once its owner is gone.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "pending_uploads")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub file_key: Uuid,
+    pub owner_id: Uuid,
+    pub file_path: String,
+    #[sea_orm(indexed)]
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl ActiveModel {
+    pub fn new(file_key: Uuid, owner_id: Uuid, file_path: String) -> Self {
+        Self {
+            file_key: Set(file_key),
+            owner_id: Set(owner_id),
+            file_path: Set(file_path),
+            created_at: Set(chrono::Utc::now().into()),
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+/// Upload handed out as a presigned PUT URL, waiting for its commit call to become a file version.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "presigned_uploads")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(indexed)]
+    pub user_id: Uuid,
+    /// Organization or user whose namespace receives the object, the uploader's own when empty.
+    pub owner_id: Option<Uuid>,
+    pub file_path: String,
+    pub file_key: Uuid,
+    pub content_type: String,
+    pub expires_at: DateTimeWithTimeZone,
+    pub created_at: DateTimeWithTimeZone,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::UserId",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl ActiveModel {
+    pub fn new(
+        user_id: Uuid,
+        file_path: String,
+        file_key: Uuid,
+        content_type: String,
+        expires_at: DateTimeWithTimeZone,
+    ) -> Self {
+        Self {
+            id: Set(Uuid::now_v7()),
+            user_id: Set(user_id),
+            owner_id: Set(None),
+            file_path: Set(file_path),
+            file_key: Set(file_key),
+            content_type: Set(content_type),
+            expires_at: Set(expires_at),
+            created_at: Set(chrono::Utc::now().into()),
+        }
+    }
+}
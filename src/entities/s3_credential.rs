@@ -0,0 +1,64 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+/// Access keys of the S3 compatible front end. SigV4 needs the secret itself to verify
+/// signatures, so unlike API keys it cannot be stored hashed.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "s3_credentials")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub access_key_id: String,
+    #[sea_orm(indexed)]
+    pub user_id: Uuid,
+    pub name: String,
+    pub secret_access_key: String,
+    pub scopes: String,
+    pub path_prefix: Option<String>,
+    pub expires_at: Option<DateTimeWithTimeZone>,
+    pub created_at: DateTimeWithTimeZone,
+    pub revoked_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::UserId",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl ActiveModel {
+    pub fn new(
+        access_key_id: String,
+        user_id: Uuid,
+        name: String,
+        secret_access_key: String,
+        scopes: String,
+        path_prefix: Option<String>,
+        expires_at: Option<DateTimeWithTimeZone>,
+    ) -> Self {
+        Self {
+            access_key_id: Set(access_key_id),
+            user_id: Set(user_id),
+            name: Set(name),
+            secret_access_key: Set(secret_access_key),
+            scopes: Set(scopes),
+            path_prefix: Set(path_prefix),
+            expires_at: Set(expires_at),
+            created_at: Set(chrono::Utc::now().into()),
+            revoked_at: Set(None),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+/// Public link to download one object without a Rose account.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "shares")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(unique)]
+    pub token: String,
+    #[sea_orm(indexed)]
+    pub user_id: Uuid,
+    pub file_path: String,
+    /// Pinned version, the link follows the latest version when empty.
+    pub version_id: Option<String>,
+    pub password_hash: Option<String>,
+    pub expires_at: Option<DateTimeWithTimeZone>,
+    pub max_downloads: Option<i32>,
+    pub download_count: i32,
+    pub created_at: DateTimeWithTimeZone,
+    pub revoked_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::UserId",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl ActiveModel {
+    pub fn new(
+        token: String,
+        user_id: Uuid,
+        file_path: String,
+        version_id: Option<String>,
+        password_hash: Option<String>,
+        expires_at: Option<DateTimeWithTimeZone>,
+        max_downloads: Option<i32>,
+    ) -> Self {
+        Self {
+            id: Set(Uuid::now_v7()),
+            token: Set(token),
+            user_id: Set(user_id),
+            file_path: Set(file_path),
+            version_id: Set(version_id),
+            password_hash: Set(password_hash),
+            expires_at: Set(expires_at),
+            max_downloads: Set(max_downloads),
+            download_count: Set(0),
+            created_at: Set(chrono::Utc::now().into()),
+            revoked_at: Set(None),
+        }
+    }
+}
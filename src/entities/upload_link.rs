@@ -0,0 +1,73 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+/// Public "file request" link: anyone holding the token can upload new files under `prefix`.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "upload_links")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub id: Uuid,
+    #[sea_orm(unique)]
+    pub token: String,
+    #[sea_orm(indexed)]
+    pub user_id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub max_file_size: Option<i64>,
+    /// Comma separated content types, `image/*` style wildcards allowed.
+    pub content_types: Option<String>,
+    pub max_files: Option<i32>,
+    pub file_count: i32,
+    pub expires_at: Option<DateTimeWithTimeZone>,
+    pub created_at: DateTimeWithTimeZone,
+    pub revoked_at: Option<DateTimeWithTimeZone>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::UserId",
+        on_delete = "Cascade"
+    )]
+    User,
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl ActiveModel {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        token: String,
+        user_id: Uuid,
+        name: String,
+        prefix: String,
+        max_file_size: Option<i64>,
+        content_types: Option<String>,
+        max_files: Option<i32>,
+        expires_at: Option<DateTimeWithTimeZone>,
+    ) -> Self {
+        Self {
+            id: Set(Uuid::now_v7()),
+            token: Set(token),
+            user_id: Set(user_id),
+            name: Set(name),
+            prefix: Set(prefix),
+            max_file_size: Set(max_file_size),
+            content_types: Set(content_types),
+            max_files: Set(max_files),
+            file_count: Set(0),
+            expires_at: Set(expires_at),
+            created_at: Set(chrono::Utc::now().into()),
+            revoked_at: Set(None),
+        }
+    }
+}
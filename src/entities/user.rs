@@ -8,8 +8,15 @@ pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
     pub user_id: Uuid,
     pub total_space_used: i64,
+    /// Maximum of `total_space_used`, unlimited when empty.
+    pub quota_bytes: Option<i64>,
     pub updated_at: DateTimeWithTimeZone,
     pub last_auto_sync_at: Option<DateTimeWithTimeZone>,
+    /// Sequence number of the last entry of the change feed of the namespace.
+    pub change_seq: i64,
+    pub created_at: DateTimeWithTimeZone,
+    /// Disabled users are refused on every request, their data is kept.
+    pub disabled_at: Option<DateTimeWithTimeZone>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -30,12 +37,17 @@ impl ActiveModel {
     pub fn new(
         user_id: Uuid,
         total_space_used: i64,
+        quota_bytes: Option<i64>,
     ) -> Self {
         Self {
             user_id: Set(user_id),
             total_space_used: Set(total_space_used),
+            quota_bytes: Set(quota_bytes),
             updated_at: Set(chrono::Utc::now().into()),
             last_auto_sync_at: Set(None),
+            change_seq: Set(0),
+            created_at: Set(chrono::Utc::now().into()),
+            disabled_at: Set(None),
         }
     }
 }
\ No newline at end of file
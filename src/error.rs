@@ -4,10 +4,16 @@ use axum::{
     Json,
 };
 use aws_sdk_s3::error::SdkError;
+use aws_sdk_s3::operation::abort_multipart_upload::AbortMultipartUploadError;
+use aws_sdk_s3::operation::complete_multipart_upload::CompleteMultipartUploadError;
+use aws_sdk_s3::operation::create_multipart_upload::CreateMultipartUploadError;
 use aws_sdk_s3::operation::delete_object::DeleteObjectError;
+use aws_sdk_s3::operation::delete_objects::DeleteObjectsError;
 use aws_sdk_s3::operation::put_object::PutObjectError;
 use aws_sdk_s3::operation::get_object::GetObjectError;
 use aws_sdk_s3::operation::head_object::HeadObjectError;
+use aws_sdk_s3::operation::list_object_versions::ListObjectVersionsError;
+use aws_sdk_s3::operation::upload_part::UploadPartError;
 use serde_json::json;
 use tracing::error;
 
@@ -15,9 +21,16 @@ use tracing::error;
 pub enum AppError {
     // Client errors (4xx)
     BadRequest(String),
+    Unauthorized(String),
+    Forbidden(String),
     NotFound(String),
+    Conflict(String),
+    QuotaExceeded(String),
+    TooLarge(String),
 
     // Server errors (5xx)
+    /// A transaction lost against a concurrent one, retried by `transaction::retry`.
+    Contention(String),
     DatabaseError(String),
     InternalError(String),
 }
@@ -26,8 +39,17 @@ impl IntoResponse for AppError {
     fn into_response(self) -> Response {
         let (status, message) = match self {
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            AppError::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
+            AppError::Forbidden(msg) => (StatusCode::FORBIDDEN, msg),
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
+            AppError::Conflict(msg) => (StatusCode::CONFLICT, msg),
+            AppError::QuotaExceeded(msg) => (StatusCode::INSUFFICIENT_STORAGE, msg),
+            AppError::TooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg),
 
+            AppError::Contention(err) => {
+                error!("Transaction contention: {:?}", err);
+                (StatusCode::SERVICE_UNAVAILABLE, "Too many concurrent changes, please retry".to_string())
+            }
             AppError::DatabaseError(err) => {
                 error!("Database error: {:?}", err);
                 (StatusCode::INTERNAL_SERVER_ERROR, format!("Database error {}", err))
@@ -86,6 +108,56 @@ impl From<SdkError<DeleteObjectError>> for AppError {
     }
 }
 
+impl From<SdkError<DeleteObjectsError>> for AppError {
+    fn from(err: SdkError<DeleteObjectsError>) -> Self {
+        tracing::error!("S3 Delete Objects Error: {:?}", err);
+        AppError::InternalError("Failed to delete objects from storage".to_string())
+    }
+}
+
+impl From<SdkError<ListObjectVersionsError>> for AppError {
+    fn from(err: SdkError<ListObjectVersionsError>) -> Self {
+        tracing::error!("S3 List Object Versions Error: {:?}", err);
+        AppError::InternalError("Failed to list objects of the storage".to_string())
+    }
+}
+
+// aws multipart upload error mappers
+impl From<SdkError<CreateMultipartUploadError>> for AppError {
+    fn from(err: SdkError<CreateMultipartUploadError>) -> Self {
+        tracing::error!("S3 Create Multipart Upload Error: {:?}", err);
+        AppError::InternalError("Failed to start multipart upload".to_string())
+    }
+}
+
+impl From<SdkError<UploadPartError>> for AppError {
+    fn from(err: SdkError<UploadPartError>) -> Self {
+        tracing::error!("S3 Upload Part Error: {:?}", err);
+        AppError::InternalError("Failed to upload part to storage".to_string())
+    }
+}
+
+impl From<SdkError<CompleteMultipartUploadError>> for AppError {
+    fn from(err: SdkError<CompleteMultipartUploadError>) -> Self {
+        match err {
+            SdkError::ServiceError(ref e) if e.raw().status().as_u16() == 400 => {
+                AppError::BadRequest("Invalid multipart upload parts".to_string())
+            }
+            _ => {
+                tracing::error!("S3 Complete Multipart Upload Error: {:?}", err);
+                AppError::InternalError("Failed to complete multipart upload".to_string())
+            }
+        }
+    }
+}
+
+impl From<SdkError<AbortMultipartUploadError>> for AppError {
+    fn from(err: SdkError<AbortMultipartUploadError>) -> Self {
+        tracing::error!("S3 Abort Multipart Upload Error: {:?}", err);
+        AppError::InternalError("Failed to abort multipart upload".to_string())
+    }
+}
+
 // anyhow error mapping
 impl From<anyhow::Error> for AppError {
     fn from(err: anyhow::Error) -> Self {
@@ -96,6 +168,9 @@ impl From<anyhow::Error> for AppError {
 // sea_orm db error mapping
 impl From<sea_orm::DbErr> for AppError {
     fn from(err: sea_orm::DbErr) -> Self {
+        if crate::transaction::is_retryable(&err) {
+            return AppError::Contention(err.to_string());
+        }
         AppError::DatabaseError(err.to_string())
     }
 }
\ No newline at end of file
@@ -0,0 +1,59 @@
+use futures::future::BoxFuture;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use super::{EventSink, ObjectEvent};
+
+/// Appends events as newline-delimited JSON, for local testing and simple pipelines.
+pub struct FileSink {
+    file: Mutex<File>,
+}
+
+impl FileSink {
+    pub async fn open(path: &str) -> anyhow::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path).await?;
+        Ok(Self { file: Mutex::new(file) })
+    }
+}
+
+impl EventSink for FileSink {
+    fn name(&self) -> &'static str {
+        "file"
+    }
+
+    fn publish<'a>(&'a self, event: &'a ObjectEvent) -> BoxFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let mut line = serde_json::to_vec(event)?;
+            line.push(b'\n');
+            // one write per line under the lock, lines of concurrent events never interleave
+            let mut file = self.file.lock().await;
+            file.write_all(&line).await?;
+            file.flush().await?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::tests::sample_event;
+
+    #[tokio::test]
+    async fn file_sink_appends_json_lines() {
+        let path = std::env::temp_dir().join(format!("rose-events-{}.ndjson", uuid::Uuid::now_v7()));
+        let sink = FileSink::open(path.to_str().unwrap()).await.unwrap();
+
+        let first = sample_event();
+        let second = sample_event();
+        sink.publish(&first).await.unwrap();
+        sink.publish(&second).await.unwrap();
+
+        let content = tokio::fs::read_to_string(&path).await.unwrap();
+        let lines: Vec<ObjectEvent> = content.lines().map(|l| serde_json::from_str(l).unwrap()).collect();
+        assert_eq!(lines, vec![first, second]);
+
+        tokio::fs::remove_file(&path).await.unwrap();
+    }
+}
@@ -0,0 +1,60 @@
+use futures::future::BoxFuture;
+use tokio::sync::broadcast;
+
+use super::{EventSink, ObjectEvent};
+
+/// Events kept for slow subscribers before they start missing some.
+const CAPACITY: usize = 1024;
+
+/// Hands events to the streams opened on this instance.
+pub struct LiveSink {
+    sender: broadcast::Sender<ObjectEvent>,
+}
+
+impl LiveSink {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self { sender }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<ObjectEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for LiveSink {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventSink for LiveSink {
+    fn name(&self) -> &'static str {
+        "live"
+    }
+
+    fn publish<'a>(&'a self, event: &'a ObjectEvent) -> BoxFuture<'a, anyhow::Result<()>> {
+        // no subscriber is not an error, the event is simply not wanted
+        let _ = self.sender.send(event.clone());
+        Box::pin(async { Ok(()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::tests::sample_event;
+
+    #[tokio::test]
+    async fn subscribers_receive_events_published_after_they_joined() {
+        let sink = LiveSink::new();
+        sink.publish(&sample_event()).await.unwrap();
+
+        let mut receiver = sink.subscribe();
+        let event = sample_event();
+        sink.publish(&event).await.unwrap();
+
+        assert_eq!(receiver.recv().await.unwrap(), event);
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -0,0 +1,206 @@
+//! Object change events, published after a successful commit to every configured sink.
+//!
+//! Delivery never fails the operation that caused the event: sink errors are logged. Events of the
+//! webhook are staged in its outbox within the transaction of the operation, publishing only wakes
+//! its dispatcher.
+
+pub mod file;
+pub mod live;
+pub mod webhook;
+
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::config::Config;
+use crate::entities::file as file_entity;
+use crate::objects::Namespace;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbErr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventKind {
+    ObjectCreated,
+    ObjectDeleted,
+    VersionRestored,
+    ObjectMoved,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ObjectEvent {
+    /// Unique per event, for consumers to ignore redeliveries.
+    pub id: Uuid,
+    pub kind: EventKind,
+    pub occurred_at: chrono::DateTime<chrono::Utc>,
+    pub owner_id: Uuid,
+    pub actor_id: Uuid,
+    pub bucket_id: Option<Uuid>,
+    pub key: String,
+    /// Previous key of a moved object.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from_key: Option<String>,
+    pub version_id: String,
+    pub content_type: String,
+    pub size: i64,
+}
+
+impl EventKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventKind::ObjectCreated => "ObjectCreated",
+            EventKind::ObjectDeleted => "ObjectDeleted",
+            EventKind::VersionRestored => "VersionRestored",
+            EventKind::ObjectMoved => "ObjectMoved",
+        }
+    }
+}
+
+impl ObjectEvent {
+    pub fn new(kind: EventKind, ns: &Namespace, file: &file_entity::Model) -> Self {
+        Self {
+            id: Uuid::now_v7(),
+            kind,
+            occurred_at: chrono::Utc::now(),
+            owner_id: ns.owner_id,
+            actor_id: ns.actor_id,
+            bucket_id: file.bucket_id,
+            key: file.file_path.clone(),
+            from_key: None,
+            version_id: file.s3_version_id.clone(),
+            content_type: file.content_type.clone(),
+            size: file.content_size,
+        }
+    }
+}
+
+/// Destination of object events.
+pub trait EventSink: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    fn publish<'a>(&'a self, event: &'a ObjectEvent) -> BoxFuture<'a, anyhow::Result<()>>;
+}
+
+/// Fans events out to the live streams and to the sinks set up from the configuration.
+#[derive(Clone)]
+pub struct EventBus {
+    live: Arc<live::LiveSink>,
+    sinks: Vec<Arc<dyn EventSink>>,
+    /// A webhook is configured, its events go through the outbox.
+    outbox: bool,
+}
+
+impl EventBus {
+    pub fn new(mut sinks: Vec<Arc<dyn EventSink>>) -> Self {
+        let live = Arc::new(live::LiveSink::new());
+        sinks.push(live.clone());
+        Self { live, sinks, outbox: false }
+    }
+
+    /// Events published from now on, on this instance.
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<ObjectEvent> {
+        self.live.subscribe()
+    }
+
+    pub async fn from_config(config: &Config, db: &DatabaseConnection) -> anyhow::Result<Self> {
+        let mut sinks: Vec<Arc<dyn EventSink>> = Vec::new();
+        if let Some(ref path) = config.event_file_path {
+            sinks.push(Arc::new(file::FileSink::open(path).await?));
+        }
+        if let Some(ref url) = config.webhook_url {
+            let secret = config
+                .webhook_secret
+                .clone()
+                .ok_or_else(|| anyhow::anyhow!("WEBHOOK_SECRET must be set along WEBHOOK_URL"))?;
+            let sink = webhook::WebhookSink::new(db.clone(), url.clone(), secret);
+            sink.spawn_dispatcher();
+            sinks.push(Arc::new(sink));
+            return Ok(Self { outbox: true, ..Self::new(sinks) });
+        }
+        Ok(Self::new(sinks))
+    }
+
+    /// Records `event` in the webhook outbox, in the transaction of the operation that caused it: a committed
+    /// operation always gets its delivery. Does nothing without a webhook.
+    pub async fn stage<C: ConnectionTrait>(&self, db: &C, event: &ObjectEvent) -> Result<(), DbErr> {
+        if !self.outbox {
+            return Ok(());
+        }
+        webhook::stage(db, event).await
+    }
+
+    pub async fn publish(&self, event: ObjectEvent) {
+        for sink in &self.sinks {
+            if let Err(err) = sink.publish(&event).await {
+                tracing::error!("Failed to publish event {} to {} sink: {:?}", event.id, sink.name(), err);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder(Mutex<Vec<ObjectEvent>>);
+
+    impl EventSink for Recorder {
+        fn name(&self) -> &'static str {
+            "recorder"
+        }
+
+        fn publish<'a>(&'a self, event: &'a ObjectEvent) -> BoxFuture<'a, anyhow::Result<()>> {
+            self.0.lock().unwrap().push(event.clone());
+            Box::pin(async { Ok(()) })
+        }
+    }
+
+    struct Failing;
+
+    impl EventSink for Failing {
+        fn name(&self) -> &'static str {
+            "failing"
+        }
+
+        fn publish<'a>(&'a self, _event: &'a ObjectEvent) -> BoxFuture<'a, anyhow::Result<()>> {
+            Box::pin(async { Err(anyhow::anyhow!("unreachable")) })
+        }
+    }
+
+    pub fn sample_event() -> ObjectEvent {
+        ObjectEvent {
+            id: Uuid::now_v7(),
+            kind: EventKind::ObjectCreated,
+            occurred_at: chrono::Utc::now(),
+            owner_id: Uuid::now_v7(),
+            actor_id: Uuid::now_v7(),
+            bucket_id: None,
+            key: "docs/report.pdf".to_string(),
+            from_key: None,
+            version_id: "v1".to_string(),
+            content_type: "application/pdf".to_string(),
+            size: 42,
+        }
+    }
+
+    #[tokio::test]
+    async fn publish_reaches_every_sink_despite_failures() {
+        let recorder = Arc::new(Recorder::default());
+        let bus = EventBus::new(vec![Arc::new(Failing), recorder.clone()]);
+
+        bus.publish(sample_event()).await;
+
+        assert_eq!(recorder.0.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn event_kind_is_serialized_by_name() {
+        let json = serde_json::to_value(sample_event()).unwrap();
+        assert_eq!(json["kind"], "ObjectCreated");
+        assert_eq!(json["kind"], EventKind::ObjectCreated.as_str());
+        assert_eq!(json["key"], "docs/report.pdf");
+        assert!(json.get("from_key").is_none());
+    }
+}
@@ -0,0 +1,192 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use hmac::{Hmac, Mac};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+    QueryOrder, QuerySelect, Set,
+};
+use sha2::Sha256;
+use tokio::sync::Notify;
+
+use super::{EventSink, ObjectEvent};
+use crate::entities::event_outbox;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+const BATCH_SIZE: u64 = 50;
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// Attempts before a delivery is marked failed, about a day with the backoff below.
+const MAX_ATTEMPTS: i32 = 30;
+const MAX_BACKOFF_SECS: i64 = 3600;
+
+pub const EVENT_ID_HEADER: &str = "x-rose-event-id";
+pub const TIMESTAMP_HEADER: &str = "x-rose-timestamp";
+pub const SIGNATURE_HEADER: &str = "x-rose-signature";
+
+/// Posts events to an HTTP endpoint, at least once.
+///
+/// Events first go to the `event_outbox` table along with the operation (see `stage`), a background
+/// dispatcher delivers them and retries failures with an exponential backoff, so deliveries survive
+/// crashes, restarts and receiver outages.
+pub struct WebhookSink {
+    db: DatabaseConnection,
+    url: String,
+    secret: String,
+    client: reqwest::Client,
+    wake: Arc<Notify>,
+}
+
+/// Adds `event` to the outbox, meant to run in the transaction of the operation it describes.
+pub async fn stage<C: ConnectionTrait>(db: &C, event: &ObjectEvent) -> Result<(), DbErr> {
+    let payload = serde_json::to_value(event).map_err(|err| DbErr::Custom(err.to_string()))?;
+    event_outbox::ActiveModel::new(event.id, payload).insert(db).await?;
+    Ok(())
+}
+
+/// `sha256=<hex>` HMAC of `<timestamp>.<body>`, the timestamp lets receivers reject replays.
+pub fn signature(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Delay before the next attempt once `attempts` have failed.
+fn backoff(attempts: i32) -> chrono::Duration {
+    let secs = 2i64.saturating_pow(attempts.clamp(0, 31) as u32).min(MAX_BACKOFF_SECS);
+    chrono::Duration::seconds(secs)
+}
+
+impl WebhookSink {
+    pub fn new(db: DatabaseConnection, url: String, secret: String) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .expect("Failed to build the webhook HTTP client");
+        Self {
+            db,
+            url,
+            secret,
+            client,
+            wake: Arc::new(Notify::new()),
+        }
+    }
+
+    pub fn spawn_dispatcher(&self) {
+        let dispatcher = Self {
+            db: self.db.clone(),
+            url: self.url.clone(),
+            secret: self.secret.clone(),
+            client: self.client.clone(),
+            wake: self.wake.clone(),
+        };
+
+        tokio::spawn(async move {
+            loop {
+                if let Err(err) = dispatcher.deliver_due().await {
+                    tracing::error!("Failed to dispatch webhook events: {:?}", err);
+                }
+                // new events wake the dispatcher, retries are picked up by the next poll
+                let _ = tokio::time::timeout(POLL_INTERVAL, dispatcher.wake.notified()).await;
+            }
+        });
+    }
+
+    async fn deliver_due(&self) -> anyhow::Result<()> {
+        let due = event_outbox::Entity::find()
+            .filter(event_outbox::Column::DeliveredAt.is_null())
+            .filter(event_outbox::Column::FailedAt.is_null())
+            .filter(event_outbox::Column::NextAttemptAt.lte(chrono::Utc::now()))
+            .order_by_asc(event_outbox::Column::NextAttemptAt)
+            .limit(BATCH_SIZE)
+            .all(&self.db)
+            .await?;
+
+        for entry in due {
+            let id = entry.id;
+            let attempts = entry.attempts + 1;
+            let mut active: event_outbox::ActiveModel = entry.clone().into();
+            active.attempts = Set(attempts);
+
+            match self.post(&entry).await {
+                Ok(()) => {
+                    active.delivered_at = Set(Some(chrono::Utc::now().into()));
+                    active.last_error = Set(None);
+                }
+                Err(err) => {
+                    tracing::warn!("Webhook delivery of event {} failed (attempt {}): {}", id, attempts, err);
+                    active.last_error = Set(Some(err.to_string()));
+                    if attempts >= MAX_ATTEMPTS {
+                        tracing::error!("Giving up webhook delivery of event {} after {} attempts", id, attempts);
+                        active.failed_at = Set(Some(chrono::Utc::now().into()));
+                    } else {
+                        active.next_attempt_at = Set((chrono::Utc::now() + backoff(attempts)).into());
+                    }
+                }
+            }
+            active.update(&self.db).await?;
+        }
+        Ok(())
+    }
+
+    async fn post(&self, entry: &event_outbox::Model) -> anyhow::Result<()> {
+        let body = serde_json::to_vec(&entry.payload)?;
+        let timestamp = chrono::Utc::now().timestamp();
+
+        let response = self
+            .client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(EVENT_ID_HEADER, entry.id.to_string())
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(SIGNATURE_HEADER, signature(&self.secret, timestamp, &body))
+            .body(body)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            anyhow::bail!("Webhook answered {}", status);
+        }
+        Ok(())
+    }
+}
+
+impl EventSink for WebhookSink {
+    fn name(&self) -> &'static str {
+        "webhook"
+    }
+
+    /// The event is in the outbox already, committed with its operation.
+    fn publish<'a>(&'a self, _event: &'a ObjectEvent) -> BoxFuture<'a, anyhow::Result<()>> {
+        self.wake.notify_one();
+        Box::pin(async { Ok(()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_covers_timestamp_and_body() {
+        let sig = signature("secret", 1_700_000_000, b"{}");
+        assert!(sig.starts_with("sha256="));
+        assert_eq!(sig.len(), "sha256=".len() + 64);
+        assert_eq!(sig, signature("secret", 1_700_000_000, b"{}"));
+
+        assert_ne!(sig, signature("secret", 1_700_000_001, b"{}"));
+        assert_ne!(sig, signature("secret", 1_700_000_000, b"[]"));
+        assert_ne!(sig, signature("other", 1_700_000_000, b"{}"));
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_an_hour() {
+        assert_eq!(backoff(1), chrono::Duration::seconds(2));
+        assert_eq!(backoff(5), chrono::Duration::seconds(32));
+        assert_eq!(backoff(12), chrono::Duration::seconds(MAX_BACKOFF_SECS));
+        assert_eq!(backoff(MAX_ATTEMPTS), chrono::Duration::seconds(MAX_BACKOFF_SECS));
+    }
+}
@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use sea_orm::{
+    sea_query::Expr, ColumnTrait, Condition, EntityTrait, ModelTrait, QueryFilter, QueryOrder, QuerySelect, UpdateMany,
+};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::archive;
+use crate::entities::{bucket, export, file};
+use crate::error::AppError;
+use crate::storage::MultipartWriter;
+use crate::AppState;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+const MAX_ATTEMPTS: i32 = 5;
+/// Archives are removed from the store this long after they are ready.
+const RETENTION_DAYS: i64 = 7;
+const PAGE_SIZE: u64 = 500;
+/// An export whose worker has not renewed its claim for this long is taken over by another one.
+const LEASE: chrono::Duration = chrono::Duration::minutes(10);
+const RENEW_INTERVAL: Duration = Duration::from_secs(60);
+pub const CONTENT_TYPE: &str = "application/x-tar";
+
+/// Builds the requested exports, resumes the ones a restart interrupted and removes the expired ones.
+pub fn spawn_worker(state: AppState) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(err) = run_pending(&state).await {
+                tracing::error!("Failed to process exports: {:?}", err);
+            }
+        }
+    });
+}
+
+async fn run_pending(state: &AppState) -> Result<(), AppError> {
+    let expired = export::Entity::find()
+        .filter(export::Column::ExpiresAt.lt(chrono::Utc::now()))
+        .all(&state.db)
+        .await?;
+    for job in expired {
+        let id = job.id;
+        if let Err(err) = remove(state, job).await {
+            tracing::error!("Failed to remove expired export {}: {:?}", id, err);
+        }
+    }
+
+    let pending = export::Entity::find()
+        .filter(export::Column::CompletedAt.is_null())
+        .filter(export::Column::FailedAt.is_null())
+        .order_by_asc(export::Column::CreatedAt)
+        .all(&state.db)
+        .await?;
+    for job in pending {
+        if let Err(err) = run(state, &job).await {
+            tracing::error!("Failed to process export {}: {:?}", job.id, err);
+        }
+    }
+    Ok(())
+}
+
+/// Removes the archive of an export from the store, then the export itself.
+pub async fn remove(state: &AppState, job: export::Model) -> Result<(), AppError> {
+    if let Some(ref upload_id) = job.backend_upload_id {
+        let _ = state.store_client.abort_multipart_upload(&job.object_key, upload_id).await;
+    }
+    if job.completed_at.is_some() {
+        state
+            .store_client
+            .delete(&job.object_key, job.object_version_id.as_deref())
+            .await?;
+    }
+    tracing::info!("Removed export {} of user {}", job.id, job.user_id);
+    job.delete(&state.db).await?;
+    Ok(())
+}
+
+/// Builds the export unless another worker holds it, and records a failed attempt.
+async fn run(state: &AppState, job: &export::Model) -> Result<(), AppError> {
+    let writer = MultipartWriter::create(&state.store_client, &job.object_key, CONTENT_TYPE).await?;
+    if !claim(state, job, writer.upload_id()).await? {
+        writer.abort().await;
+        return Ok(());
+    }
+    // left by an interrupted attempt, the export now writes to the new one
+    if let Some(ref upload_id) = job.backend_upload_id {
+        let _ = state.store_client.abort_multipart_upload(&job.object_key, upload_id).await;
+    }
+
+    let upload_id = writer.upload_id().to_string();
+    let Err(err) = build(state, job, writer).await else {
+        return Ok(());
+    };
+    tracing::error!("Export {} of user {} failed: {:?}", job.id, job.user_id, err);
+    let attempts = job.attempts + 1;
+    let mut failed = export::Entity::update_many()
+        .col_expr(export::Column::Attempts, Expr::value(attempts))
+        .col_expr(export::Column::LastError, Expr::value(format!("{:?}", err)))
+        .col_expr(export::Column::BackendUploadId, Expr::value(Option::<String>::None))
+        .col_expr(export::Column::UpdatedAt, Expr::value(chrono::Utc::now()));
+    if attempts >= MAX_ATTEMPTS {
+        failed = failed.col_expr(export::Column::FailedAt, Expr::value(chrono::Utc::now()));
+    }
+    owned_by(failed, job, &upload_id).exec(&state.db).await?;
+    Ok(())
+}
+
+/// Makes `upload_id` the upload of the export, unless another worker got there first. An export is free when
+/// no upload is recorded, or when the worker writing it has not been heard of for `LEASE`.
+async fn claim(state: &AppState, job: &export::Model, upload_id: &str) -> Result<bool, AppError> {
+    let now = chrono::Utc::now();
+    let free = match job.backend_upload_id {
+        Some(ref previous) => Condition::all()
+            .add(export::Column::BackendUploadId.eq(previous.as_str()))
+            .add(export::Column::UpdatedAt.lt(now - LEASE)),
+        None => Condition::all().add(export::Column::BackendUploadId.is_null()),
+    };
+    let claimed = export::Entity::update_many()
+        .col_expr(export::Column::BackendUploadId, Expr::value(upload_id))
+        .col_expr(export::Column::UpdatedAt, Expr::value(now))
+        .filter(export::Column::Id.eq(job.id))
+        .filter(export::Column::CompletedAt.is_null())
+        .filter(export::Column::FailedAt.is_null())
+        .filter(free)
+        .exec(&state.db)
+        .await?;
+    Ok(claimed.rows_affected > 0)
+}
+
+/// Restricts an update to the export while `upload_id` is still its upload.
+fn owned_by(update: UpdateMany<export::Entity>, job: &export::Model, upload_id: &str) -> UpdateMany<export::Entity> {
+    update
+        .filter(export::Column::Id.eq(job.id))
+        .filter(export::Column::BackendUploadId.eq(upload_id))
+}
+
+/// Claim of the worker writing an export, renewed while it writes.
+struct Lease<'a> {
+    job: &'a export::Model,
+    upload_id: String,
+    renewed_at: Instant,
+}
+
+impl Lease<'_> {
+    /// Tells the other workers the export is still being written, fails once one of them took it over.
+    async fn renew(&mut self, state: &AppState) -> Result<(), AppError> {
+        if self.renewed_at.elapsed() < RENEW_INTERVAL {
+            return Ok(());
+        }
+        let update =
+            export::Entity::update_many().col_expr(export::Column::UpdatedAt, Expr::value(chrono::Utc::now()));
+        if owned_by(update, self.job, &self.upload_id).exec(&state.db).await?.rows_affected == 0 {
+            return Err(AppError::Conflict(format!("Export {} was taken over by another worker", self.job.id)));
+        }
+        self.renewed_at = Instant::now();
+        Ok(())
+    }
+}
+
+/// Writes the archive from scratch to the upload of `writer`.
+async fn build(state: &AppState, job: &export::Model, mut writer: MultipartWriter) -> Result<(), AppError> {
+    let mut lease = Lease { job, upload_id: writer.upload_id().to_string(), renewed_at: Instant::now() };
+    let file_count = match write_archive(state, job, &mut writer, &mut lease).await {
+        Ok(count) => count,
+        Err(err) => {
+            writer.abort().await;
+            return Err(err);
+        }
+    };
+    let upload_id = writer.upload_id().to_string();
+    let (version_id, size) = writer.finish().await?;
+
+    let now = chrono::Utc::now();
+    let done = export::Entity::update_many()
+        .col_expr(export::Column::BackendUploadId, Expr::value(Option::<String>::None))
+        .col_expr(export::Column::ObjectVersionId, Expr::value(version_id))
+        .col_expr(export::Column::SizeBytes, Expr::value(size as i64))
+        .col_expr(export::Column::FileCount, Expr::value(file_count))
+        .col_expr(export::Column::LastError, Expr::value(Option::<String>::None))
+        .col_expr(export::Column::UpdatedAt, Expr::value(now))
+        .col_expr(export::Column::CompletedAt, Expr::value(now))
+        .col_expr(export::Column::ExpiresAt, Expr::value(now + chrono::Duration::days(RETENTION_DAYS)));
+    owned_by(done, job, &upload_id).exec(&state.db).await?;
+
+    tracing::info!("Export {} of user {} ready: {} files, {} bytes", job.id, job.user_id, file_count, size);
+    Ok(())
+}
+
+/// Every file of the user, then `manifest.json` describing them, returns the number of files written.
+async fn write_archive(
+    state: &AppState,
+    job: &export::Model,
+    writer: &mut MultipartWriter,
+    lease: &mut Lease<'_>,
+) -> Result<i64, AppError> {
+    let buckets: HashMap<Uuid, String> = bucket::Entity::find()
+        .filter(bucket::Column::OwnerId.eq(job.user_id))
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(|b| (b.id, b.name))
+        .collect();
+
+    let mut manifest = Vec::new();
+    let mut written = 0;
+    let mut after: Option<Uuid> = None;
+    loop {
+        let mut query = file::Entity::find().filter(file::Column::UserId.eq(job.user_id));
+        if !job.all_versions {
+            query = query.filter(file::Column::IsLatest.eq(true));
+        }
+        if let Some(id) = after {
+            query = query.filter(file::Column::Id.gt(id));
+        }
+        let page = query.order_by_asc(file::Column::Id).limit(PAGE_SIZE).all(&state.db).await?;
+        let Some(last) = page.last() else {
+            break;
+        };
+        after = Some(last.id);
+
+        for file in page {
+            let bucket_name = file.bucket_id.map(|id| buckets.get(&id).cloned().unwrap_or_else(|| id.to_string()));
+            let path = archive_path(&file, bucket_name.as_deref());
+            let mut entry = serde_json::to_value(&file).map_err(|err| AppError::InternalError(err.to_string()))?;
+            match write_file(state, writer, lease, &file, &path).await {
+                Ok(()) => {
+                    entry["archive_path"] = json!(path);
+                    written += 1;
+                }
+                // reported in the manifest rather than failing the whole export
+                Err(AppError::NotFound(_)) => {
+                    tracing::error!("Version {} of {} is missing from the store", file.s3_version_id, file.file_path);
+                    entry["archive_path"] = Value::Null;
+                    entry["missing"] = json!(true);
+                }
+                Err(err) => return Err(err),
+            }
+            manifest.push(entry);
+        }
+    }
+
+    let manifest = json!({
+        "export_id": job.id,
+        "user_id": job.user_id,
+        "all_versions": job.all_versions,
+        "exported_at": chrono::Utc::now(),
+        "files": manifest,
+    });
+    let manifest = serde_json::to_vec_pretty(&manifest).map_err(|err| AppError::InternalError(err.to_string()))?;
+    writer
+        .write(&archive::tar_header("manifest.json", manifest.len() as u64, chrono::Utc::now().timestamp())?)
+        .await?;
+    writer.write(&manifest).await?;
+    writer.write(&vec![0; archive::tar_padding(manifest.len() as u64)]).await?;
+    writer.write(&archive::tar_end()).await?;
+
+    Ok(written)
+}
+
+async fn write_file(
+    state: &AppState,
+    writer: &mut MultipartWriter,
+    lease: &mut Lease<'_>,
+    file: &file::Model,
+    path: &str,
+) -> Result<(), AppError> {
+    let output = state
+        .store_client
+        .get(&file.file_key.to_string(), Some(&file.s3_version_id))
+        .await?;
+    let size = output.content_length.map(|len| len as u64).unwrap_or(file.content_size as u64);
+
+    writer.write(&archive::tar_header(path, size, file.added_at.timestamp())?).await?;
+    let mut body = output.body;
+    let mut copied = 0u64;
+    while let Some(chunk) = body.try_next().await.map_err(|err| AppError::InternalError(err.to_string()))? {
+        copied += chunk.len() as u64;
+        writer.write(&chunk).await?;
+        lease.renew(state).await?;
+    }
+    // the header announced `size` bytes, anything else would shift every following entry
+    if copied != size {
+        return Err(AppError::InternalError(format!(
+            "Read {} bytes of {} instead of {}",
+            copied, file.file_path, size
+        )));
+    }
+    writer.write(&vec![0; archive::tar_padding(size)]).await?;
+    Ok(())
+}
+
+/// `objects/<key>` for the default bucket, `buckets/<name>/<key>` for the others, and older versions
+/// under `versions/` followed by the path of their key and their version id.
+fn archive_path(file: &file::Model, bucket_name: Option<&str>) -> String {
+    let root = match bucket_name {
+        Some(name) => format!("buckets/{}", name),
+        None => "objects".to_string(),
+    };
+    let key = file.file_path.trim_start_matches('/');
+    if file.is_latest {
+        format!("{}/{}", root, key)
+    } else {
+        format!("versions/{}/{}/{}", root, key, file.s3_version_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(path: &str, is_latest: bool) -> file::Model {
+        let mut file = file::Model {
+            id: Uuid::now_v7(),
+            file_key: Uuid::now_v7(),
+            user_id: Uuid::now_v7(),
+            file_name: String::new(),
+            file_path: path.to_string(),
+            content_type: "text/plain".to_string(),
+            content_size: 1,
+            s3_version_id: "v1".to_string(),
+            is_latest,
+            added_at: chrono::Utc::now().into(),
+            uploaded_by: None,
+            bucket_id: None,
+            retain_until: None,
+            checksum_sha256: None,
+            verified_at: None,
+            corrupted_at: None,
+        };
+        file.file_name = crate::objects::file_name_from_key(path);
+        file
+    }
+
+    #[test]
+    fn archive_path_separates_buckets_and_versions() {
+        assert_eq!(archive_path(&version("docs/a.txt", true), None), "objects/docs/a.txt");
+        assert_eq!(archive_path(&version("/docs/a.txt", true), Some("photos")), "buckets/photos/docs/a.txt");
+        assert_eq!(
+            archive_path(&version("docs/a.txt", false), Some("photos")),
+            "versions/buckets/photos/docs/a.txt/v1"
+        );
+    }
+}
@@ -0,0 +1,447 @@
+use std::collections::{HashMap, HashSet};
+
+use aws_sdk_s3::operation::list_object_versions::ListObjectVersionsOutput;
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, Select, Set, TransactionTrait,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::changes::{self, ChangeKind};
+use crate::entities::{file, multipart_upload, pending_upload, presigned_upload, user};
+use crate::error::AppError;
+use crate::events::{EventKind, ObjectEvent};
+use crate::objects::{bucket_condition, Namespace};
+use crate::transaction;
+use crate::AppState;
+
+const FILES_PAGE_SIZE: u64 = 1000;
+
+/// Version of an object found in the store.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct StoredVersion {
+    size: i64,
+    last_modified: i64,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct OrphanObject {
+    pub file_key: Uuid,
+    pub version_id: String,
+    pub size: i64,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct MissingObject {
+    pub file_id: Uuid,
+    pub owner_id: Uuid,
+    pub key: String,
+    pub file_key: Uuid,
+    pub version_id: String,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct SizeMismatch {
+    pub file_id: Uuid,
+    pub file_key: Uuid,
+    pub version_id: String,
+    pub recorded: i64,
+    pub stored: i64,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+pub struct UsageMismatch {
+    pub user_id: Uuid,
+    pub recorded: i64,
+    pub actual: i64,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct FsckReport {
+    pub repaired: bool,
+    pub store_versions: u64,
+    pub file_rows: u64,
+    /// Objects of the store not named after a file key, left alone.
+    pub foreign_objects: u64,
+    /// Versions in the store that no file row references.
+    pub orphan_objects: Vec<OrphanObject>,
+    /// File rows whose version is gone from the store, e.g. expired by the lifecycle policy.
+    pub missing_objects: Vec<MissingObject>,
+    pub size_mismatches: Vec<SizeMismatch>,
+    /// Users whose `total_space_used` differs from the size of their file rows.
+    pub usage_mismatches: Vec<UsageMismatch>,
+}
+
+/// Compares the file rows, read page by page, with the listing of the store.
+struct Reconciler {
+    /// Nothing written after this instant is judged, it may be an operation in flight.
+    started_at: i64,
+    repair: bool,
+    store: HashMap<(Uuid, String), StoredVersion>,
+    usage: HashMap<Uuid, i64>,
+    missing: Vec<file::Model>,
+    report: FsckReport,
+}
+
+impl Reconciler {
+    fn new(started_at: i64, repair: bool) -> Self {
+        Self {
+            started_at,
+            repair,
+            store: HashMap::new(),
+            usage: HashMap::new(),
+            missing: Vec::new(),
+            report: FsckReport { repaired: repair, ..Default::default() },
+        }
+    }
+
+    fn add_store_page(&mut self, page: &ListObjectVersionsOutput) {
+        for version in page.versions() {
+            self.report.store_versions += 1;
+            let Some(file_key) = version.key().and_then(|k| Uuid::parse_str(k).ok()) else {
+                self.report.foreign_objects += 1;
+                continue;
+            };
+            let stored = StoredVersion {
+                size: version.size().unwrap_or_default(),
+                last_modified: version.last_modified().map(|t| t.secs()).unwrap_or_default(),
+            };
+            let version_id = version.version_id().unwrap_or("null").to_string();
+            self.store.insert((file_key, version_id), stored);
+        }
+    }
+
+    fn check_row(&mut self, row: file::Model) {
+        self.report.file_rows += 1;
+        let stored = self.store.remove(&(row.file_key, row.s3_version_id.clone()));
+
+        let size = match stored {
+            None if row.added_at.timestamp() < self.started_at => {
+                self.report.missing_objects.push(MissingObject {
+                    file_id: row.id,
+                    owner_id: row.user_id,
+                    key: row.file_path.clone(),
+                    file_key: row.file_key,
+                    version_id: row.s3_version_id.clone(),
+                });
+                let size = if self.repair { 0 } else { row.content_size };
+                self.missing.push(row.clone());
+                size
+            }
+            Some(stored) if stored.size != row.content_size => {
+                self.report.size_mismatches.push(SizeMismatch {
+                    file_id: row.id,
+                    file_key: row.file_key,
+                    version_id: row.s3_version_id.clone(),
+                    recorded: row.content_size,
+                    stored: stored.size,
+                });
+                if self.repair { stored.size } else { row.content_size }
+            }
+            _ => row.content_size,
+        };
+        *self.usage.entry(row.user_id).or_default() += size;
+    }
+
+    /// Versions left in the store once every row has been seen, except uploads still being tracked.
+    fn finish(&mut self, tracked: &HashSet<Uuid>, users: &[user::Model]) {
+        let mut orphans: Vec<OrphanObject> = self
+            .store
+            .drain()
+            .filter(|((file_key, _), stored)| !tracked.contains(file_key) && stored.last_modified < self.started_at)
+            .map(|((file_key, version_id), stored)| OrphanObject { file_key, version_id, size: stored.size })
+            .collect();
+        orphans.sort_by(|a, b| (a.file_key, &a.version_id).cmp(&(b.file_key, &b.version_id)));
+        self.report.orphan_objects = orphans;
+
+        self.report.usage_mismatches = users
+            .iter()
+            .filter_map(|user| {
+                let actual = self.usage.get(&user.user_id).copied().unwrap_or_default();
+                (actual != user.total_space_used).then_some(UsageMismatch {
+                    user_id: user.user_id,
+                    recorded: user.total_space_used,
+                    actual,
+                })
+            })
+            .collect();
+    }
+}
+
+/// Walks the store and the files table and reports (and with `repair`, fixes) the differences.
+///
+/// Meant for quiet periods: operations running meanwhile are skipped, not reported.
+pub async fn run(state: &AppState, admin_id: Uuid, repair: bool) -> Result<FsckReport, AppError> {
+    let mut reconciler = Reconciler::new(chrono::Utc::now().timestamp(), repair);
+
+    let (mut key_marker, mut version_id_marker) = (None, None);
+    loop {
+        let page = state
+            .store_client
+            .list_versions(None, key_marker.as_deref(), version_id_marker.as_deref())
+            .await?;
+        reconciler.add_store_page(&page);
+        if !page.is_truncated.unwrap_or(false) {
+            break;
+        }
+        key_marker = page.next_key_marker;
+        version_id_marker = page.next_version_id_marker;
+    }
+
+    let mut pages = file::Entity::find()
+        .order_by_asc(file::Column::Id)
+        .paginate(&state.db, FILES_PAGE_SIZE);
+    while let Some(rows) = pages.fetch_and_next().await? {
+        rows.into_iter().for_each(|row| reconciler.check_row(row));
+    }
+
+    // read last: an upload committed during the walk is either seen above or still tracked here
+    let mut tracked: HashSet<Uuid> = HashSet::new();
+    tracked.extend(pending_upload::Entity::find().all(&state.db).await?.into_iter().map(|u| u.file_key));
+    tracked.extend(presigned_upload::Entity::find().all(&state.db).await?.into_iter().map(|u| u.file_key));
+    tracked.extend(multipart_upload::Entity::find().all(&state.db).await?.into_iter().map(|u| u.file_key));
+    let users = user::Entity::find().all(&state.db).await?;
+    reconciler.finish(&tracked, &users);
+
+    if repair {
+        apply_repairs(state, admin_id, &mut reconciler).await?;
+    }
+
+    let report = reconciler.report;
+    tracing::info!(
+        "FSCK by {} (repair: {}): {} orphan objects, {} missing objects, {} size mismatches, {} usage mismatches",
+        admin_id,
+        repair,
+        report.orphan_objects.len(),
+        report.missing_objects.len(),
+        report.size_mismatches.len(),
+        report.usage_mismatches.len()
+    );
+    Ok(report)
+}
+
+async fn apply_repairs(state: &AppState, admin_id: Uuid, reconciler: &mut Reconciler) -> Result<(), AppError> {
+    let report = &reconciler.report;
+
+    for orphan in &report.orphan_objects {
+        state
+            .store_client
+            .delete(&orphan.file_key.to_string(), Some(&orphan.version_id))
+            .await?;
+    }
+
+    let missing_ids: Vec<Uuid> = reconciler.missing.iter().map(|row| row.id).collect();
+    for row in reconciler.missing.drain(..) {
+        let ns = Namespace::acting(row.user_id, admin_id);
+        let event = ObjectEvent::new(EventKind::ObjectDeleted, &ns, &row);
+        let (row, missing_ids, event) = (&row, &missing_ids, &event);
+        let promoted = transaction::retry(|| async move {
+            let txn = state.db.begin().await?;
+            changes::record(&txn, &ns, ChangeKind::Delete, row, None).await?;
+            state.events.stage(&txn, event).await?;
+            file::Entity::delete_by_id(row.id).exec(&txn).await?;
+            // the key would keep versions that GET and listings cannot reach
+            let mut promoted = None;
+            if row.is_latest
+                && let Some(previous) = previous_version(row, missing_ids).one(&txn).await?
+            {
+                let mut previous: file::ActiveModel = previous.into();
+                previous.is_latest = Set(true);
+                let previous = previous.update(&txn).await?;
+                changes::record(&txn, &ns, ChangeKind::Create, &previous, None).await?;
+                let restored = ObjectEvent::new(EventKind::VersionRestored, &ns, &previous);
+                state.events.stage(&txn, &restored).await?;
+                promoted = Some(restored);
+            }
+            txn.commit().await?;
+            Ok(promoted)
+        })
+        .await?;
+        state.events.publish(event.clone()).await;
+        if let Some(restored) = promoted {
+            state.events.publish(restored).await;
+        }
+    }
+
+    for mismatch in &report.size_mismatches {
+        file::ActiveModel {
+            id: Set(mismatch.file_id),
+            content_size: Set(mismatch.stored),
+            ..Default::default()
+        }
+        .update(&state.db)
+        .await?;
+    }
+
+    // by difference, uploads and deletions done meanwhile are kept
+    for mismatch in &report.usage_mismatches {
+        user::Entity::update_many()
+            .col_expr(
+                user::Column::TotalSpaceUsed,
+                Expr::col(user::Column::TotalSpaceUsed).add(mismatch.actual - mismatch.recorded),
+            )
+            .col_expr(user::Column::UpdatedAt, Expr::value(chrono::Utc::now()))
+            .filter(user::Column::UserId.eq(mismatch.user_id))
+            .exec(&state.db)
+            .await?;
+    }
+
+    Ok(())
+}
+
+/// Newest version of the key of `row` still in the store, to become the latest one once `row` is removed.
+/// `missing_ids` holds every row whose object is gone, `row` included.
+fn previous_version(row: &file::Model, missing_ids: &[Uuid]) -> Select<file::Entity> {
+    file::Entity::find()
+        .filter(file::Column::UserId.eq(row.user_id))
+        .filter(bucket_condition(row.bucket_id))
+        .filter(file::Column::FilePath.eq(row.file_path.as_str()))
+        .filter(file::Column::Id.is_not_in(missing_ids.iter().copied()))
+        .order_by_desc(file::Column::AddedAt)
+        .order_by_desc(file::Column::Id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_sdk_s3::primitives::DateTime;
+    use aws_sdk_s3::types::ObjectVersion;
+
+    const STARTED_AT: i64 = 1_700_000_000;
+
+    fn row(user_id: Uuid, file_key: Uuid, version: &str, size: i64) -> file::Model {
+        file::Model {
+            id: Uuid::now_v7(),
+            file_key,
+            user_id,
+            file_name: "a.txt".to_string(),
+            file_path: "docs/a.txt".to_string(),
+            content_type: "text/plain".to_string(),
+            content_size: size,
+            s3_version_id: version.to_string(),
+            is_latest: true,
+            added_at: chrono::DateTime::from_timestamp(STARTED_AT - 60, 0).unwrap().into(),
+            uploaded_by: None,
+            bucket_id: None,
+            retain_until: None,
+            checksum_sha256: None,
+            verified_at: None,
+            corrupted_at: None,
+        }
+    }
+
+    fn version(key: &str, version: &str, size: i64) -> ObjectVersion {
+        ObjectVersion::builder()
+            .key(key)
+            .version_id(version)
+            .size(size)
+            .last_modified(DateTime::from_secs(STARTED_AT - 60))
+            .build()
+    }
+
+    fn user(user_id: Uuid, used: i64) -> user::Model {
+        user::Model {
+            user_id,
+            total_space_used: used,
+            quota_bytes: None,
+            updated_at: chrono::Utc::now().into(),
+            last_auto_sync_at: None,
+            change_seq: 0,
+            created_at: chrono::Utc::now().into(),
+            disabled_at: None,
+        }
+    }
+
+    #[test]
+    fn reconciler_reports_every_kind_of_drift() {
+        let owner = Uuid::now_v7();
+        let (ok_key, resized_key, missing_key, orphan_key, tracked_key) =
+            (Uuid::now_v7(), Uuid::now_v7(), Uuid::now_v7(), Uuid::now_v7(), Uuid::now_v7());
+
+        let mut reconciler = Reconciler::new(STARTED_AT, false);
+        reconciler.add_store_page(
+            &ListObjectVersionsOutput::builder()
+                .versions(version(&ok_key.to_string(), "v1", 10))
+                .versions(version(&resized_key.to_string(), "v1", 25))
+                .versions(version(&orphan_key.to_string(), "v1", 5))
+                .versions(version(&tracked_key.to_string(), "v1", 5))
+                .versions(version("not-a-file-key", "v1", 5))
+                .build(),
+        );
+        reconciler.check_row(row(owner, ok_key, "v1", 10));
+        reconciler.check_row(row(owner, resized_key, "v1", 20));
+        reconciler.check_row(row(owner, missing_key, "v1", 7));
+        reconciler.finish(&HashSet::from([tracked_key]), &[user(owner, 37)]);
+
+        let report = reconciler.report;
+        assert_eq!((report.store_versions, report.file_rows, report.foreign_objects), (5, 3, 1));
+        assert_eq!(report.orphan_objects, vec![OrphanObject { file_key: orphan_key, version_id: "v1".to_string(), size: 5 }]);
+        assert_eq!(report.missing_objects.len(), 1);
+        assert_eq!(report.missing_objects[0].file_key, missing_key);
+        assert_eq!(report.size_mismatches.len(), 1);
+        assert_eq!((report.size_mismatches[0].recorded, report.size_mismatches[0].stored), (20, 25));
+        assert!(report.usage_mismatches.is_empty());
+    }
+
+    #[test]
+    fn reconciler_counts_usage_after_repairs() {
+        let owner = Uuid::now_v7();
+        let (resized_key, missing_key) = (Uuid::now_v7(), Uuid::now_v7());
+
+        let mut reconciler = Reconciler::new(STARTED_AT, true);
+        reconciler.add_store_page(
+            &ListObjectVersionsOutput::builder()
+                .versions(version(&resized_key.to_string(), "v1", 25))
+                .build(),
+        );
+        reconciler.check_row(row(owner, resized_key, "v1", 20));
+        reconciler.check_row(row(owner, missing_key, "v1", 7));
+        reconciler.finish(&HashSet::new(), &[user(owner, 27)]);
+
+        assert_eq!(
+            reconciler.report.usage_mismatches,
+            vec![UsageMismatch { user_id: owner, recorded: 27, actual: 25 }]
+        );
+        assert_eq!(reconciler.missing.len(), 1);
+    }
+
+    #[test]
+    fn previous_version_finds_an_older_version_still_stored() {
+        use sea_orm::{DbBackend, QueryTrait};
+
+        let owner = Uuid::now_v7();
+        let latest = row(owner, Uuid::now_v7(), "v3", 7);
+        let also_missing = Uuid::now_v7();
+        let sql = previous_version(&latest, &[latest.id, also_missing])
+            .build(DbBackend::Postgres)
+            .to_string();
+
+        assert!(sql.contains(&format!("\"files\".\"user_id\" = '{}'", owner)), "{}", sql);
+        assert!(sql.contains("\"files\".\"bucket_id\" IS NULL"), "{}", sql);
+        assert!(sql.contains("\"files\".\"file_path\" = 'docs/a.txt'"), "{}", sql);
+        assert!(sql.contains(&format!("NOT IN ('{}', '{}')", latest.id, also_missing)), "{}", sql);
+        assert!(!sql.contains("\"is_latest\" ="), "{}", sql);
+        assert!(sql.ends_with("ORDER BY \"files\".\"added_at\" DESC, \"files\".\"id\" DESC"), "{}", sql);
+    }
+
+    #[test]
+    fn reconciler_skips_recent_writes() {
+        let owner = Uuid::now_v7();
+        let mut reconciler = Reconciler::new(STARTED_AT, false);
+        let recent = ObjectVersion::builder()
+            .key(Uuid::now_v7().to_string())
+            .version_id("v1")
+            .size(5)
+            .last_modified(DateTime::from_secs(STARTED_AT + 1))
+            .build();
+        reconciler.add_store_page(&ListObjectVersionsOutput::builder().versions(recent).build());
+        let mut new_row = row(owner, Uuid::now_v7(), "v1", 3);
+        new_row.added_at = chrono::DateTime::from_timestamp(STARTED_AT + 1, 0).unwrap().into();
+        reconciler.check_row(new_row);
+        reconciler.finish(&HashSet::new(), &[]);
+
+        assert!(reconciler.report.orphan_objects.is_empty());
+        assert!(reconciler.report.missing_objects.is_empty());
+    }
+}
@@ -0,0 +1,253 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::auth::scope::{join_scopes, parse_scopes};
+use crate::auth::{api_key, AuthUser, Scope};
+use crate::entities::api_key as api_key_entity;
+use crate::error::AppError;
+use crate::objects;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub prefix: Option<String>,
+    pub expires_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiKeyResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub prefix: Option<String>,
+    pub expires_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+    pub last_used_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+    pub created_at: chrono::DateTime<chrono::FixedOffset>,
+    pub revoked_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl TryFrom<api_key_entity::Model> for ApiKeyResponse {
+    type Error = AppError;
+
+    fn try_from(key: api_key_entity::Model) -> Result<Self, Self::Error> {
+        Ok(Self {
+            id: key.id,
+            name: key.name,
+            scopes: parse_scopes(&key.scopes)?,
+            prefix: key.path_prefix,
+            expires_at: key.expires_at,
+            last_used_at: key.last_used_at,
+            created_at: key.created_at,
+            revoked_at: key.revoked_at,
+            token: None,
+        })
+    }
+}
+
+// a key can never hold more rights than the credential that creates it
+pub fn validate_new_key(auth: &AuthUser, request: &CreateApiKeyRequest) -> Result<(), AppError> {
+    if request.name.trim().is_empty() {
+        return Err(AppError::BadRequest("API key name cannot be empty".to_string()));
+    }
+    if request.scopes.is_empty() {
+        return Err(AppError::BadRequest("At least one scope is required".to_string()));
+    }
+    for scope in &request.scopes {
+        auth.require_scope(*scope)?;
+    }
+
+    match (&auth.path_prefix, &request.prefix) {
+        (Some(own), Some(requested)) if !requested.starts_with(own.as_str()) => {
+            return Err(AppError::Forbidden(format!(
+                "Prefix must stay within '{}'",
+                own
+            )));
+        }
+        (Some(own), None) => {
+            return Err(AppError::Forbidden(format!(
+                "Prefix must stay within '{}'",
+                own
+            )));
+        }
+        _ => {}
+    }
+
+    if request.expires_at.is_some_and(|exp| exp <= chrono::Utc::now()) {
+        return Err(AppError::BadRequest("expires_at must be in the future".to_string()));
+    }
+
+    Ok(())
+}
+
+pub async fn create_api_key(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(request): Json<CreateApiKeyRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Admin)?;
+    validate_new_key(&auth, &request)?;
+
+    tracing::info!("Creating API key '{}' for user {}", request.name, auth.user_id);
+
+    // keys belong to a user row, create it if this is the first call of the user
+    objects::ensure_user(&state, auth.user_id).await?;
+
+    let token = api_key::generate_token();
+    let key = api_key_entity::ActiveModel::new(
+        auth.user_id,
+        request.name.trim().to_string(),
+        api_key::hash_token(&token),
+        join_scopes(&request.scopes),
+        request.prefix,
+        request.expires_at,
+    )
+    .insert(&state.db)
+    .await?;
+
+    let mut response = ApiKeyResponse::try_from(key)?;
+    response.token = Some(token);
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+pub async fn list_api_keys(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Admin)?;
+
+    let keys = api_key_entity::Entity::find()
+        .filter(api_key_entity::Column::UserId.eq(auth.user_id))
+        .order_by_desc(api_key_entity::Column::CreatedAt)
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(ApiKeyResponse::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((StatusCode::OK, Json(keys)))
+}
+
+pub async fn revoke_api_key(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Admin)?;
+
+    let key = api_key_entity::Entity::find_by_id(id)
+        .filter(api_key_entity::Column::UserId.eq(auth.user_id))
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("API key not found".to_string()))?;
+
+    if key.revoked_at.is_none() {
+        let mut active: api_key_entity::ActiveModel = key.into();
+        active.revoked_at = Set(Some(chrono::Utc::now().into()));
+        active.update(&state.db).await?;
+        tracing::info!("Revoked API key {} of user {}", id, auth.user_id);
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "message": "API key revoked",
+            "id": id,
+        })),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(scopes: Vec<Scope>, prefix: Option<&str>) -> CreateApiKeyRequest {
+        CreateApiKeyRequest {
+            name: "ci".to_string(),
+            scopes,
+            prefix: prefix.map(str::to_string),
+            expires_at: None,
+        }
+    }
+
+    fn restricted_key() -> AuthUser {
+        AuthUser {
+            user_id: Uuid::now_v7(),
+            scopes: vec![Scope::Read, Scope::Admin],
+            path_prefix: Some("backups/".to_string()),
+        }
+    }
+
+    #[test]
+    fn validate_new_key_accepts_user_session() {
+        let auth = AuthUser::user(Uuid::now_v7());
+        assert!(validate_new_key(&auth, &request(vec![Scope::Read, Scope::Write], None)).is_ok());
+    }
+
+    #[test]
+    fn validate_new_key_requires_name_and_scopes() {
+        let auth = AuthUser::user(Uuid::now_v7());
+
+        let mut unnamed = request(vec![Scope::Read], None);
+        unnamed.name = "  ".to_string();
+        assert!(matches!(validate_new_key(&auth, &unnamed), Err(AppError::BadRequest(_))));
+        assert!(matches!(validate_new_key(&auth, &request(vec![], None)), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn validate_new_key_cannot_escape_creator_prefix() {
+        let auth = restricted_key();
+
+        assert!(validate_new_key(&auth, &request(vec![Scope::Read], Some("backups/db/"))).is_ok());
+        assert!(matches!(
+            validate_new_key(&auth, &request(vec![Scope::Read], Some("photos/"))),
+            Err(AppError::Forbidden(_))
+        ));
+        assert!(matches!(
+            validate_new_key(&auth, &request(vec![Scope::Read], None)),
+            Err(AppError::Forbidden(_))
+        ));
+    }
+
+    #[test]
+    fn validate_new_key_rejects_past_expiry() {
+        let auth = AuthUser::user(Uuid::now_v7());
+        let mut expired = request(vec![Scope::Read], None);
+        expired.expires_at = Some((chrono::Utc::now() - chrono::Duration::hours(1)).into());
+
+        assert!(matches!(validate_new_key(&auth, &expired), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn api_key_response_hides_hash() {
+        let model = api_key_entity::Model {
+            id: Uuid::now_v7(),
+            user_id: Uuid::now_v7(),
+            name: "ci".to_string(),
+            key_hash: "secret-hash".to_string(),
+            scopes: "read,delete".to_string(),
+            path_prefix: None,
+            expires_at: None,
+            last_used_at: None,
+            created_at: chrono::Utc::now().into(),
+            revoked_at: None,
+        };
+
+        let body = serde_json::to_value(ApiKeyResponse::try_from(model).unwrap()).unwrap();
+        assert_eq!(body["scopes"], json!(["read", "delete"]));
+        assert!(body.get("key_hash").is_none());
+        assert!(body.get("token").is_none());
+    }
+}
@@ -0,0 +1,309 @@
+use std::io;
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use bytes::Bytes;
+use futures::{channel::mpsc, SinkExt};
+use sea_orm::{ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde::Deserialize;
+
+use crate::archive::{self, GzipWriter, ZipWriter};
+use crate::audit::AuditRecord;
+use crate::auth::{access, AuthUser, Scope};
+use crate::entities::file;
+use crate::error::AppError;
+use crate::objects;
+use crate::AppState;
+
+/// Chunks waiting for a slow client, reading from the store pauses beyond.
+const CHANNEL_SIZE: usize = 8;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ArchiveParams {
+    /// `zip` (the default) or `tar.gz`.
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArchiveFormat {
+    Zip,
+    TarGz,
+}
+
+impl ArchiveFormat {
+    fn parse(format: Option<&str>) -> Result<Self, AppError> {
+        match format {
+            None | Some("zip") => Ok(Self::Zip),
+            Some("tar.gz" | "tgz") => Ok(Self::TarGz),
+            Some(other) => Err(AppError::BadRequest(format!(
+                "Unknown archive format '{}', use zip or tar.gz",
+                other
+            ))),
+        }
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Zip => "zip",
+            Self::TarGz => "tar.gz",
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Zip => "application/zip",
+            Self::TarGz => "application/gzip",
+        }
+    }
+}
+
+/// Either format, written entry by entry and compressed as the content comes from the store.
+enum Encoder {
+    Zip(Box<ZipWriter>),
+    TarGz(GzipWriter),
+}
+
+impl Encoder {
+    fn new(format: ArchiveFormat) -> Self {
+        match format {
+            ArchiveFormat::Zip => Self::Zip(Box::default()),
+            ArchiveFormat::TarGz => Self::TarGz(GzipWriter::default()),
+        }
+    }
+
+    fn start_entry(&mut self, path: &str, size: u64, mtime: i64) -> Result<Vec<u8>, AppError> {
+        match self {
+            Self::Zip(zip) => zip.start_entry(path, size, mtime),
+            Self::TarGz(gzip) => gzip.write(&archive::tar_header(path, size, mtime)?),
+        }
+    }
+
+    fn data(&mut self, chunk: &[u8]) -> Result<Vec<u8>, AppError> {
+        match self {
+            Self::Zip(zip) => zip.write(chunk),
+            Self::TarGz(gzip) => gzip.write(chunk),
+        }
+    }
+
+    fn end_entry(&mut self, size: u64) -> Result<Vec<u8>, AppError> {
+        match self {
+            // closed by the next entry or the central directory
+            Self::Zip(_) => Ok(Vec::new()),
+            Self::TarGz(gzip) => gzip.write(&vec![0; archive::tar_padding(size)]),
+        }
+    }
+
+    fn finish(self) -> Result<Vec<u8>, AppError> {
+        match self {
+            Self::Zip(zip) => zip.finish(),
+            Self::TarGz(mut gzip) => {
+                let mut end = gzip.write(&archive::tar_end())?;
+                end.extend(gzip.finish()?);
+                Ok(end)
+            }
+        }
+    }
+}
+
+/// Path of `path` in the archive, relative to `prefix`. Empty, `.` and `..` segments are dropped so that
+/// extracting the archive cannot write outside of its folder.
+fn entry_name(prefix: &str, path: &str) -> Option<String> {
+    let relative = path.strip_prefix(prefix)?;
+    let segments: Vec<&str> = relative
+        .split('/')
+        .filter(|s| !s.is_empty() && *s != "." && *s != "..")
+        .collect();
+    if segments.is_empty() {
+        return None;
+    }
+    Some(segments.join("/"))
+}
+
+/// Named after the last folder of the prefix.
+fn archive_file_name(prefix: &str, format: ArchiveFormat) -> String {
+    let folder = prefix
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty())
+        .unwrap_or("archive")
+        .replace('"', "");
+    format!("{}.{}", folder, format.extension())
+}
+
+/// Streams an archive of the latest version of every object under the prefix, read from the store
+/// while it is sent, with paths relative to the prefix.
+pub async fn download_archive(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(prefix): Path<String>,
+    Query(params): Query<ArchiveParams>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let format = ArchiveFormat::parse(params.format.as_deref())?;
+    let owner_id = access::owner_from_headers(&headers)?;
+    let ns = access::authorize(&state.db, &auth, owner_id, Scope::Read, &prefix).await?;
+    let prefix = objects::normalize_prefix(Some(&prefix));
+
+    let mut query = file::Entity::find()
+        .filter(file::Column::UserId.eq(ns.owner_id))
+        .filter(objects::bucket_condition(ns.bucket_id))
+        .filter(file::Column::IsLatest.eq(true))
+        .filter(objects::starts_with(file::Column::FilePath, &prefix));
+    if let Some(condition) = access::visible_files(&state.db, &auth, ns.owner_id, ns.bucket_id).await? {
+        query = query.filter(condition);
+    }
+    let files = query.order_by_asc(file::Column::FilePath).all(&state.db).await?;
+    if files.is_empty() {
+        return Err(AppError::NotFound(format!("No objects under '{}'", prefix)));
+    }
+
+    let total: i64 = files.iter().map(|f| f.content_size).sum();
+    if total as u64 > state.config.archive_max_bytes {
+        return Err(AppError::TooLarge(format!(
+            "The {} objects under '{}' add up to {} bytes, archives are limited to {} bytes",
+            files.len(),
+            prefix,
+            total,
+            state.config.archive_max_bytes
+        )));
+    }
+
+    tracing::info!(
+        "ARCHIVE request for user {}, prefix '{}': {} objects, {} bytes as {}",
+        ns.owner_id,
+        prefix,
+        files.len(),
+        total,
+        format.extension()
+    );
+
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(format.content_type()));
+    let disposition = format!("attachment; filename=\"{}\"", archive_file_name(&prefix, format));
+    if let Ok(value) = HeaderValue::from_str(&disposition) {
+        response_headers.insert(header::CONTENT_DISPOSITION, value);
+    }
+
+    let record = AuditRecord {
+        actor_id: Some(ns.actor_id),
+        owner_id: Some(ns.owner_id),
+        bucket_id: ns.bucket_id,
+        file_path: Some(prefix.clone()),
+        version_id: None,
+        bytes: Some(total),
+    };
+
+    let (tx, rx) = mpsc::channel(CHANNEL_SIZE);
+    tokio::spawn(stream_archive(state, files, prefix, format, tx));
+
+    Ok(record.attach((StatusCode::OK, response_headers, Body::from_stream(rx)).into_response()))
+}
+
+async fn stream_archive(
+    state: AppState,
+    files: Vec<file::Model>,
+    prefix: String,
+    format: ArchiveFormat,
+    mut tx: mpsc::Sender<io::Result<Bytes>>,
+) {
+    if let Err(err) = write_archive(&state, &files, &prefix, format, &mut tx).await {
+        if tx.is_closed() {
+            tracing::info!("Archive of '{}' cancelled by the client", prefix);
+            return;
+        }
+        tracing::error!("Failed to stream the archive of '{}': {:?}", prefix, err);
+        // cuts the response short, so that the client does not take a truncated archive for a whole one
+        let _ = tx.send(Err(io::Error::other(format!("{:?}", err)))).await;
+    }
+}
+
+async fn write_archive(
+    state: &AppState,
+    files: &[file::Model],
+    prefix: &str,
+    format: ArchiveFormat,
+    tx: &mut mpsc::Sender<io::Result<Bytes>>,
+) -> Result<(), AppError> {
+    let mut encoder = Encoder::new(format);
+
+    for file in files {
+        let Some(name) = entry_name(prefix, &file.file_path) else {
+            continue;
+        };
+        let output = match state
+            .store_client
+            .get(&file.file_key.to_string(), Some(&file.s3_version_id))
+            .await
+            .map_err(AppError::from)
+        {
+            Ok(output) => output,
+            // fsck reports it, the rest of the folder is still worth having
+            Err(AppError::NotFound(_)) => {
+                tracing::error!("Skipping {} from the archive, missing from the store", file.file_path);
+                continue;
+            }
+            Err(err) => return Err(err),
+        };
+        let size = output.content_length.map(|len| len as u64).unwrap_or(file.content_size as u64);
+
+        send(tx, encoder.start_entry(&name, size, file.added_at.timestamp())?.into()).await?;
+        let mut body = output.body;
+        let mut copied = 0u64;
+        while let Some(chunk) = body.try_next().await.map_err(|err| AppError::InternalError(err.to_string()))? {
+            copied += chunk.len() as u64;
+            send(tx, encoder.data(&chunk)?.into()).await?;
+        }
+        if copied != size {
+            return Err(AppError::InternalError(format!(
+                "Read {} bytes of {} instead of {}",
+                copied, file.file_path, size
+            )));
+        }
+        send(tx, encoder.end_entry(size)?.into()).await?;
+    }
+
+    send(tx, encoder.finish()?.into()).await
+}
+
+async fn send(tx: &mut mpsc::Sender<io::Result<Bytes>>, bytes: Bytes) -> Result<(), AppError> {
+    if bytes.is_empty() {
+        return Ok(());
+    }
+    tx.send(Ok(bytes))
+        .await
+        .map_err(|_| AppError::InternalError("Client disconnected".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_format_defaults_to_zip() {
+        assert_eq!(ArchiveFormat::parse(None).unwrap(), ArchiveFormat::Zip);
+        assert_eq!(ArchiveFormat::parse(Some("tar.gz")).unwrap(), ArchiveFormat::TarGz);
+        assert!(ArchiveFormat::parse(Some("rar")).is_err());
+    }
+
+    #[test]
+    fn entry_name_stays_inside_the_archive() {
+        assert_eq!(entry_name("docs/", "docs/a/b.txt").as_deref(), Some("a/b.txt"));
+        assert_eq!(entry_name("", "top.txt").as_deref(), Some("top.txt"));
+        assert_eq!(entry_name("docs/", "docs/../../etc/passwd").as_deref(), Some("etc/passwd"));
+        assert_eq!(entry_name("docs/", "docs//./x").as_deref(), Some("x"));
+        assert_eq!(entry_name("docs/", "docs/.."), None);
+        assert_eq!(entry_name("docs/", "other/x"), None);
+    }
+
+    #[test]
+    fn archive_file_name_uses_the_last_folder() {
+        assert_eq!(archive_file_name("docs/2024/", ArchiveFormat::Zip), "2024.zip");
+        assert_eq!(archive_file_name("", ArchiveFormat::TarGz), "archive.tar.gz");
+        assert_eq!(archive_file_name("say \"hi\"/", ArchiveFormat::Zip), "say hi.zip");
+    }
+}
@@ -0,0 +1,141 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sea_orm::{ColumnTrait, Condition, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder, Select};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::AuthUser;
+use crate::entities::audit_event;
+use crate::error::AppError;
+use crate::objects;
+use crate::AppState;
+
+const DEFAULT_PER_PAGE: u64 = 100;
+const MAX_PER_PAGE: u64 = 1000;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AuditParams {
+    /// Events where this user is the actor or the namespace owner.
+    pub user_id: Option<Uuid>,
+    pub key: Option<String>,
+    pub prefix: Option<String>,
+    pub from: Option<chrono::DateTime<chrono::FixedOffset>>,
+    pub to: Option<chrono::DateTime<chrono::FixedOffset>>,
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditResponse {
+    pub items: Vec<audit_event::Model>,
+    pub page: u64,
+    pub per_page: u64,
+    pub total_items: u64,
+    pub total_pages: u64,
+}
+
+fn validate_params(params: &AuditParams) -> Result<(u64, u64), AppError> {
+    let page = params.page.unwrap_or(1);
+    if page == 0 {
+        return Err(AppError::BadRequest("page starts at 1".to_string()));
+    }
+    let per_page = params.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    if per_page == 0 || per_page > MAX_PER_PAGE {
+        return Err(AppError::BadRequest(format!(
+            "per_page must be between 1 and {}",
+            MAX_PER_PAGE
+        )));
+    }
+    if let (Some(from), Some(to)) = (params.from, params.to)
+        && from > to
+    {
+        return Err(AppError::BadRequest("from must be before to".to_string()));
+    }
+    Ok((page, per_page))
+}
+
+fn build_audit_query(params: &AuditParams) -> Select<audit_event::Entity> {
+    let mut query = audit_event::Entity::find();
+
+    if let Some(user_id) = params.user_id {
+        query = query.filter(
+            Condition::any()
+                .add(audit_event::Column::ActorId.eq(user_id))
+                .add(audit_event::Column::OwnerId.eq(user_id)),
+        );
+    }
+    if let Some(ref key) = params.key {
+        query = query.filter(audit_event::Column::FilePath.eq(key.as_str()));
+    }
+    if let Some(ref prefix) = params.prefix {
+        query = query.filter(objects::starts_with(audit_event::Column::FilePath, prefix));
+    }
+    if let Some(from) = params.from {
+        query = query.filter(audit_event::Column::OccurredAt.gte(from));
+    }
+    if let Some(to) = params.to {
+        query = query.filter(audit_event::Column::OccurredAt.lte(to));
+    }
+
+    // newest first, ids are time ordered too
+    query
+        .order_by_desc(audit_event::Column::OccurredAt)
+        .order_by_desc(audit_event::Column::Id)
+}
+
+/// Audit trail of object operations, reserved to instance administrators.
+pub async fn list_audit_events(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(params): Query<AuditParams>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_instance_admin(&state.config.admin_user_ids)?;
+    let (page, per_page) = validate_params(&params)?;
+
+    tracing::info!("AUDIT request from user {}: {:?}", auth.user_id, params);
+
+    let paginator = build_audit_query(&params).paginate(&state.db, per_page);
+    let totals = paginator.num_items_and_pages().await?;
+    let items = paginator.fetch_page(page - 1).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(AuditResponse {
+            items,
+            page,
+            per_page,
+            total_items: totals.number_of_items,
+            total_pages: totals.number_of_pages,
+        }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{DbBackend, QueryTrait};
+
+    #[test]
+    fn validate_params_rejects_inverted_range() {
+        let now = chrono::Utc::now().fixed_offset();
+        let params = AuditParams {
+            from: Some(now),
+            to: Some(now - chrono::Duration::hours(1)),
+            ..Default::default()
+        };
+        assert!(matches!(validate_params(&params), Err(AppError::BadRequest(_))));
+        assert_eq!(validate_params(&AuditParams::default()).unwrap(), (1, DEFAULT_PER_PAGE));
+    }
+
+    #[test]
+    fn user_filter_matches_actor_or_owner() {
+        let params = AuditParams { user_id: Some(Uuid::nil()), ..Default::default() };
+        let sql = build_audit_query(&params).build(DbBackend::Postgres).to_string();
+
+        assert!(sql.contains(r#""actor_id" = '00000000-0000-0000-0000-000000000000' OR "audit_events"."owner_id""#));
+    }
+}
@@ -0,0 +1,382 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, ModelTrait, PaginatorTrait, QueryFilter, QueryOrder,
+    Set,
+};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+use super::{delete, extract, get, head, put};
+use crate::audit::AuditRecord;
+use crate::auth::{access, AuthUser, Scope};
+use crate::entities::{bucket, file};
+use crate::error::AppError;
+use crate::objects::{self, Namespace};
+use crate::users;
+use crate::AppState;
+
+const MIN_NAME_LEN: usize = 3;
+const MAX_NAME_LEN: usize = 63;
+// ten years, past that a retention is better handled by a lifecycle policy
+const MAX_RETENTION_DAYS: i32 = 3650;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateBucketRequest {
+    pub name: String,
+    #[serde(default = "default_versioning")]
+    pub versioning: bool,
+    pub default_retention_days: Option<i32>,
+    #[serde(default)]
+    pub public: bool,
+}
+
+fn default_versioning() -> bool {
+    true
+}
+
+// a field set to null is `Some(None)`, a missing one stays `None` through `default`
+fn present<'de, D>(deserializer: D) -> Result<Option<Option<i32>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Option::<i32>::deserialize(deserializer).map(Some)
+}
+
+/// Settings to change, absent fields are left as they are.
+#[derive(Debug, Default, Deserialize)]
+pub struct UpdateBucketRequest {
+    pub versioning: Option<bool>,
+    /// `null` removes the default retention.
+    #[serde(default, deserialize_with = "present")]
+    pub default_retention_days: Option<Option<i32>>,
+    pub public: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BucketResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub owner_id: Uuid,
+    pub versioning: bool,
+    pub default_retention_days: Option<i32>,
+    pub public: bool,
+    pub created_at: chrono::DateTime<chrono::FixedOffset>,
+}
+
+impl From<bucket::Model> for BucketResponse {
+    fn from(bucket: bucket::Model) -> Self {
+        Self {
+            id: bucket.id,
+            name: bucket.name,
+            owner_id: bucket.owner_id,
+            versioning: bucket.versioning,
+            default_retention_days: bucket.default_retention_days,
+            public: bucket.public,
+            created_at: bucket.created_at,
+        }
+    }
+}
+
+/// S3 naming rules: 3 to 63 lowercase letters, digits, dots and hyphens, starting and ending with a letter or digit.
+fn validate_bucket_name(name: &str) -> Result<(), AppError> {
+    let valid_chars = name
+        .chars()
+        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '-');
+    let valid_ends = name.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && name.ends_with(|c: char| c.is_ascii_alphanumeric());
+
+    if !(MIN_NAME_LEN..=MAX_NAME_LEN).contains(&name.len()) || !valid_chars || !valid_ends || name.contains("..") {
+        return Err(AppError::BadRequest(format!(
+            "Bucket names are {} to {} lowercase letters, digits, dots and hyphens",
+            MIN_NAME_LEN, MAX_NAME_LEN
+        )));
+    }
+    Ok(())
+}
+
+fn validate_retention(days: Option<i32>) -> Result<(), AppError> {
+    if days.is_some_and(|d| !(1..=MAX_RETENTION_DAYS).contains(&d)) {
+        return Err(AppError::BadRequest(format!(
+            "default_retention_days must be between 1 and {}",
+            MAX_RETENTION_DAYS
+        )));
+    }
+    Ok(())
+}
+
+/// Bucket settings are managed by its owner, or by the admins of the organization owning it.
+async fn manage_bucket(state: &AppState, auth: &AuthUser, name: &str) -> Result<bucket::Model, AppError> {
+    let bucket = objects::find_bucket(&state.db, name).await?;
+    access::authorize(&state.db, auth, Some(bucket.owner_id), Scope::Admin, "").await?;
+    Ok(bucket)
+}
+
+/// Namespace of `key` in the bucket, once the caller is allowed to do `scope` on it.
+async fn bucket_namespace(
+    state: &AppState,
+    auth: &AuthUser,
+    bucket: &bucket::Model,
+    scope: Scope,
+    key: &str,
+) -> Result<Namespace, AppError> {
+    let ns = access::authorize_in(&state.db, auth, Some(bucket.owner_id), Some(bucket.id), scope, key).await?;
+    Ok(ns.in_bucket(bucket))
+}
+
+/// Reads of public buckets need no credentials (`None` namespace), any credential sent is still checked.
+async fn read_namespace(
+    state: &AppState,
+    headers: &HeaderMap,
+    bucket: &bucket::Model,
+    key: &str,
+) -> Result<Option<Namespace>, AppError> {
+    if bucket.public && !headers.contains_key(header::AUTHORIZATION) {
+        // public buckets of a disabled owner stop being served, like their share links
+        users::check_enabled(&state.db, bucket.owner_id).await?;
+        return Ok(None);
+    }
+    let auth = state.auth.authenticate(headers, &state.db).await?;
+    users::check_enabled(&state.db, auth.user_id).await?;
+    Ok(Some(bucket_namespace(state, &auth, bucket, Scope::Read, key).await?))
+}
+
+// anonymous reads of a public bucket act as its owner, but are not recorded as theirs
+fn anonymous(mut response: Response) -> Response {
+    if let Some(record) = response.extensions_mut().remove::<AuditRecord>() {
+        response.extensions_mut().insert(record.anonymous());
+    }
+    response
+}
+
+/// New bucket of the caller, or of the organization set in `x-owner-id`.
+pub async fn create_bucket(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    headers: HeaderMap,
+    Json(request): Json<CreateBucketRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let owner_id = access::owner_from_headers(&headers)?;
+    let ns = access::authorize(&state.db, &auth, owner_id, Scope::Admin, "").await?;
+    validate_bucket_name(&request.name)?;
+    validate_retention(request.default_retention_days)?;
+
+    let taken = bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(request.name.as_str()))
+        .count(&state.db)
+        .await?;
+    if taken > 0 {
+        return Err(AppError::Conflict(format!("Bucket '{}' already exists", request.name)));
+    }
+
+    objects::ensure_user(&state, ns.owner_id).await?;
+    let bucket = bucket::ActiveModel::new(
+        request.name,
+        ns.owner_id,
+        request.versioning,
+        request.default_retention_days,
+        request.public,
+    )
+    .insert(&state.db)
+    .await?;
+
+    tracing::info!("User {} created bucket {} for {}", auth.user_id, bucket.name, bucket.owner_id);
+
+    Ok((StatusCode::CREATED, Json(BucketResponse::from(bucket))))
+}
+
+/// Buckets of the caller, or of the organization set in `x-owner-id`.
+pub async fn list_buckets(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Read)?;
+    let owner_id = access::owner_from_headers(&headers)?.unwrap_or(auth.user_id);
+    let mut query = bucket::Entity::find()
+        .filter(bucket::Column::OwnerId.eq(owner_id))
+        .order_by_asc(bucket::Column::Name);
+    // buckets of another user are only listed when something in them is granted to the caller
+    if let Some(condition) = access::visible_buckets(&state.db, &auth, owner_id).await? {
+        query = query.filter(condition);
+    }
+
+    let buckets: Vec<BucketResponse> = query
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(BucketResponse::from)
+        .collect();
+
+    Ok((StatusCode::OK, Json(buckets)))
+}
+
+pub async fn get_bucket(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Read)?;
+    let bucket = objects::find_bucket(&state.db, &name).await?;
+    access::visible_files(&state.db, &auth, bucket.owner_id, Some(bucket.id)).await?;
+
+    Ok((StatusCode::OK, Json(BucketResponse::from(bucket))))
+}
+
+/// Changes the settings of a bucket, they apply to the versions written from now on.
+pub async fn update_bucket(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(name): Path<String>,
+    Json(request): Json<UpdateBucketRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let bucket = manage_bucket(&state, &auth, &name).await?;
+
+    let mut active: bucket::ActiveModel = bucket.into();
+    if let Some(versioning) = request.versioning {
+        active.versioning = Set(versioning);
+    }
+    if let Some(days) = request.default_retention_days {
+        validate_retention(days)?;
+        active.default_retention_days = Set(days);
+    }
+    if let Some(public) = request.public {
+        active.public = Set(public);
+    }
+    let bucket = active.update(&state.db).await?;
+
+    tracing::info!("User {} updated bucket {}", auth.user_id, bucket.name);
+
+    Ok((StatusCode::OK, Json(BucketResponse::from(bucket))))
+}
+
+/// Only empty buckets can be deleted.
+pub async fn delete_bucket(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(name): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    let bucket = manage_bucket(&state, &auth, &name).await?;
+
+    let files = file::Entity::find()
+        .filter(file::Column::BucketId.eq(bucket.id))
+        .count(&state.db)
+        .await?;
+    if files > 0 {
+        return Err(AppError::Conflict(format!(
+            "Bucket '{}' still holds {} object versions",
+            name, files
+        )));
+    }
+
+    bucket.delete(&state.db).await?;
+    tracing::info!("User {} deleted bucket {}", auth.user_id, name);
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "message": "Bucket deleted",
+            "name": name,
+        })),
+    ))
+}
+
+pub async fn get_bucket_object(
+    State(state): State<AppState>,
+    Path((name, key)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let bucket = objects::find_bucket(&state.db, &name).await?;
+    match read_namespace(&state, &headers, &bucket, &key).await? {
+        Some(ns) => get::send_object(&state, ns, &key, &headers).await,
+        None => {
+            let ns = Namespace::own(bucket.owner_id).in_bucket(&bucket);
+            get::send_object(&state, ns, &key, &headers).await.map(anonymous)
+        }
+    }
+}
+
+pub async fn head_bucket_object(
+    State(state): State<AppState>,
+    Path((name, key)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let bucket = objects::find_bucket(&state.db, &name).await?;
+    match read_namespace(&state, &headers, &bucket, &key).await? {
+        Some(ns) => head::describe_object(&state, ns, &key, &headers).await,
+        None => {
+            let ns = Namespace::own(bucket.owner_id).in_bucket(&bucket);
+            head::describe_object(&state, ns, &key, &headers).await.map(anonymous)
+        }
+    }
+}
+
+pub async fn put_bucket_object(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((name, key)): Path<(String, String)>,
+    Query(params): Query<put::PutParams>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<impl IntoResponse, AppError> {
+    let bucket = objects::find_bucket(&state.db, &name).await?;
+    if params.extract {
+        let prefix = objects::normalize_prefix(Some(&key));
+        let ns = bucket_namespace(&state, &auth, &bucket, Scope::Write, &prefix).await?;
+        return extract::extract_into(&state, ns, prefix, &headers, body).await;
+    }
+    let ns = bucket_namespace(&state, &auth, &bucket, Scope::Write, &key).await?;
+    put::store_in(&state, ns, key, &headers, body).await
+}
+
+pub async fn delete_bucket_object(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((name, key)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    let bucket = objects::find_bucket(&state.db, &name).await?;
+    let ns = bucket_namespace(&state, &auth, &bucket, Scope::Delete, &key).await?;
+    delete::remove_object(&state, ns, key, &headers).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_bucket_name_follows_s3_rules() {
+        assert!(validate_bucket_name("my-project").is_ok());
+        assert!(validate_bucket_name("logs.2026").is_ok());
+        assert!(validate_bucket_name("ab").is_err());
+        assert!(validate_bucket_name(&"a".repeat(MAX_NAME_LEN + 1)).is_err());
+        assert!(validate_bucket_name("My-Project").is_err());
+        assert!(validate_bucket_name("-project").is_err());
+        assert!(validate_bucket_name("project.").is_err());
+        assert!(validate_bucket_name("my..project").is_err());
+        assert!(validate_bucket_name("my_project").is_err());
+    }
+
+    #[test]
+    fn validate_retention_bounds() {
+        assert!(validate_retention(None).is_ok());
+        assert!(validate_retention(Some(30)).is_ok());
+        assert!(validate_retention(Some(0)).is_err());
+        assert!(validate_retention(Some(MAX_RETENTION_DAYS + 1)).is_err());
+    }
+
+    #[test]
+    fn update_request_tells_null_from_absent() {
+        let request: UpdateBucketRequest = serde_json::from_str(r#"{"default_retention_days": null}"#).unwrap();
+        assert_eq!(request.default_retention_days, Some(None));
+
+        let request: UpdateBucketRequest = serde_json::from_str(r#"{"public": true}"#).unwrap();
+        assert_eq!(request.default_retention_days, None);
+    }
+}
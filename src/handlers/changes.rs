@@ -0,0 +1,199 @@
+use std::time::Duration;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sea_orm::{ColumnTrait, Condition};
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::auth::{access, AuthUser, Scope};
+use crate::changes;
+use crate::entities::change;
+use crate::error::AppError;
+use crate::objects;
+use crate::AppState;
+
+const DEFAULT_LIMIT: u64 = 100;
+const MAX_LIMIT: u64 = 1000;
+const MAX_WAIT_SECS: u64 = 60;
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ChangesParams {
+    /// Follows the objects of an organization of the caller, or shared with them by another user.
+    pub owner_id: Option<Uuid>,
+    /// Bucket to follow, the default bucket of the owner when absent.
+    pub bucket: Option<String>,
+    /// Last sequence number applied by the client, the current one is returned without changes when absent.
+    pub cursor: Option<i64>,
+    pub limit: Option<u64>,
+    /// Seconds to hold the request until a change happens, when there is none after the cursor.
+    #[serde(default)]
+    pub wait: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangeItem {
+    pub seq: i64,
+    pub kind: String,
+    pub key: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from_key: Option<String>,
+    pub version_id: String,
+    pub content_size: i64,
+    pub actor_id: Uuid,
+    pub occurred_at: chrono::DateTime<chrono::FixedOffset>,
+}
+
+impl From<change::Model> for ChangeItem {
+    fn from(change: change::Model) -> Self {
+        Self {
+            seq: change.seq,
+            kind: change.kind,
+            key: change.file_path,
+            from_key: change.from_path,
+            version_id: change.version_id,
+            content_size: change.content_size,
+            actor_id: change.actor_id,
+            occurred_at: change.occurred_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangesResponse {
+    pub changes: Vec<ChangeItem>,
+    /// To send back as `cursor` to get the following changes.
+    pub cursor: i64,
+    pub has_more: bool,
+}
+
+fn validate_params(params: &ChangesParams) -> Result<(u64, Duration), AppError> {
+    if params.cursor.is_some_and(|cursor| cursor < 0) {
+        return Err(AppError::BadRequest("cursor must be positive".to_string()));
+    }
+
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
+    if limit == 0 || limit > MAX_LIMIT {
+        return Err(AppError::BadRequest(format!("limit must be between 1 and {}", MAX_LIMIT)));
+    }
+
+    if params.wait > MAX_WAIT_SECS {
+        return Err(AppError::BadRequest(format!("wait must be at most {} seconds", MAX_WAIT_SECS)));
+    }
+
+    Ok((limit, Duration::from_secs(params.wait)))
+}
+
+/// Changes of the namespace after `cursor`, in the order they were committed.
+///
+/// Clients first take the current cursor, list the objects, then apply the changes from that cursor on.
+pub async fn list_changes(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(params): Query<ChangesParams>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Read)?;
+
+    let (limit, wait) = validate_params(&params)?;
+
+    let (owner_id, bucket_id) = match params.bucket {
+        Some(ref name) => {
+            let bucket = objects::find_bucket(&state.db, name).await?;
+            (bucket.owner_id, Some(bucket.id))
+        }
+        None => (params.owner_id.unwrap_or(auth.user_id), None),
+    };
+    let mut filter = Condition::all().add(match bucket_id {
+        Some(id) => change::Column::BucketId.eq(id),
+        None => change::Column::BucketId.is_null(),
+    });
+    // credentials restricted to a prefix only see what is under it
+    if let Some(ref prefix) = auth.path_prefix {
+        filter = filter.add(objects::starts_with(change::Column::FilePath, prefix));
+    }
+    // changes of another user are only listed under what they granted to the caller
+    if let Some(condition) = access::visible_keys(&state.db, &auth, owner_id, bucket_id, change::Column::FilePath).await? {
+        filter = filter.add(condition);
+    }
+
+    let Some(cursor) = params.cursor else {
+        let cursor = changes::latest_seq(&state.db, owner_id).await?;
+        return Ok((StatusCode::OK, Json(ChangesResponse { changes: Vec::new(), cursor, has_more: false })));
+    };
+
+    // long polling, a change committed on any instance is picked up by the next query
+    let deadline = Instant::now() + wait;
+    let found = loop {
+        let found = changes::since(&state.db, owner_id, cursor, filter.clone(), limit).await?;
+        let now = Instant::now();
+        if !found.is_empty() || now >= deadline {
+            break found;
+        }
+        tokio::time::sleep(POLL_INTERVAL.min(deadline - now)).await;
+    };
+
+    let has_more = found.len() as u64 == limit;
+    let cursor = found.last().map(|c| c.seq).unwrap_or(cursor);
+
+    Ok((
+        StatusCode::OK,
+        Json(ChangesResponse {
+            changes: found.into_iter().map(ChangeItem::from).collect(),
+            cursor,
+            has_more,
+        }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_params_defaults() {
+        let params = ChangesParams::default();
+        assert_eq!(validate_params(&params).unwrap(), (DEFAULT_LIMIT, Duration::ZERO));
+    }
+
+    #[test]
+    fn validate_params_rejects_out_of_range_values() {
+        let params = ChangesParams { cursor: Some(-1), ..Default::default() };
+        assert!(matches!(validate_params(&params), Err(AppError::BadRequest(_))));
+
+        let params = ChangesParams { limit: Some(MAX_LIMIT + 1), ..Default::default() };
+        assert!(matches!(validate_params(&params), Err(AppError::BadRequest(_))));
+
+        let params = ChangesParams { wait: MAX_WAIT_SECS + 1, ..Default::default() };
+        assert!(matches!(validate_params(&params), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn change_item_hides_from_key_unless_moved() {
+        let change = change::Model {
+            owner_id: Uuid::now_v7(),
+            seq: 7,
+            kind: "create".to_string(),
+            bucket_id: None,
+            file_path: "docs/a.txt".to_string(),
+            from_path: None,
+            version_id: "v1".to_string(),
+            content_size: 3,
+            actor_id: Uuid::now_v7(),
+            occurred_at: chrono::Utc::now().into(),
+        };
+        let json = serde_json::to_value(ChangeItem::from(change.clone())).unwrap();
+        assert_eq!(json["seq"], 7);
+        assert_eq!(json["key"], "docs/a.txt");
+        assert!(json.get("from_key").is_none());
+
+        let moved = change::Model { kind: "move".to_string(), from_path: Some("a.txt".to_string()), ..change };
+        let json = serde_json::to_value(ChangeItem::from(moved)).unwrap();
+        assert_eq!(json["from_key"], "a.txt");
+    }
+}
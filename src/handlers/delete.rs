@@ -1,27 +1,17 @@
 use axum::{
     extract::{Path, State},
     http::{HeaderMap, StatusCode},
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     Json,
 };
-use sea_orm::{ColumnTrait, EntityTrait, ModelTrait, QueryFilter};
 use serde_json::{json, Value};
-use uuid::Uuid;
 
-use crate::entities::file;
+use crate::audit::AuditRecord;
+use crate::auth::{access, AuthUser, Scope};
 use crate::error::AppError;
+use crate::objects::{self, Namespace};
 use crate::AppState;
 
-fn extract_user_id(headers: &HeaderMap) -> Result<Uuid, AppError> {
-    headers
-        .get("x-user-id")
-        .and_then(|v| v.to_str().ok())
-        .and_then(|v| Uuid::parse_str(v).ok())
-        .ok_or(AppError::BadRequest(
-            "Missing or invalid x-user-id header".to_string(),
-        ))
-}
-
 fn extract_version_id(headers: &HeaderMap) -> Option<String> {
     headers
         .get("x-version-id")
@@ -42,13 +32,26 @@ fn build_deleted_response(key: String, version_id: String) -> (StatusCode, Json<
 
 pub async fn delete_object(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(key): Path<String>,
     headers: HeaderMap,
 ) -> Result<impl IntoResponse, AppError> {
-    // Extract user ID from headers (assuming some form of authentication is done)
-    let user_id: Uuid = extract_user_id(&headers)?;
+    let owner_id = access::owner_from_headers(&headers)?;
+    // objects of an organization or of another user are reached through membership or a grant
+    let ns = access::authorize(&state.db, &auth, owner_id, Scope::Delete, &key).await?;
+
+    remove_object(&state, ns, key, &headers).await
+}
 
-    let version_id: Option<String> = extract_version_id(&headers);
+/// Deletes the latest version of `key` in the namespace, or the one asked in `x-version-id`.
+pub async fn remove_object(
+    state: &AppState,
+    ns: Namespace,
+    key: String,
+    headers: &HeaderMap,
+) -> Result<Response, AppError> {
+    let user_id = ns.owner_id;
+    let version_id: Option<String> = extract_version_id(headers);
 
     tracing::info!(
         "DELETE request for user {}, key: {}:{:?}",
@@ -57,33 +60,17 @@ pub async fn delete_object(
         version_id
     );
 
-    let mut query = file::Entity::find()
-        .filter(file::Column::UserId.eq(user_id))
-        .filter(file::Column::FilePath.eq(key.clone()));
-    if let Some(ref vid) = version_id {
-        query = query.filter(file::Column::S3VersionId.eq(vid));
-    } else {
-        query = query.filter(file::Column::IsLatest.eq(true));
-    }
-
-    let file_meta = query
-        .one(&state.db)
+    let file_meta = objects::find_in_bucket(&state.db, user_id, ns.bucket_id, &key, version_id.as_deref())
         .await?
         .ok_or(AppError::NotFound("File not found".to_string()))?;
     let file_version_id = file_meta.s3_version_id.to_string();
+    let record = AuditRecord::new(&ns, &file_meta);
 
-    // delete from s3 storage
-    state
-        .store_client
-        .delete(&file_meta.file_key.to_string(), Some(&file_version_id))
-        .await?;
-
-    // delete from db
-    file_meta.delete(&state.db).await?;
+    objects::delete_version(state, ns, file_meta).await?;
 
     tracing::info!("Deleted file {} (version: {})", key, file_version_id);
 
-    Ok(build_deleted_response(key, file_version_id))
+    Ok(record.attach(build_deleted_response(key, file_version_id).into_response()))
 }
 
 #[cfg(test)]
@@ -91,39 +78,6 @@ mod tests {
     use super::*;
     use axum::http::HeaderValue;
 
-    #[test]
-    fn extract_user_id_missing_is_bad_request() {
-        let headers = HeaderMap::new();
-        let err = extract_user_id(&headers).unwrap_err();
-
-        match err {
-            AppError::BadRequest(msg) => assert!(msg.contains("x-user-id")),
-            other => panic!("expected BadRequest, got: {:?}", other),
-        }
-    }
-
-    #[test]
-    fn extract_user_id_invalid_is_bad_request() {
-        let mut headers = HeaderMap::new();
-        headers.insert("x-user-id", HeaderValue::from_static("not-a-uuid"));
-
-        let err = extract_user_id(&headers).unwrap_err();
-        match err {
-            AppError::BadRequest(msg) => assert!(msg.contains("invalid")),
-            other => panic!("expected BadRequest, got: {:?}", other),
-        }
-    }
-
-    #[test]
-    fn extract_user_id_valid() {
-        let mut headers = HeaderMap::new();
-        let u = Uuid::now_v7();
-        headers.insert("x-user-id", HeaderValue::from_str(&u.to_string()).unwrap());
-
-        let parsed = extract_user_id(&headers).unwrap();
-        assert_eq!(parsed, u);
-    }
-
     #[test]
     fn extract_version_id_absent_is_none() {
         let headers = HeaderMap::new();
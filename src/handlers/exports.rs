@@ -0,0 +1,156 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::{AuthUser, Scope};
+use crate::entities::{export, user};
+use crate::error::AppError;
+use crate::handlers::presign::presigning_config;
+use crate::AppState;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateExportRequest {
+    #[serde(default)]
+    pub all_versions: bool,
+    /// Instance administrators can export the data of another user.
+    pub user_id: Option<Uuid>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportParams {
+    /// Validity of the download link, in seconds.
+    pub expires_in: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportResponse {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub requested_by: Uuid,
+    pub status: &'static str,
+    pub all_versions: bool,
+    pub size_bytes: Option<i64>,
+    pub file_count: Option<i64>,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::FixedOffset>,
+    pub completed_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+    pub expires_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub download_expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+impl From<export::Model> for ExportResponse {
+    fn from(job: export::Model) -> Self {
+        let status = if job.completed_at.is_some() {
+            "completed"
+        } else if job.failed_at.is_some() {
+            "failed"
+        } else {
+            "in_progress"
+        };
+        Self {
+            id: job.id,
+            user_id: job.user_id,
+            requested_by: job.requested_by,
+            status,
+            all_versions: job.all_versions,
+            size_bytes: job.size_bytes,
+            file_count: job.file_count,
+            attempts: job.attempts,
+            last_error: job.last_error,
+            created_at: job.created_at,
+            completed_at: job.completed_at,
+            expires_at: job.expires_at,
+            download_url: None,
+            download_expires_at: None,
+        }
+    }
+}
+
+/// Queues an archive of every file of the user, the export worker builds it in the store.
+pub async fn create_export(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(request): Json<CreateExportRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Admin)?;
+    let user_id = request.user_id.unwrap_or(auth.user_id);
+    if user_id != auth.user_id {
+        auth.require_instance_admin(&state.config.admin_user_ids)?;
+    }
+    if user::Entity::find_by_id(user_id).one(&state.db).await?.is_none() {
+        return Err(AppError::NotFound("User not found".to_string()));
+    }
+
+    let job = export::ActiveModel::new(user_id, auth.user_id, request.all_versions)
+        .insert(&state.db)
+        .await?;
+    tracing::info!("User {} requested export {} of user {}", auth.user_id, job.id, user_id);
+
+    Ok((StatusCode::ACCEPTED, Json(ExportResponse::from(job))))
+}
+
+/// Exports of the caller's data, newest first.
+pub async fn list_exports(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Admin)?;
+
+    let exports: Vec<ExportResponse> = export::Entity::find()
+        .filter(export::Column::UserId.eq(auth.user_id))
+        .order_by_desc(export::Column::CreatedAt)
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(ExportResponse::from)
+        .collect();
+    Ok((StatusCode::OK, Json(exports)))
+}
+
+/// Progress of an export, with a time-limited download link once the archive is ready.
+pub async fn get_export(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ExportParams>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Admin)?;
+
+    let job = export::Entity::find_by_id(id)
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Export not found".to_string()))?;
+    if job.user_id != auth.user_id && job.requested_by != auth.user_id {
+        auth.require_instance_admin(&state.config.admin_user_ids)
+            .map_err(|_| AppError::NotFound("Export not found".to_string()))?;
+    }
+
+    let download = match job.completed_at {
+        Some(_) => {
+            let (config, expires_at) = presigning_config(params.expires_in)?;
+            let request = state
+                .store_client
+                .presign_get(&job.object_key, job.object_version_id.as_deref(), config)
+                .await?;
+            Some((request.uri().to_string(), expires_at))
+        }
+        None => None,
+    };
+
+    let mut response = ExportResponse::from(job);
+    if let Some((url, expires_at)) = download {
+        response.download_url = Some(url);
+        response.download_expires_at = Some(expires_at);
+    }
+    Ok((StatusCode::OK, Json(response)))
+}
@@ -0,0 +1,149 @@
+use axum::{
+    body::Bytes,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+
+use crate::audit::AuditRecord;
+use crate::checksums::{self, Checksums};
+use crate::error::AppError;
+use crate::objects::{self, Namespace};
+use crate::unpack::{self, Limits};
+use crate::AppState;
+
+const MAX_ENTRIES: usize = 10_000;
+const MAX_RATIO: u64 = 200;
+
+#[derive(Debug, Serialize)]
+pub struct EntryResult {
+    /// Path in the archive.
+    pub name: String,
+    pub file_path: Option<String>,
+    pub status: &'static str,
+    pub size: Option<i64>,
+    pub version: Option<String>,
+    pub error: Option<String>,
+}
+
+impl EntryResult {
+    fn failed(name: String, file_path: Option<String>, error: String) -> Self {
+        Self { name, file_path, status: "failed", size: None, version: None, error: Some(error) }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExtractResponse {
+    pub prefix: String,
+    pub created: usize,
+    pub failed: usize,
+    pub entries: Vec<EntryResult>,
+}
+
+/// What the client can be told of an entry that could not be stored.
+fn client_message(err: AppError) -> String {
+    match err {
+        AppError::BadRequest(msg)
+        | AppError::Unauthorized(msg)
+        | AppError::Forbidden(msg)
+        | AppError::NotFound(msg)
+        | AppError::Conflict(msg)
+        | AppError::QuotaExceeded(msg)
+        | AppError::TooLarge(msg) => msg,
+        AppError::Contention(_) => "Too many concurrent changes, please retry".to_string(),
+        AppError::DatabaseError(msg) | AppError::InternalError(msg) => {
+            tracing::error!("Failed to store an extracted entry: {}", msg);
+            "Internal server error".to_string()
+        }
+    }
+}
+
+/// Stores every file of the zip, tar or tar.gz archive in `body` as its own object under `prefix`, content types
+/// guessed from their names. Entries are stored one by one, each of them gets a result in the response.
+pub async fn extract_into(
+    state: &AppState,
+    ns: Namespace,
+    prefix: String,
+    headers: &HeaderMap,
+    body: Bytes,
+) -> Result<Response, AppError> {
+    checksums::verify_upload(headers, &body)?;
+    let limits = Limits {
+        max_entries: MAX_ENTRIES,
+        max_bytes: state.config.extract_max_bytes,
+        max_ratio: MAX_RATIO,
+    };
+    let entries = unpack::unpack(body, &limits)?;
+
+    tracing::info!(
+        "EXTRACT request from user {} into '{}' of user {} ({} entries)",
+        ns.actor_id,
+        prefix,
+        ns.owner_id,
+        entries.len()
+    );
+
+    let mut results = Vec::with_capacity(entries.len());
+    let mut bytes = 0;
+    for entry in entries {
+        let path = match unpack::safe_path(&entry.name) {
+            Ok(path) => path,
+            Err(reason) => {
+                results.push(EntryResult::failed(entry.name, None, reason.to_string()));
+                continue;
+            }
+        };
+        let key = format!("{}{}", prefix, path);
+        let content = match entry.read() {
+            Ok(content) => content,
+            Err(reason) => {
+                results.push(EntryResult::failed(entry.name, Some(key), reason));
+                continue;
+            }
+        };
+
+        let content_type = mime_guess::from_path(&key).first_or_octet_stream().to_string();
+        let checksums = Checksums::compute(&content);
+        match objects::store_object(state, ns, &key, content_type, content, &checksums, Default::default()).await {
+            Ok(file) => {
+                bytes += file.content_size;
+                results.push(EntryResult {
+                    name: entry.name,
+                    file_path: Some(key),
+                    status: "created",
+                    size: Some(file.content_size),
+                    version: Some(file.s3_version_id),
+                    error: None,
+                });
+            }
+            Err(err) => results.push(EntryResult::failed(entry.name, Some(key), client_message(err))),
+        }
+    }
+
+    let created = results.iter().filter(|r| r.status == "created").count();
+    let failed = results.len() - created;
+    let status = if failed == 0 { StatusCode::CREATED } else { StatusCode::MULTI_STATUS };
+
+    let record = AuditRecord {
+        actor_id: Some(ns.actor_id),
+        owner_id: Some(ns.owner_id),
+        bucket_id: ns.bucket_id,
+        file_path: Some(prefix.clone()),
+        version_id: None,
+        bytes: Some(bytes),
+    };
+    let response = ExtractResponse { prefix, created, failed, entries: results };
+    Ok(record.attach((status, Json(response)).into_response()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn client_message_hides_server_errors() {
+        assert_eq!(client_message(AppError::QuotaExceeded("Quota exceeded".to_string())), "Quota exceeded");
+        assert_eq!(client_message(AppError::DatabaseError("connection reset".to_string())), "Internal server error");
+    }
+}
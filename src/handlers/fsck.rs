@@ -0,0 +1,33 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use serde::Deserialize;
+
+use crate::auth::AuthUser;
+use crate::error::AppError;
+use crate::fsck;
+use crate::AppState;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct FsckParams {
+    /// Removes orphan objects and rows of missing objects, fixes sizes and usage.
+    #[serde(default)]
+    pub repair: bool,
+}
+
+/// Reconciles the store with the files table, instance administrators only.
+pub async fn run_fsck(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(params): Query<FsckParams>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_instance_admin(&state.config.admin_user_ids)?;
+
+    tracing::info!("FSCK request from {} (repair: {})", auth.user_id, params.repair);
+
+    let report = fsck::run(&state, auth.user_id, params.repair).await?;
+    Ok((StatusCode::OK, Json(report)))
+}
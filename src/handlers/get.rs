@@ -2,34 +2,34 @@ use axum::{
     body::Body,
     extract::{Path, State},
     http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
-    response::IntoResponse,
+    response::{IntoResponse, Response},
 };
-use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
 use tokio_util::io::ReaderStream;
-use uuid::Uuid;
 
+use crate::audit::AuditRecord;
+use crate::auth::{access, AuthUser, Scope};
 use crate::entities::file;
 use crate::error::AppError;
+use crate::objects::{self, Namespace};
+use crate::scrub;
 use crate::AppState;
 
-fn extract_user_id(headers: &HeaderMap) -> Result<Uuid, AppError> {
+fn extract_version_id(headers: &HeaderMap) -> Option<String> {
     headers
-        .get("x-user-id")
+        .get("x-version-id")
         .and_then(|v| v.to_str().ok())
-        .and_then(|v| Uuid::parse_str(v).ok())
-        .ok_or(AppError::BadRequest(
-            "Missing or invalid x-user-id header".to_string(),
-        ))
+        .map(|s| s.to_string())
 }
 
-fn extract_version_id(headers: &HeaderMap) -> Option<String> {
+/// `x-verify-checksum: true` checks the content against its checksum while it is sent.
+fn wants_verification(headers: &HeaderMap) -> bool {
     headers
-        .get("x-version-id")
+        .get("x-verify-checksum")
         .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string())
+        .is_some_and(|v| v == "true")
 }
 
-fn build_response_headers(file_meta: &file::Model, etag: Option<String>) -> HeaderMap {
+pub fn build_response_headers(file_meta: &file::Model, etag: Option<String>) -> HeaderMap {
     let mut response_headers = HeaderMap::new();
 
     let mut insert_header = |key, value: String| {
@@ -55,35 +55,41 @@ fn build_response_headers(file_meta: &file::Model, etag: Option<String>) -> Head
     if let Some(etag) = etag {
         insert_header(header::ETAG, etag);
     }
+    if let Some(ref checksum) = file_meta.checksum_sha256 {
+        insert_header(HeaderName::from_static("x-checksum-sha256"), checksum.clone());
+    }
 
     response_headers
 }
 
 pub async fn get_object(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(key): Path<String>,
     headers: HeaderMap,
-) -> Result<impl IntoResponse, AppError> {
-    // Extract user ID from headers (assuming some authentication is done)
-    let user_id = extract_user_id(&headers)?;
+) -> Result<Response, AppError> {
+    let owner_id = access::owner_from_headers(&headers)?;
+    // objects of an organization or of another user are reached through membership or a grant
+    let ns = access::authorize(&state.db, &auth, owner_id, Scope::Read, &key).await?;
 
-    // Extract version ID from headers if provided
-    let version_id = extract_version_id(&headers);
+    send_object(&state, ns, &key, &headers).await
+}
 
-    tracing::info!("GET request for user {}, key {}:{:?}", user_id, key, version_id);
+/// Streams the latest version of `key` in the namespace, or the one asked in `x-version-id`.
+pub async fn send_object(
+    state: &AppState,
+    ns: Namespace,
+    key: &str,
+    headers: &HeaderMap,
+) -> Result<Response, AppError> {
+    let user_id = ns.owner_id;
 
-    let mut query = file::Entity::find()
-        .filter(file::Column::UserId.eq(user_id))
-        .filter(file::Column::FilePath.eq(key.clone()));
+    // Extract version ID from headers if provided
+    let version_id = extract_version_id(headers);
 
-    if let Some(ref vid) = version_id {
-        query = query.filter(file::Column::S3VersionId.eq(vid));
-    } else {
-        query = query.filter(file::Column::IsLatest.eq(true));
-    }
+    tracing::info!("GET request for user {}, key {}:{:?}", user_id, key, version_id);
 
-    let file_meta = query
-        .one(&state.db)
+    let file_meta = objects::find_in_bucket(&state.db, user_id, ns.bucket_id, key, version_id.as_deref())
         .await?
         .ok_or_else(|| AppError::NotFound("File not found".to_string()))?;
 
@@ -95,17 +101,32 @@ pub async fn get_object(
     // from AWS Stream errors to standard Axum I/O error
     let reader = s3_output.body.into_async_read();
     let stream = ReaderStream::new(reader);
-    let body = Body::from_stream(stream);
+    let body = match file_meta.checksum_sha256 {
+        Some(ref checksum) if wants_verification(headers) => {
+            let (db, file_id, key) = (state.db.clone(), file_meta.id, key.to_string());
+            Body::from_stream(scrub::verify_stream(stream, checksum.clone(), move || {
+                tracing::error!("Checksum mismatch while sending {} (file {})", key, file_id);
+                tokio::spawn(async move {
+                    if let Err(err) = scrub::mark_corrupted(&db, file_id).await {
+                        tracing::error!("Failed to mark file {} as corrupted: {:?}", file_id, err);
+                    }
+                });
+            }))
+        }
+        _ => Body::from_stream(stream),
+    };
 
     let response_headers = build_response_headers(&file_meta, s3_output.e_tag);
 
-    Ok((StatusCode::OK, response_headers, body))
+    let record = AuditRecord::new(&ns, &file_meta);
+    Ok(record.attach((StatusCode::OK, response_headers, body).into_response()))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use axum::http::HeaderValue;
+    use uuid::Uuid;
 
     fn sample_file_model() -> file::Model {
         file::Model {
@@ -119,46 +140,15 @@ mod tests {
             s3_version_id: "ver-123".to_string(),
             is_latest: true,
             added_at: chrono::Utc::now().into(),
+            uploaded_by: None,
+            bucket_id: None,
+            retain_until: None,
+            checksum_sha256: None,
+            verified_at: None,
+            corrupted_at: None,
         }
     }
 
-    #[test]
-    fn extract_user_id_missing_is_bad_request() {
-        let headers = HeaderMap::new();
-        let err = extract_user_id(&headers).unwrap_err();
-
-        match err {
-            AppError::BadRequest(msg) => {
-                assert!(msg.contains("x-user-id"));
-            }
-            other => panic!("expected BadRequest, got: {:?}", other),
-        }
-    }
-
-    #[test]
-    fn extract_user_id_invalid_is_bad_request() {
-        let mut headers = HeaderMap::new();
-        headers.insert("x-user-id", HeaderValue::from_static("not-a-uuid"));
-
-        let err = extract_user_id(&headers).unwrap_err();
-        match err {
-            AppError::BadRequest(msg) => {
-                assert!(msg.contains("invalid"));
-            }
-            other => panic!("expected BadRequest, got: {:?}", other),
-        }
-    }
-
-    #[test]
-    fn extract_user_id_valid() {
-        let mut headers = HeaderMap::new();
-        let u = Uuid::now_v7();
-        headers.insert("x-user-id", HeaderValue::from_str(&u.to_string()).unwrap());
-
-        let parsed = extract_user_id(&headers).unwrap();
-        assert_eq!(parsed, u);
-    }
-
     #[test]
     fn extract_version_id_absent_is_none() {
         let headers = HeaderMap::new();
@@ -173,6 +163,15 @@ mod tests {
         assert_eq!(extract_version_id(&headers), Some("v42".to_string()));
     }
 
+    #[test]
+    fn wants_verification_only_when_asked() {
+        let mut headers = HeaderMap::new();
+        assert!(!wants_verification(&headers));
+
+        headers.insert("x-verify-checksum", HeaderValue::from_static("true"));
+        assert!(wants_verification(&headers));
+    }
+
     #[test]
     fn build_response_headers_sets_expected_headers_without_etag() {
         let file_meta = sample_file_model();
@@ -0,0 +1,231 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sea_orm::{ActiveModelTrait, ColumnTrait, Condition, EntityTrait, ModelTrait, QueryFilter, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::auth::grant::Permission;
+use crate::auth::{AuthUser, Scope};
+use crate::entities::grant;
+use crate::error::AppError;
+use crate::objects;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateGrantRequest {
+    pub grantee_id: Uuid,
+    /// Bucket of the caller the grant applies to, their default bucket when absent.
+    pub bucket: Option<String>,
+    /// Key, or prefix when it ends with `/` (empty for everything).
+    #[serde(default)]
+    pub prefix: String,
+    pub permission: Permission,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GrantResponse {
+    pub id: Uuid,
+    pub owner_id: Uuid,
+    pub grantee_id: Uuid,
+    pub bucket_id: Option<Uuid>,
+    pub prefix: String,
+    pub permission: String,
+    pub created_at: chrono::DateTime<chrono::FixedOffset>,
+}
+
+impl From<grant::Model> for GrantResponse {
+    fn from(grant: grant::Model) -> Self {
+        Self {
+            id: grant.id,
+            owner_id: grant.owner_id,
+            grantee_id: grant.grantee_id,
+            bucket_id: grant.bucket_id,
+            prefix: grant.prefix,
+            permission: grant.permission,
+            created_at: grant.created_at,
+        }
+    }
+}
+
+fn validate_new_grant(owner_id: Uuid, request: &CreateGrantRequest) -> Result<(), AppError> {
+    if request.grantee_id == owner_id {
+        return Err(AppError::BadRequest("Cannot grant access to yourself".to_string()));
+    }
+    if request.prefix.starts_with('/') {
+        return Err(AppError::BadRequest("prefix cannot start with '/'".to_string()));
+    }
+    Ok(())
+}
+
+/// Grants `permission` on a key or prefix of one bucket to another user, granting the same prefix again replaces
+/// the permission.
+pub async fn create_grant(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(request): Json<CreateGrantRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    // a credential can only hand out what it could do itself
+    auth.require(request.permission.required_scope(), &request.prefix)?;
+    validate_new_grant(auth.user_id, &request)?;
+
+    let bucket_id = match request.bucket {
+        Some(ref name) => {
+            let bucket = objects::find_bucket(&state.db, name).await?;
+            if bucket.owner_id != auth.user_id {
+                return Err(AppError::Forbidden(format!("Bucket '{}' belongs to another user", name)));
+            }
+            Some(bucket.id)
+        }
+        None => None,
+    };
+
+    objects::ensure_user(&state, auth.user_id).await?;
+    objects::ensure_user(&state, request.grantee_id).await?;
+
+    let existing = grant::Entity::find()
+        .filter(grant::Column::OwnerId.eq(auth.user_id))
+        .filter(grant::Column::GranteeId.eq(request.grantee_id))
+        .filter(crate::auth::grant::bucket_condition(bucket_id))
+        .filter(grant::Column::Prefix.eq(request.prefix.as_str()))
+        .one(&state.db)
+        .await?;
+
+    let (status, grant) = match existing {
+        Some(existing) => {
+            let mut active: grant::ActiveModel = existing.into();
+            active.permission = Set(request.permission.as_str().to_string());
+            (StatusCode::OK, active.update(&state.db).await?)
+        }
+        None => {
+            let grant = grant::ActiveModel::new(
+                auth.user_id,
+                request.grantee_id,
+                bucket_id,
+                request.prefix,
+                request.permission.as_str().to_string(),
+            )
+            .insert(&state.db)
+            .await?;
+            (StatusCode::CREATED, grant)
+        }
+    };
+
+    tracing::info!(
+        "User {} granted {} on '{}' to user {}",
+        grant.owner_id,
+        grant.permission,
+        grant.prefix,
+        grant.grantee_id
+    );
+
+    Ok((status, Json(GrantResponse::from(grant))))
+}
+
+/// Grants given by the caller.
+pub async fn list_grants(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Read)?;
+
+    let mut query = grant::Entity::find()
+        .filter(grant::Column::OwnerId.eq(auth.user_id))
+        .order_by_desc(grant::Column::CreatedAt);
+    if let Some(ref key_prefix) = auth.path_prefix {
+        query = query.filter(objects::starts_with(grant::Column::Prefix, key_prefix));
+    }
+
+    let grants: Vec<GrantResponse> = query
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(GrantResponse::from)
+        .collect();
+
+    Ok((StatusCode::OK, Json(grants)))
+}
+
+/// Grants received by the caller, their objects are reached with the `x-owner-id` header
+/// or the `owner_id` parameter of `/search` and `/stats`, the ones of a named bucket through that bucket.
+pub async fn list_shared_with_me(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Read)?;
+
+    let grants: Vec<GrantResponse> = grant::Entity::find()
+        .filter(grant::Column::GranteeId.eq(auth.user_id))
+        .order_by_desc(grant::Column::CreatedAt)
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(GrantResponse::from)
+        .collect();
+
+    Ok((StatusCode::OK, Json(grants)))
+}
+
+/// Revoked by the owner, or given up by the grantee.
+pub async fn revoke_grant(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let grant = grant::Entity::find_by_id(id)
+        .filter(
+            Condition::any()
+                .add(grant::Column::OwnerId.eq(auth.user_id))
+                .add(grant::Column::GranteeId.eq(auth.user_id)),
+        )
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Grant not found".to_string()))?;
+
+    if grant.owner_id == auth.user_id {
+        let permission = Permission::parse(&grant.permission).unwrap_or(Permission::ReadWrite);
+        auth.require(permission.required_scope(), &grant.prefix)?;
+    } else {
+        auth.require_scope(Scope::Read)?;
+    }
+
+    grant.delete(&state.db).await?;
+    tracing::info!("Revoked grant {} by user {}", id, auth.user_id);
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "message": "Grant revoked",
+            "id": id,
+        })),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(grantee_id: Uuid, prefix: &str) -> CreateGrantRequest {
+        CreateGrantRequest {
+            grantee_id,
+            bucket: None,
+            prefix: prefix.to_string(),
+            permission: Permission::Read,
+        }
+    }
+
+    #[test]
+    fn validate_new_grant_rejects_self_and_absolute_prefix() {
+        let owner = Uuid::now_v7();
+        let grantee = Uuid::now_v7();
+
+        assert!(validate_new_grant(owner, &request(grantee, "team/")).is_ok());
+        assert!(validate_new_grant(owner, &request(grantee, "")).is_ok());
+        assert!(matches!(validate_new_grant(owner, &request(owner, "team/")), Err(AppError::BadRequest(_))));
+        assert!(matches!(validate_new_grant(owner, &request(grantee, "/team/")), Err(AppError::BadRequest(_))));
+    }
+}
@@ -1,24 +1,15 @@
 use axum::{
     extract::{Path, State},
     http::{header, HeaderMap, HeaderValue, StatusCode},
-    response::IntoResponse,
+    response::{IntoResponse, Response},
 };
-use sea_orm::{ColumnTrait, EntityTrait, QueryFilter};
-use uuid::Uuid;
+use crate::audit::AuditRecord;
+use crate::auth::{access, AuthUser, Scope};
 use crate::entities::file;
 use crate::error::AppError;
+use crate::objects::{self, Namespace};
 use crate::AppState;
 
-fn extract_user_id(headers: &HeaderMap) -> Result<Uuid, AppError> {
-    headers
-        .get("x-user-id")
-        .and_then(|v| v.to_str().ok())
-        .and_then(|v| Uuid::parse_str(v).ok())
-        .ok_or(AppError::BadRequest(
-            "Missing or invalid x-user-id header".to_string(),
-        ))
-}
-
 fn extract_version_id(headers: &HeaderMap) -> Option<String> {
     headers
         .get("x-version-id")
@@ -60,11 +51,26 @@ fn build_head_response_headers(file: &file::Model) -> HeaderMap {
 
 pub async fn head_object(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(key): Path<String>,
     headers: HeaderMap,
-) -> Result<impl IntoResponse, AppError> {
-    let user_id: Uuid = extract_user_id(&headers)?;
-    let version_id = extract_version_id(&headers);
+) -> Result<Response, AppError> {
+    let owner_id = access::owner_from_headers(&headers)?;
+    // objects of an organization or of another user are reached through membership or a grant
+    let ns = access::authorize(&state.db, &auth, owner_id, Scope::Read, &key).await?;
+
+    describe_object(&state, ns, &key, &headers).await
+}
+
+/// Metadata of the latest version of `key` in the namespace, or of the one asked in `x-version-id`.
+pub async fn describe_object(
+    state: &AppState,
+    ns: Namespace,
+    key: &str,
+    headers: &HeaderMap,
+) -> Result<Response, AppError> {
+    let user_id = ns.owner_id;
+    let version_id = extract_version_id(headers);
 
     tracing::info!(
         "HEAD request for user {} and key {}:{:?}",
@@ -73,31 +79,20 @@ pub async fn head_object(
         version_id
     );
 
-    let mut query = file::Entity::find()
-        .filter(file::Column::UserId.eq(user_id))
-        .filter(file::Column::FilePath.eq(&key));
-
-    // if there is a version_id set, we take this version, otherwise the latest version
-    if let Some(v_id) = version_id {
-        query = query.filter(file::Column::S3VersionId.eq(v_id));
-    } else {
-        query = query.filter(file::Column::IsLatest.eq(true));
-    }
-
-    let file = query
-        .one(&state.db)
+    let file = objects::find_in_bucket(&state.db, user_id, ns.bucket_id, key, version_id.as_deref())
         .await?
         .ok_or(AppError::NotFound("File not found".to_string()))?;
 
     let response_headers = build_head_response_headers(&file);
 
-    Ok((StatusCode::OK, response_headers))
+    Ok(AuditRecord::new(&ns, &file).attach((StatusCode::OK, response_headers).into_response()))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use axum::http::HeaderValue;
+    use uuid::Uuid;
 
     fn sample_file_model() -> file::Model {
         file::Model {
@@ -111,42 +106,15 @@ mod tests {
             s3_version_id: "ver-123".to_string(),
             is_latest: true,
             added_at: chrono::Utc::now().into(),
+            uploaded_by: None,
+            bucket_id: None,
+            retain_until: None,
+            checksum_sha256: None,
+            verified_at: None,
+            corrupted_at: None,
         }
     }
 
-    #[test]
-    fn extract_user_id_missing_is_bad_request() {
-        let headers = HeaderMap::new();
-        let err = extract_user_id(&headers).unwrap_err();
-
-        match err {
-            AppError::BadRequest(msg) => assert!(msg.contains("x-user-id")),
-            other => panic!("expected BadRequest, got: {:?}", other),
-        }
-    }
-
-    #[test]
-    fn extract_user_id_invalid_is_bad_request() {
-        let mut headers = HeaderMap::new();
-        headers.insert("x-user-id", HeaderValue::from_static("not-a-uuid"));
-
-        let err = extract_user_id(&headers).unwrap_err();
-        match err {
-            AppError::BadRequest(msg) => assert!(msg.contains("invalid")),
-            other => panic!("expected BadRequest, got: {:?}", other),
-        }
-    }
-
-    #[test]
-    fn extract_user_id_valid() {
-        let mut headers = HeaderMap::new();
-        let u = Uuid::now_v7();
-        headers.insert("x-user-id", HeaderValue::from_str(&u.to_string()).unwrap());
-
-        let parsed = extract_user_id(&headers).unwrap();
-        assert_eq!(parsed, u);
-    }
-
     #[test]
     fn extract_version_id_absent_is_none() {
         let headers = HeaderMap::new();
@@ -0,0 +1,199 @@
+use std::convert::Infallible;
+use std::time::Duration;
+
+use axum::{
+    extract::{Query, State},
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::stream::{self, Stream};
+use serde::Deserialize;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::time::{Instant, Interval};
+use uuid::Uuid;
+
+use crate::auth::{access, grant, AuthUser, Scope};
+use crate::entities::grant::Model as Grant;
+use crate::error::AppError;
+use crate::events::ObjectEvent;
+use crate::objects;
+use crate::AppState;
+
+/// How often an open stream checks again what the caller may see, so that revoked access ends it.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Default, Deserialize)]
+pub struct LiveParams {
+    /// Follows the objects of an organization of the caller, or shared with them by another user.
+    pub owner_id: Option<Uuid>,
+    /// Bucket to follow, the default bucket of the owner when absent.
+    pub bucket: Option<String>,
+    /// Only events on keys starting with this prefix.
+    pub prefix: Option<String>,
+}
+
+/// What a stream lets through, decided when it is opened and checked again every `REFRESH_INTERVAL`.
+struct LiveFilter {
+    owner_id: Uuid,
+    bucket_id: Option<Uuid>,
+    prefix: Option<String>,
+    path_prefix: Option<String>,
+    /// Grants of a user sharing their objects with the caller, `None` when the caller sees everything.
+    grants: Option<Vec<Grant>>,
+}
+
+impl LiveFilter {
+    fn matches(&self, event: &ObjectEvent) -> bool {
+        event.owner_id == self.owner_id
+            && event.bucket_id == self.bucket_id
+            && self.prefix.as_deref().is_none_or(|prefix| event.key.starts_with(prefix))
+            && self.path_prefix.as_deref().is_none_or(|prefix| event.key.starts_with(prefix))
+            && self
+                .grants
+                .as_deref()
+                .is_none_or(|grants| grants.iter().any(|g| grant::covers(&g.prefix, &event.key)))
+    }
+
+    /// Fails once nothing is visible to the caller anymore: grants revoked, membership removed or owner disabled.
+    async fn refresh(&mut self, state: &AppState, auth: &AuthUser) -> Result<(), AppError> {
+        self.grants = access::visible_grants(&state.db, auth, self.owner_id, self.bucket_id).await?;
+        Ok(())
+    }
+}
+
+struct LiveStream {
+    state: AppState,
+    auth: AuthUser,
+    receiver: broadcast::Receiver<ObjectEvent>,
+    filter: LiveFilter,
+    refresh: Interval,
+}
+
+/// Server-sent events for every object change of the namespace on this instance, as it happens.
+///
+/// Each event is named after its kind and carries the JSON event; a `lagged` event tells a client
+/// too slow to keep up that some were dropped and that it should refresh. The stream ends when the caller
+/// loses access to the namespace.
+pub async fn stream_events(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(params): Query<LiveParams>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    auth.require_scope(Scope::Read)?;
+
+    let (owner_id, bucket_id) = match params.bucket {
+        Some(ref name) => {
+            let bucket = objects::find_bucket(&state.db, name).await?;
+            (bucket.owner_id, Some(bucket.id))
+        }
+        None => (params.owner_id.unwrap_or(auth.user_id), None),
+    };
+    let filter = LiveFilter {
+        owner_id,
+        bucket_id,
+        prefix: params.prefix,
+        path_prefix: auth.path_prefix.clone(),
+        grants: access::visible_grants(&state.db, &auth, owner_id, bucket_id).await?,
+    };
+
+    tracing::info!("EVENTS stream opened by user {} on {}", auth.user_id, owner_id);
+
+    let live = LiveStream {
+        receiver: state.events.subscribe(),
+        refresh: tokio::time::interval_at(Instant::now() + REFRESH_INTERVAL, REFRESH_INTERVAL),
+        state,
+        auth,
+        filter,
+    };
+    let events = stream::unfold(live, |mut live| async move {
+        loop {
+            let received = tokio::select! {
+                received = live.receiver.recv() => received,
+                _ = live.refresh.tick() => {
+                    if let Err(err) = live.filter.refresh(&live.state, &live.auth).await {
+                        let (user_id, owner_id) = (live.auth.user_id, live.filter.owner_id);
+                        tracing::info!("EVENTS stream of user {} on {} closed: {:?}", user_id, owner_id, err);
+                        return None;
+                    }
+                    continue;
+                }
+            };
+            let event = match received {
+                Ok(event) if live.filter.matches(&event) => Event::default()
+                    .event(event.kind.as_str())
+                    .id(event.id.to_string())
+                    .json_data(&event)
+                    .unwrap_or_else(|_| Event::default().comment("unserializable event")),
+                Ok(_) => continue,
+                Err(RecvError::Lagged(missed)) => Event::default().event("lagged").data(missed.to_string()),
+                Err(RecvError::Closed) => return None,
+            };
+            return Some((Ok(event), live));
+        }
+    });
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::EventKind;
+
+    fn event(owner_id: Uuid, key: &str) -> ObjectEvent {
+        ObjectEvent {
+            id: Uuid::now_v7(),
+            kind: EventKind::ObjectCreated,
+            occurred_at: chrono::Utc::now(),
+            owner_id,
+            actor_id: Uuid::now_v7(),
+            bucket_id: None,
+            key: key.to_string(),
+            from_key: None,
+            version_id: "v1".to_string(),
+            content_type: "text/plain".to_string(),
+            size: 1,
+        }
+    }
+
+    fn filter(owner_id: Uuid) -> LiveFilter {
+        LiveFilter {
+            owner_id,
+            bucket_id: None,
+            prefix: None,
+            path_prefix: None,
+            grants: None,
+        }
+    }
+
+    #[test]
+    fn filter_keeps_the_namespace_and_bucket() {
+        let owner = Uuid::now_v7();
+        let filter = filter(owner);
+
+        assert!(filter.matches(&event(owner, "a.txt")));
+        assert!(!filter.matches(&event(Uuid::now_v7(), "a.txt")));
+        assert!(!filter.matches(&ObjectEvent { bucket_id: Some(Uuid::now_v7()), ..event(owner, "a.txt") }));
+    }
+
+    #[test]
+    fn filter_applies_prefixes_and_grants() {
+        let owner = Uuid::now_v7();
+        let filter = LiveFilter {
+            prefix: Some("docs/".to_string()),
+            grants: Some(vec![Grant {
+                id: Uuid::now_v7(),
+                owner_id: owner,
+                grantee_id: Uuid::now_v7(),
+                bucket_id: None,
+                prefix: "docs/shared/".to_string(),
+                permission: "read".to_string(),
+                created_at: chrono::Utc::now().into(),
+            }]),
+            ..filter(owner)
+        };
+
+        assert!(filter.matches(&event(owner, "docs/shared/a.txt")));
+        assert!(!filter.matches(&event(owner, "docs/private/a.txt")));
+        assert!(!filter.matches(&event(owner, "photos/a.jpg")));
+    }
+}
@@ -2,8 +2,52 @@ pub mod get;
 pub mod head;
 pub mod put;
 pub mod delete;
+pub mod restore;
+pub mod moves;
+pub mod search;
+pub mod stats;
+pub mod changes;
+pub mod live;
+pub mod api_keys;
+pub mod s3_keys;
+pub mod presign;
+pub mod shares;
+pub mod upload_links;
+pub mod grants;
+pub mod orgs;
+pub mod buckets;
+pub mod audit;
+pub mod fsck;
+pub mod scrub;
+pub mod users;
+pub mod exports;
+pub mod archive;
+pub mod extract;
 
 pub use get::get_object;
 pub use head::head_object;
 pub use put::put_object;
-pub use delete::delete_object;
\ No newline at end of file
+pub use delete::delete_object;
+pub use restore::restore_object;
+pub use moves::move_object;
+pub use search::search_objects;
+pub use stats::prefix_stats;
+pub use changes::list_changes;
+pub use live::stream_events;
+pub use api_keys::{create_api_key, list_api_keys, revoke_api_key};
+pub use s3_keys::{create_s3_key, list_s3_keys, revoke_s3_key};
+pub use presign::{commit_upload, presign_download, presign_upload};
+pub use shares::{create_share, download_share, list_shares, revoke_share};
+pub use upload_links::{create_upload_link, list_upload_links, revoke_upload_link, upload_to_link};
+pub use grants::{create_grant, list_grants, list_shared_with_me, revoke_grant};
+pub use orgs::{create_org, delete_org, get_org, list_members, list_orgs, remove_member, set_member};
+pub use buckets::{
+    create_bucket, delete_bucket, delete_bucket_object, get_bucket, get_bucket_object, head_bucket_object,
+    list_buckets, put_bucket_object, update_bucket,
+};
+pub use audit::list_audit_events;
+pub use fsck::run_fsck;
+pub use scrub::scrub_report;
+pub use users::{create_user, delete_user, disable_user, enable_user, get_account_deletion, get_user};
+pub use archive::download_archive;
+pub use exports::{create_export, get_export, list_exports};
\ No newline at end of file
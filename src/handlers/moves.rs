@@ -0,0 +1,70 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::audit::AuditRecord;
+use crate::auth::{access, AuthUser, Scope};
+use crate::error::AppError;
+use crate::objects;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct MoveRequest {
+    /// New key of the object.
+    pub to: String,
+}
+
+fn validate_destination(to: &str) -> Result<(), AppError> {
+    if to.is_empty() || to.ends_with('/') {
+        return Err(AppError::BadRequest("Destination must be an object key".to_string()));
+    }
+    Ok(())
+}
+
+/// Renames `key` with all its versions, without copying anything in the store.
+pub async fn move_object(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(key): Path<String>,
+    headers: HeaderMap,
+    Json(request): Json<MoveRequest>,
+) -> Result<Response, AppError> {
+    validate_destination(&request.to)?;
+    let owner_id = access::owner_from_headers(&headers)?;
+    // the key leaves its place and takes a new one, both need to be allowed
+    let ns = access::authorize(&state.db, &auth, owner_id, Scope::Delete, &key).await?;
+    access::authorize(&state.db, &auth, owner_id, Scope::Write, &request.to).await?;
+
+    tracing::info!("MOVE request from user {} for key {} to {} of {}", ns.actor_id, key, request.to, ns.owner_id);
+
+    let moved = objects::move_object(&state, ns, &key, &request.to).await?;
+
+    let record = AuditRecord::new(&ns, &moved);
+    let response = (
+        StatusCode::OK,
+        Json(json!({
+            "message": "Object moved successfully",
+            "from": key,
+            "key": moved.file_path,
+            "version_id": moved.s3_version_id,
+        })),
+    );
+    Ok(record.attach(response.into_response()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_destination_rejects_prefixes() {
+        assert!(validate_destination("docs/report.pdf").is_ok());
+        assert!(matches!(validate_destination(""), Err(AppError::BadRequest(_))));
+        assert!(matches!(validate_destination("docs/"), Err(AppError::BadRequest(_))));
+    }
+}
@@ -0,0 +1,333 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, EntityTrait, ModelTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, Set, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::auth::org::{self, Role};
+use crate::auth::{AuthUser, Scope};
+use crate::entities::{file, org_member, organization, user};
+use crate::error::AppError;
+use crate::objects;
+use crate::transaction;
+use crate::AppState;
+
+const MAX_NAME_LEN: usize = 255;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateOrgRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetMemberRequest {
+    pub role: Role,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OrgResponse {
+    pub id: Uuid,
+    pub name: String,
+    pub role: Role,
+    pub created_by: Uuid,
+    pub created_at: chrono::DateTime<chrono::FixedOffset>,
+    pub total_space_used: Option<i64>,
+    pub quota_bytes: Option<i64>,
+}
+
+impl OrgResponse {
+    fn new(org: organization::Model, role: Role, usage: Option<user::Model>) -> Self {
+        Self {
+            id: org.id,
+            name: org.name,
+            role,
+            created_by: org.created_by,
+            created_at: org.created_at,
+            total_space_used: usage.as_ref().map(|u| u.total_space_used),
+            quota_bytes: usage.and_then(|u| u.quota_bytes),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct MemberResponse {
+    pub user_id: Uuid,
+    pub role: String,
+    pub created_at: chrono::DateTime<chrono::FixedOffset>,
+}
+
+impl From<org_member::Model> for MemberResponse {
+    fn from(member: org_member::Model) -> Self {
+        Self {
+            user_id: member.user_id,
+            role: member.role,
+            created_at: member.created_at,
+        }
+    }
+}
+
+fn validate_org_name(name: &str) -> Result<(), AppError> {
+    let name = name.trim();
+    if name.is_empty() || name.len() > MAX_NAME_LEN {
+        return Err(AppError::BadRequest(format!(
+            "name must be between 1 and {} characters",
+            MAX_NAME_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// Role of the caller, organizations they are not part of look like unknown ones.
+async fn require_member(state: &AppState, org_id: Uuid, user_id: Uuid) -> Result<Role, AppError> {
+    org::member_role(&state.db, org_id, user_id)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Organization not found".to_string()))
+}
+
+/// Changing or removing `target` must not leave the organization without an owner.
+async fn ensure_other_owner(state: &AppState, org_id: Uuid, target: Uuid) -> Result<(), AppError> {
+    let owners = org_member::Entity::find()
+        .filter(org_member::Column::OrgId.eq(org_id))
+        .filter(org_member::Column::Role.eq(Role::Owner.as_str()))
+        .filter(org_member::Column::UserId.ne(target))
+        .count(&state.db)
+        .await?;
+    if owners == 0 {
+        return Err(AppError::Conflict("An organization needs at least one owner".to_string()));
+    }
+    Ok(())
+}
+
+/// New organization with its own namespace and quota, the caller becomes its owner.
+pub async fn create_org(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(request): Json<CreateOrgRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Admin)?;
+    validate_org_name(&request.name)?;
+
+    objects::ensure_user(&state, auth.user_id).await?;
+
+    let org_id = Uuid::now_v7();
+    let (state, auth, request) = (&state, &auth, &request);
+    let (usage, org) = transaction::retry(|| async move {
+        let txn = state.db.begin().await?;
+        let usage = user::ActiveModel::new(org_id, 0, state.config.default_org_quota_bytes)
+            .insert(&txn)
+            .await?;
+        let org = organization::ActiveModel::new(org_id, request.name.trim().to_string(), auth.user_id)
+            .insert(&txn)
+            .await?;
+        org_member::ActiveModel::new(org_id, auth.user_id, Role::Owner.as_str().to_string())
+            .insert(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok((usage, org))
+    })
+    .await?;
+
+    tracing::info!("User {} created organization {} ({})", auth.user_id, org.id, org.name);
+
+    Ok((StatusCode::CREATED, Json(OrgResponse::new(org, Role::Owner, Some(usage)))))
+}
+
+/// Organizations the caller is a member of.
+pub async fn list_orgs(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Read)?;
+
+    let memberships = org_member::Entity::find()
+        .filter(org_member::Column::UserId.eq(auth.user_id))
+        .find_also_related(organization::Entity)
+        .order_by_asc(org_member::Column::CreatedAt)
+        .all(&state.db)
+        .await?;
+
+    let orgs: Vec<OrgResponse> = memberships
+        .into_iter()
+        .filter_map(|(member, org)| Some(OrgResponse::new(org?, Role::parse(&member.role)?, None)))
+        .collect();
+
+    Ok((StatusCode::OK, Json(orgs)))
+}
+
+/// Organization details with its shared usage and quota.
+pub async fn get_org(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Read)?;
+    let role = require_member(&state, id, auth.user_id).await?;
+
+    let org = organization::Entity::find_by_id(id)
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Organization not found".to_string()))?;
+    let usage = user::Entity::find_by_id(id).one(&state.db).await?;
+
+    Ok((StatusCode::OK, Json(OrgResponse::new(org, role, usage))))
+}
+
+/// Only empty organizations can be deleted, by one of their owners.
+pub async fn delete_org(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Admin)?;
+    if require_member(&state, id, auth.user_id).await? != Role::Owner {
+        return Err(AppError::Forbidden("Only owners can delete an organization".to_string()));
+    }
+
+    let files = file::Entity::find()
+        .filter(file::Column::UserId.eq(id))
+        .count(&state.db)
+        .await?;
+    if files > 0 {
+        return Err(AppError::Conflict(format!(
+            "Organization still holds {} object versions",
+            files
+        )));
+    }
+
+    // members, grants and links go with the namespace
+    user::Entity::delete_by_id(id).exec(&state.db).await?;
+    tracing::info!("User {} deleted organization {}", auth.user_id, id);
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "message": "Organization deleted",
+            "id": id,
+        })),
+    ))
+}
+
+pub async fn list_members(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Read)?;
+    require_member(&state, id, auth.user_id).await?;
+
+    let members: Vec<MemberResponse> = org_member::Entity::find()
+        .filter(org_member::Column::OrgId.eq(id))
+        .order_by_asc(org_member::Column::CreatedAt)
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(MemberResponse::from)
+        .collect();
+
+    Ok((StatusCode::OK, Json(members)))
+}
+
+/// Adds a member or changes their role.
+pub async fn set_member(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+    Json(request): Json<SetMemberRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Admin)?;
+    let role = require_member(&state, id, auth.user_id).await?;
+
+    let existing = org_member::Entity::find_by_id((id, user_id)).one(&state.db).await?;
+    let current = existing.as_ref().and_then(|m| Role::parse(&m.role));
+    if !role.can_assign(request.role) || current.is_some_and(|c| !role.can_assign(c)) {
+        return Err(AppError::Forbidden(format!(
+            "A {} cannot make someone {}",
+            role.as_str(),
+            request.role.as_str()
+        )));
+    }
+    if current == Some(Role::Owner) && request.role != Role::Owner {
+        ensure_other_owner(&state, id, user_id).await?;
+    }
+
+    let (status, member) = match existing {
+        Some(existing) => {
+            let mut active: org_member::ActiveModel = existing.into();
+            active.role = Set(request.role.as_str().to_string());
+            (StatusCode::OK, active.update(&state.db).await?)
+        }
+        None => {
+            objects::ensure_user(&state, user_id).await?;
+            let member = org_member::ActiveModel::new(id, user_id, request.role.as_str().to_string())
+                .insert(&state.db)
+                .await?;
+            (StatusCode::CREATED, member)
+        }
+    };
+
+    tracing::info!("User {} set {} as {} of organization {}", auth.user_id, user_id, member.role, id);
+
+    Ok((status, Json(MemberResponse::from(member))))
+}
+
+/// Removes a member, members can also leave on their own.
+pub async fn remove_member(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path((id, user_id)): Path<(Uuid, Uuid)>,
+) -> Result<impl IntoResponse, AppError> {
+    let role = require_member(&state, id, auth.user_id).await?;
+    let member = org_member::Entity::find_by_id((id, user_id))
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Member not found".to_string()))?;
+    let member_role = Role::parse(&member.role);
+
+    if user_id == auth.user_id {
+        auth.require_scope(Scope::Read)?;
+    } else {
+        auth.require_scope(Scope::Admin)?;
+        if !member_role.is_some_and(|r| role.can_assign(r)) {
+            return Err(AppError::Forbidden(format!(
+                "A {} cannot remove a {}",
+                role.as_str(),
+                member.role
+            )));
+        }
+    }
+    if member_role == Some(Role::Owner) {
+        ensure_other_owner(&state, id, user_id).await?;
+    }
+
+    member.delete(&state.db).await?;
+    tracing::info!("User {} removed {} from organization {}", auth.user_id, user_id, id);
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "message": "Member removed",
+            "org_id": id,
+            "user_id": user_id,
+        })),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_org_name_bounds() {
+        assert!(validate_org_name("Acme").is_ok());
+        assert!(matches!(validate_org_name("   "), Err(AppError::BadRequest(_))));
+        assert!(matches!(validate_org_name(&"a".repeat(MAX_NAME_LEN + 1)), Err(AppError::BadRequest(_))));
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use aws_sdk_s3::presigning::{PresignedRequest, PresigningConfig};
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::audit::AuditRecord;
+use crate::auth::{access, AuthUser, Scope};
+use crate::entities::presigned_upload;
+use crate::error::AppError;
+use crate::objects;
+use crate::AppState;
+
+const DEFAULT_EXPIRES_IN_SECS: u64 = 15 * 60;
+// longest validity SigV4 allows
+const MAX_EXPIRES_IN_SECS: u64 = 7 * 24 * 3600;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PresignDownloadParams {
+    pub version_id: Option<String>,
+    pub expires_in: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct PresignUploadParams {
+    pub content_type: Option<String>,
+    pub expires_in: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PresignedUrlResponse {
+    pub method: String,
+    pub url: String,
+    /// Headers the client must send along with the request.
+    pub headers: BTreeMap<String, String>,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upload_id: Option<Uuid>,
+}
+
+impl PresignedUrlResponse {
+    fn new(request: &PresignedRequest, expires_at: chrono::DateTime<chrono::Utc>) -> Self {
+        Self {
+            method: request.method().to_string(),
+            url: request.uri().to_string(),
+            headers: request
+                .headers()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            expires_at,
+            version_id: None,
+            upload_id: None,
+        }
+    }
+}
+
+fn validate_expires_in(expires_in: Option<u64>) -> Result<Duration, AppError> {
+    match expires_in.unwrap_or(DEFAULT_EXPIRES_IN_SECS) {
+        secs if (1..=MAX_EXPIRES_IN_SECS).contains(&secs) => Ok(Duration::from_secs(secs)),
+        _ => Err(AppError::BadRequest(format!(
+            "expires_in must be between 1 and {} seconds",
+            MAX_EXPIRES_IN_SECS
+        ))),
+    }
+}
+
+pub fn presigning_config(
+    expires_in: Option<u64>,
+) -> Result<(PresigningConfig, chrono::DateTime<chrono::Utc>), AppError> {
+    let expires_in = validate_expires_in(expires_in)?;
+    let config = PresigningConfig::expires_in(expires_in)
+        .map_err(|e| AppError::BadRequest(e.to_string()))?;
+    let expires_at = chrono::Utc::now() + expires_in;
+    Ok((config, expires_at))
+}
+
+/// Time-limited URL to download a version straight from the bucket.
+pub async fn presign_download(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(key): Path<String>,
+    Query(params): Query<PresignDownloadParams>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    let owner_id = access::owner_from_headers(&headers)?;
+    let ns = access::authorize(&state.db, &auth, owner_id, Scope::Read, &key).await?;
+    let (config, expires_at) = presigning_config(params.expires_in)?;
+
+    tracing::info!("PRESIGN GET request for user {}, key {}:{:?}", auth.user_id, key, params.version_id);
+
+    let file_meta = objects::find_version(&state.db, ns.owner_id, &key, params.version_id.as_deref())
+        .await?
+        .ok_or_else(|| AppError::NotFound("File not found".to_string()))?;
+
+    let presigned = state
+        .store_client
+        .presign_get(&file_meta.file_key.to_string(), params.version_id.as_deref(), config)
+        .await?;
+
+    let mut response = PresignedUrlResponse::new(&presigned, expires_at);
+    response.version_id = Some(file_meta.s3_version_id);
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// Time-limited URL to upload straight to the bucket, the version only exists once committed.
+pub async fn presign_upload(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(key): Path<String>,
+    Query(params): Query<PresignUploadParams>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    let owner_id = access::owner_from_headers(&headers)?;
+    let ns = access::authorize(&state.db, &auth, owner_id, Scope::Write, &key).await?;
+    let (config, expires_at) = presigning_config(params.expires_in)?;
+    let content_type = params
+        .content_type
+        .unwrap_or_else(|| mime_guess::from_path(&key).first_or_octet_stream().to_string());
+
+    tracing::info!("PRESIGN PUT request for user {}, key {}", auth.user_id, key);
+
+    objects::ensure_user(&state, auth.user_id).await?;
+    objects::ensure_user(&state, ns.owner_id).await?;
+
+    let file_key = Uuid::now_v7();
+    let presigned = state
+        .store_client
+        .presign_put(&file_key.to_string(), &content_type, config)
+        .await?;
+
+    let mut upload = presigned_upload::ActiveModel::new(
+        auth.user_id,
+        key,
+        file_key,
+        content_type,
+        expires_at.into(),
+    );
+    if ns.owner_id != auth.user_id {
+        upload.owner_id = Set(Some(ns.owner_id));
+    }
+    let upload = upload.insert(&state.db).await?;
+
+    let mut response = PresignedUrlResponse::new(&presigned, expires_at);
+    response.upload_id = Some(upload.id);
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+/// Records an object uploaded through a presigned URL as the latest version of its key.
+pub async fn commit_upload(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let upload = presigned_upload::Entity::find_by_id(id)
+        .filter(presigned_upload::Column::UserId.eq(auth.user_id))
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Upload not found".to_string()))?;
+    // access to someone else's namespace may have been lost since the URL was handed out
+    let ns = access::authorize(&state.db, &auth, upload.owner_id, Scope::Write, &upload.file_path).await?;
+
+    let file_key = upload.file_key.to_string();
+    let head = state.store_client.head(&file_key, None).await.map_err(|err| match AppError::from(err) {
+        AppError::NotFound(_) => AppError::BadRequest("Object has not been uploaded yet".to_string()),
+        other => other,
+    })?;
+
+    let new_file = objects::commit_version(
+        &state,
+        ns,
+        &upload.file_path,
+        upload.file_key,
+        head.content_type.unwrap_or_else(|| upload.content_type.clone()),
+        head.content_length.unwrap_or_default(),
+        head.version_id.unwrap_or_else(|| "null".to_string()),
+        None,
+        objects::CommitConditions { presigned_upload: Some(upload.id), ..Default::default() },
+    )
+    .await?;
+
+    tracing::info!("Committed presigned upload {} of user {} for key {}", id, auth.user_id, new_file.file_path);
+
+    let record = AuditRecord::new(&ns, &new_file);
+    let response = (
+        StatusCode::CREATED,
+        Json(json!({
+            "message": "New object created successfully",
+            "file_path": new_file.file_path,
+            "file_key": new_file.file_key,
+            "version": new_file.s3_version_id,
+        })),
+    );
+    Ok(record.attach(response.into_response()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_expires_in_defaults_and_bounds() {
+        assert_eq!(validate_expires_in(None).unwrap(), Duration::from_secs(DEFAULT_EXPIRES_IN_SECS));
+        assert_eq!(validate_expires_in(Some(60)).unwrap(), Duration::from_secs(60));
+        assert!(matches!(validate_expires_in(Some(0)), Err(AppError::BadRequest(_))));
+        assert!(matches!(
+            validate_expires_in(Some(MAX_EXPIRES_IN_SECS + 1)),
+            Err(AppError::BadRequest(_))
+        ));
+    }
+
+    #[test]
+    fn presigned_url_response_skips_missing_ids() {
+        let response = PresignedUrlResponse {
+            method: "GET".to_string(),
+            url: "https://bucket.example/key?X-Amz-Signature=abc".to_string(),
+            headers: BTreeMap::new(),
+            expires_at: chrono::Utc::now(),
+            version_id: Some("v1".to_string()),
+            upload_id: None,
+        };
+
+        let body = serde_json::to_value(response).unwrap();
+        assert_eq!(body["version_id"], "v1");
+        assert!(body.get("upload_id").is_none());
+    }
+}
@@ -1,30 +1,23 @@
 use axum::{
     body::Bytes,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     http::{HeaderMap, StatusCode},
-    response::IntoResponse,
+    response::{IntoResponse, Response},
     Json,
 };
 use mime_guess;
-use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, Set, TransactionTrait};
+use serde::Deserialize;
 use serde_json::{json, Value};
-use uuid::Uuid;
 
-use crate::entities::{file, user};
+use crate::audit::AuditRecord;
+use crate::auth::{access, AuthUser, Scope};
+use crate::checksums::{self, Checksums};
 use crate::error::AppError;
+use crate::handlers::extract;
+use crate::objects::{self, Namespace};
 use crate::AppState;
 
-fn extract_user_id(headers: &HeaderMap) -> Result<Uuid, AppError> {
-    headers
-        .get("x-user-id")
-        .and_then(|v| v.to_str().ok())
-        .and_then(|v| Uuid::parse_str(v).ok())
-        .ok_or(AppError::BadRequest(
-            "Missing or invalid x-user-id header".to_string(),
-        ))
-}
-
-fn content_type_from_headers_or_path(headers: &HeaderMap, key: &str) -> String {
+pub fn content_type_from_headers_or_path(headers: &HeaderMap, key: &str) -> String {
     headers
         .get("content-type")
         .and_then(|v| v.to_str().ok())
@@ -32,14 +25,11 @@ fn content_type_from_headers_or_path(headers: &HeaderMap, key: &str) -> String {
         .unwrap_or_else(|| mime_guess::from_path(key).first_or_octet_stream().to_string())
 }
 
-fn file_name_from_key(key: &str) -> String {
-    key.split('/').last().unwrap_or(key).to_string()
-}
-
 fn build_created_response(
     key: String,
     s3_key_string: String,
     s3_version_id: String,
+    checksums: &Checksums,
 ) -> (StatusCode, Json<Value>) {
     (
         StatusCode::CREATED,
@@ -48,75 +38,66 @@ fn build_created_response(
             "file_path": key,
             "file_key": s3_key_string,
             "version": s3_version_id,
+            "checksums": checksums.to_json(),
         })),
     )
 }
 
+#[derive(Debug, Default, Deserialize)]
+pub struct PutParams {
+    /// The body is a zip, tar or tar.gz archive whose files are stored under the key, taken as a prefix.
+    #[serde(default)]
+    pub extract: bool,
+}
+
 pub async fn put_object(
     State(state): State<AppState>,
+    auth: AuthUser,
     Path(key): Path<String>,
+    Query(params): Query<PutParams>,
     headers: HeaderMap,
     body: Bytes,
 ) -> Result<impl IntoResponse, AppError> {
-    // Extract user ID from headers (assuming some authentication is done)
-    let user_id: Uuid = extract_user_id(&headers)?;
+    let owner_id = access::owner_from_headers(&headers)?;
+    if params.extract {
+        let prefix = objects::normalize_prefix(Some(&key));
+        let ns = access::authorize(&state.db, &auth, owner_id, Scope::Write, &prefix).await?;
+        return extract::extract_into(&state, ns, prefix, &headers, body).await;
+    }
+    // writes through a grant are charged to the owner and attributed to the caller
+    let ns = access::authorize(&state.db, &auth, owner_id, Scope::Write, &key).await?;
+
+    store_in(&state, ns, key, &headers, body).await
+}
+
+/// Stores `body` as the new latest version of `key` in the namespace.
+pub async fn store_in(
+    state: &AppState,
+    ns: Namespace,
+    key: String,
+    headers: &HeaderMap,
+    body: Bytes,
+) -> Result<Response, AppError> {
+    let user_id = ns.owner_id;
 
     // Extract Content-Type and Content-Length from headers
-    let content_type = content_type_from_headers_or_path(&headers, &key);
+    let content_type = content_type_from_headers_or_path(headers, &key);
     let content_size = body.len() as i64;
-    let file_name = file_name_from_key(&key);
 
     tracing::info!(
-        "PUT request from user {} for key {} ({} bytes)",
-        user_id,
+        "PUT request from user {} for key {} of user {} ({} bytes)",
+        ns.actor_id,
         key,
-        content_size
-    );
-
-    // Create or update user record if not exists
-    let user_exists = user::Entity::find_by_id(user_id).one(&state.db).await?;
-    // If user does not exist, we create a new user record
-    if user_exists.is_none() {
-        let new_user = user::ActiveModel::new(user_id, 0);
-        new_user.insert(&state.db).await?;
-        tracing::info!("Created new user profile {}", user_id);
-    }
-
-    let new_file_uuid = Uuid::now_v7();
-    let s3_key_string = new_file_uuid.to_string();
-
-    let s3_output = state.store_client.put(&s3_key_string, body).await?;
-    let s3_version_id = s3_output.version_id.unwrap_or_else(|| "null".to_string());
-
-    // transactionnal update
-    let txn = state.db.begin().await?;
-
-    let existing_active_file = file::Entity::find()
-        .filter(file::Column::UserId.eq(user_id))
-        .filter(file::Column::FilePath.eq(key.clone()))
-        .filter(file::Column::IsLatest.eq(true))
-        .one(&txn)
-        .await?;
-    if let Some(old_file) = existing_active_file {
-        let mut old_active: file::ActiveModel = old_file.into();
-        old_active.is_latest = Set(false);
-        old_active.update(&txn).await?;
-    }
-    let new_file_entry = file::ActiveModel::new(
-        new_file_uuid,
         user_id,
-        file_name,
-        key.clone(),
-        content_type,
-        content_size,
-        s3_version_id.clone(),
+        content_size
     );
-    new_file_entry.insert(&txn).await?;
 
-    // commit transaction
-    txn.commit().await?;
+    let checksums = checksums::verify_upload(headers, &body)?;
+    let new_file = objects::store_object(state, ns, &key, content_type, body, &checksums, Default::default()).await?;
 
-    Ok(build_created_response(key, s3_key_string, s3_version_id))
+    let record = AuditRecord::new(&ns, &new_file);
+    let response = build_created_response(key, new_file.file_key.to_string(), new_file.s3_version_id, &checksums);
+    Ok(record.attach(response.into_response()))
 }
 
 #[cfg(test)]
@@ -124,39 +105,6 @@ mod tests {
     use super::*;
     use axum::http::HeaderValue;
 
-    #[test]
-    fn extract_user_id_missing_is_bad_request() {
-        let headers = HeaderMap::new();
-        let err = extract_user_id(&headers).unwrap_err();
-
-        match err {
-            AppError::BadRequest(msg) => assert!(msg.contains("x-user-id")),
-            other => panic!("expected BadRequest, got: {:?}", other),
-        }
-    }
-
-    #[test]
-    fn extract_user_id_invalid_is_bad_request() {
-        let mut headers = HeaderMap::new();
-        headers.insert("x-user-id", HeaderValue::from_static("not-a-uuid"));
-
-        let err = extract_user_id(&headers).unwrap_err();
-        match err {
-            AppError::BadRequest(msg) => assert!(msg.contains("invalid")),
-            other => panic!("expected BadRequest, got: {:?}", other),
-        }
-    }
-
-    #[test]
-    fn extract_user_id_valid() {
-        let mut headers = HeaderMap::new();
-        let u = Uuid::now_v7();
-        headers.insert("x-user-id", HeaderValue::from_str(&u.to_string()).unwrap());
-
-        let parsed = extract_user_id(&headers).unwrap();
-        assert_eq!(parsed, u);
-    }
-
     #[test]
     fn content_type_from_headers_or_path_prefers_header() {
         let mut headers = HeaderMap::new();
@@ -174,18 +122,13 @@ mod tests {
         assert_eq!(ct, "text/plain");
     }
 
-    #[test]
-    fn file_name_from_key_extracts_last_segment() {
-        assert_eq!(file_name_from_key("a/b/c.txt"), "c.txt");
-        assert_eq!(file_name_from_key("single"), "single");
-    }
-
     #[test]
     fn build_created_response_has_expected_shape() {
         let (status, Json(body)) = build_created_response(
             "path/to/file.txt".to_string(),
             "s3-key-1".to_string(),
             "ver-123".to_string(),
+            &Checksums::compute(b"hello world"),
         );
 
         assert_eq!(status, StatusCode::CREATED);
@@ -193,5 +136,6 @@ mod tests {
         assert_eq!(body["file_path"], "path/to/file.txt");
         assert_eq!(body["file_key"], "s3-key-1");
         assert_eq!(body["version"], "ver-123");
+        assert_eq!(body["checksums"]["content_md5"], "XrY7u+Ae7tCTyyK7j1rNww==");
     }
 }
\ No newline at end of file
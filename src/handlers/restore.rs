@@ -0,0 +1,66 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+
+use crate::audit::AuditRecord;
+use crate::auth::{access, AuthUser, Scope};
+use crate::error::AppError;
+use crate::objects;
+use crate::AppState;
+
+fn extract_version_id(headers: &HeaderMap) -> Result<String, AppError> {
+    headers
+        .get("x-version-id")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string())
+        .ok_or_else(|| AppError::BadRequest("x-version-id header is required".to_string()))
+}
+
+/// Makes the version given in `x-version-id` the latest version of `key`.
+pub async fn restore_object(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(key): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let owner_id = access::owner_from_headers(&headers)?;
+    let ns = access::authorize(&state.db, &auth, owner_id, Scope::Write, &key).await?;
+    let version_id = extract_version_id(&headers)?;
+
+    tracing::info!("RESTORE request from user {} for key {}:{} of {}", ns.actor_id, key, version_id, ns.owner_id);
+
+    let file_meta = objects::find_version(&state.db, ns.owner_id, &key, Some(&version_id))
+        .await?
+        .ok_or_else(|| AppError::NotFound("File not found".to_string()))?;
+    let restored = objects::restore_version(&state, ns, file_meta).await?;
+
+    let record = AuditRecord::new(&ns, &restored);
+    let response = (
+        StatusCode::OK,
+        Json(json!({
+            "message": "Version restored successfully",
+            "key": restored.file_path,
+            "version_id": restored.s3_version_id,
+        })),
+    );
+    Ok(record.attach(response.into_response()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn extract_version_id_is_required() {
+        let mut headers = HeaderMap::new();
+        assert!(matches!(extract_version_id(&headers), Err(AppError::BadRequest(_))));
+
+        headers.insert("x-version-id", HeaderValue::from_static("v42"));
+        assert_eq!(extract_version_id(&headers).unwrap(), "v42");
+    }
+}
@@ -0,0 +1,153 @@
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, QueryFilter, QueryOrder, Set};
+use serde::Serialize;
+use serde_json::json;
+
+use super::api_keys::{validate_new_key, CreateApiKeyRequest};
+use crate::auth::scope::{join_scopes, parse_scopes};
+use crate::auth::{AuthUser, Scope};
+use crate::entities::s3_credential;
+use crate::error::AppError;
+use crate::objects;
+use crate::s3api::auth::{generate_access_key_id, generate_secret_access_key};
+use crate::AppState;
+
+#[derive(Debug, Serialize)]
+pub struct S3KeyResponse {
+    pub access_key_id: String,
+    pub name: String,
+    pub scopes: Vec<Scope>,
+    pub prefix: Option<String>,
+    pub expires_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+    pub created_at: chrono::DateTime<chrono::FixedOffset>,
+    pub revoked_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secret_access_key: Option<String>,
+}
+
+impl TryFrom<s3_credential::Model> for S3KeyResponse {
+    type Error = AppError;
+
+    fn try_from(key: s3_credential::Model) -> Result<Self, Self::Error> {
+        Ok(Self {
+            access_key_id: key.access_key_id,
+            name: key.name,
+            scopes: parse_scopes(&key.scopes)?,
+            prefix: key.path_prefix,
+            expires_at: key.expires_at,
+            created_at: key.created_at,
+            revoked_at: key.revoked_at,
+            secret_access_key: None,
+        })
+    }
+}
+
+/// Same rules as API keys: the new access key cannot exceed the rights of its creator.
+pub async fn create_s3_key(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(request): Json<CreateApiKeyRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Admin)?;
+    validate_new_key(&auth, &request)?;
+
+    tracing::info!("Creating S3 access key '{}' for user {}", request.name, auth.user_id);
+
+    objects::ensure_user(&state, auth.user_id).await?;
+
+    let secret = generate_secret_access_key();
+    let key = s3_credential::ActiveModel::new(
+        generate_access_key_id(),
+        auth.user_id,
+        request.name.trim().to_string(),
+        secret.clone(),
+        join_scopes(&request.scopes),
+        request.prefix,
+        request.expires_at,
+    )
+    .insert(&state.db)
+    .await?;
+
+    let mut response = S3KeyResponse::try_from(key)?;
+    response.secret_access_key = Some(secret);
+
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+pub async fn list_s3_keys(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Admin)?;
+
+    let keys = s3_credential::Entity::find()
+        .filter(s3_credential::Column::UserId.eq(auth.user_id))
+        .order_by_desc(s3_credential::Column::CreatedAt)
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(S3KeyResponse::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok((StatusCode::OK, Json(keys)))
+}
+
+pub async fn revoke_s3_key(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(access_key_id): Path<String>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Admin)?;
+
+    let key = s3_credential::Entity::find_by_id(access_key_id.clone())
+        .filter(s3_credential::Column::UserId.eq(auth.user_id))
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("S3 access key not found".to_string()))?;
+
+    if key.revoked_at.is_none() {
+        let mut active: s3_credential::ActiveModel = key.into();
+        active.revoked_at = Set(Some(chrono::Utc::now().into()));
+        active.update(&state.db).await?;
+        tracing::info!("Revoked S3 access key {} of user {}", access_key_id, auth.user_id);
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "message": "S3 access key revoked",
+            "access_key_id": access_key_id,
+        })),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn s3_key_response_hides_secret() {
+        let model = s3_credential::Model {
+            access_key_id: "RKEXAMPLE0000000000".to_string(),
+            user_id: Uuid::now_v7(),
+            name: "rclone".to_string(),
+            secret_access_key: "secret".to_string(),
+            scopes: "read,write".to_string(),
+            path_prefix: Some("backups/".to_string()),
+            expires_at: None,
+            created_at: chrono::Utc::now().into(),
+            revoked_at: None,
+        };
+
+        let body = serde_json::to_value(S3KeyResponse::try_from(model).unwrap()).unwrap();
+        assert_eq!(body["scopes"], json!(["read", "write"]));
+        assert_eq!(body["prefix"], "backups/");
+        assert!(body.get("secret_access_key").is_none());
+    }
+}
@@ -0,0 +1,17 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+
+use crate::auth::AuthUser;
+use crate::error::AppError;
+use crate::scrub;
+use crate::AppState;
+
+/// Progress of the scrubber and the versions found corrupted, instance administrators only.
+pub async fn scrub_report(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_instance_admin(&state.config.admin_user_ids)?;
+
+    let report = scrub::report(&state.db).await?;
+    Ok((StatusCode::OK, Json(report)))
+}
@@ -0,0 +1,306 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sea_orm::sea_query::{extension::postgres::PgExpr, Expr, LikeExpr};
+use sea_orm::{ColumnTrait, EntityTrait, Order, PaginatorTrait, QueryFilter, QueryOrder, Select};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::{access, AuthUser, Scope};
+use crate::entities::file;
+use crate::error::AppError;
+use crate::objects;
+use crate::AppState;
+
+const DEFAULT_PER_PAGE: u64 = 50;
+const MAX_PER_PAGE: u64 = 1000;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortField {
+    Name,
+    Size,
+    #[default]
+    Date,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct SearchParams {
+    /// Searches the objects of an organization of the caller, or shared with them by another user.
+    pub owner_id: Option<Uuid>,
+    /// Bucket to look into, the default bucket of the owner when absent.
+    pub bucket: Option<String>,
+    pub name: Option<String>,
+    pub content_type: Option<String>,
+    pub min_size: Option<i64>,
+    pub max_size: Option<i64>,
+    pub added_after: Option<chrono::DateTime<chrono::FixedOffset>>,
+    pub added_before: Option<chrono::DateTime<chrono::FixedOffset>>,
+    #[serde(default)]
+    pub all_versions: bool,
+    #[serde(default)]
+    pub sort: SortField,
+    #[serde(default)]
+    pub order: SortOrder,
+    pub page: Option<u64>,
+    pub per_page: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchItem {
+    pub file_path: String,
+    pub file_name: String,
+    pub content_type: String,
+    pub content_size: i64,
+    pub version_id: String,
+    pub is_latest: bool,
+    pub added_at: chrono::DateTime<chrono::FixedOffset>,
+}
+
+impl From<file::Model> for SearchItem {
+    fn from(file: file::Model) -> Self {
+        Self {
+            file_path: file.file_path,
+            file_name: file.file_name,
+            content_type: file.content_type,
+            content_size: file.content_size,
+            version_id: file.s3_version_id,
+            is_latest: file.is_latest,
+            added_at: file.added_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResponse {
+    pub items: Vec<SearchItem>,
+    pub page: u64,
+    pub per_page: u64,
+    pub total_items: u64,
+    pub total_pages: u64,
+}
+
+fn validate_params(params: &SearchParams) -> Result<(u64, u64), AppError> {
+    let page = params.page.unwrap_or(1);
+    if page == 0 {
+        return Err(AppError::BadRequest("page starts at 1".to_string()));
+    }
+
+    let per_page = params.per_page.unwrap_or(DEFAULT_PER_PAGE);
+    if per_page == 0 || per_page > MAX_PER_PAGE {
+        return Err(AppError::BadRequest(format!(
+            "per_page must be between 1 and {}",
+            MAX_PER_PAGE
+        )));
+    }
+
+    if let (Some(min), Some(max)) = (params.min_size, params.max_size)
+        && min > max
+    {
+        return Err(AppError::BadRequest(
+            "min_size must be lower than or equal to max_size".to_string(),
+        ));
+    }
+
+    if let (Some(after), Some(before)) = (params.added_after, params.added_before)
+        && after > before
+    {
+        return Err(AppError::BadRequest(
+            "added_after must be before added_before".to_string(),
+        ));
+    }
+
+    Ok((page, per_page))
+}
+
+fn build_search_query(
+    user_id: Uuid,
+    path_prefix: Option<&str>,
+    params: &SearchParams,
+) -> Select<file::Entity> {
+    let mut query = file::Entity::find().filter(file::Column::UserId.eq(user_id));
+
+    // credentials restricted to a prefix only see what is under it
+    if let Some(prefix) = path_prefix {
+        query = query.filter(objects::starts_with(file::Column::FilePath, prefix));
+    }
+
+    if !params.all_versions {
+        query = query.filter(file::Column::IsLatest.eq(true));
+    }
+
+    if let Some(ref name) = params.name {
+        let pattern = LikeExpr::new(format!("%{}%", objects::escape_like(name))).escape('\\');
+        query = query.filter(Expr::col((file::Entity, file::Column::FileName)).ilike(pattern));
+    }
+
+    // "image/*" matches every image subtype, anything else is an exact match
+    if let Some(ref content_type) = params.content_type {
+        match content_type.strip_suffix('*') {
+            Some(prefix) => {
+                query = query.filter(objects::starts_with(file::Column::ContentType, prefix));
+            }
+            None => {
+                query = query.filter(file::Column::ContentType.eq(content_type.clone()));
+            }
+        }
+    }
+
+    if let Some(min) = params.min_size {
+        query = query.filter(file::Column::ContentSize.gte(min));
+    }
+    if let Some(max) = params.max_size {
+        query = query.filter(file::Column::ContentSize.lte(max));
+    }
+    if let Some(after) = params.added_after {
+        query = query.filter(file::Column::AddedAt.gte(after));
+    }
+    if let Some(before) = params.added_before {
+        query = query.filter(file::Column::AddedAt.lte(before));
+    }
+
+    let order = match params.order {
+        SortOrder::Asc => Order::Asc,
+        SortOrder::Desc => Order::Desc,
+    };
+    let column = match params.sort {
+        SortField::Name => file::Column::FileName,
+        SortField::Size => file::Column::ContentSize,
+        SortField::Date => file::Column::AddedAt,
+    };
+
+    // id as tie-breaker keeps pages stable
+    query
+        .order_by(column, order.clone())
+        .order_by(file::Column::Id, order)
+}
+
+pub async fn search_objects(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(params): Query<SearchParams>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Read)?;
+
+    let (page, per_page) = validate_params(&params)?;
+
+    tracing::info!("SEARCH request for user {}: {:?}", auth.user_id, params);
+
+    let (user_id, bucket_id) = match params.bucket {
+        Some(ref name) => {
+            let bucket = objects::find_bucket(&state.db, name).await?;
+            (bucket.owner_id, Some(bucket.id))
+        }
+        None => (params.owner_id.unwrap_or(auth.user_id), None),
+    };
+    let mut query = build_search_query(user_id, auth.path_prefix.as_deref(), &params)
+        .filter(objects::bucket_condition(bucket_id));
+    // objects of another user are only listed under what they granted to the caller
+    if let Some(condition) = access::visible_files(&state.db, &auth, user_id, bucket_id).await? {
+        query = query.filter(condition);
+    }
+
+    let paginator = query.paginate(&state.db, per_page);
+    let totals = paginator.num_items_and_pages().await?;
+    let files = paginator.fetch_page(page - 1).await?;
+
+    Ok((
+        StatusCode::OK,
+        Json(SearchResponse {
+            items: files.into_iter().map(SearchItem::from).collect(),
+            page,
+            per_page,
+            total_items: totals.number_of_items,
+            total_pages: totals.number_of_pages,
+        }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::{DbBackend, QueryTrait};
+
+    fn sql(params: &SearchParams) -> String {
+        build_search_query(Uuid::nil(), None, params)
+            .build(DbBackend::Postgres)
+            .to_string()
+    }
+
+    #[test]
+    fn validate_params_defaults() {
+        let params = SearchParams::default();
+        assert_eq!(validate_params(&params).unwrap(), (1, DEFAULT_PER_PAGE));
+    }
+
+    #[test]
+    fn validate_params_rejects_bad_pagination() {
+        let params = SearchParams { page: Some(0), ..Default::default() };
+        assert!(matches!(validate_params(&params), Err(AppError::BadRequest(_))));
+
+        let params = SearchParams { per_page: Some(MAX_PER_PAGE + 1), ..Default::default() };
+        assert!(matches!(validate_params(&params), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn validate_params_rejects_inverted_ranges() {
+        let params = SearchParams { min_size: Some(10), max_size: Some(1), ..Default::default() };
+        assert!(matches!(validate_params(&params), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn build_search_query_latest_only_by_default() {
+        let query = sql(&SearchParams::default());
+        assert!(query.contains("\"is_latest\" = TRUE"));
+        assert!(query.contains("ORDER BY \"files\".\"added_at\" DESC"));
+    }
+
+    #[test]
+    fn build_search_query_all_versions_and_filters() {
+        let params = SearchParams {
+            name: Some("rep".to_string()),
+            content_type: Some("image/*".to_string()),
+            min_size: Some(1),
+            all_versions: true,
+            sort: SortField::Size,
+            order: SortOrder::Asc,
+            ..Default::default()
+        };
+        let query = sql(&params);
+
+        assert!(!query.contains("\"is_latest\" = TRUE"));
+        assert!(query.contains("ILIKE ('%rep%'"));
+        assert!(query.contains("LIKE 'image/%'"));
+        assert!(query.contains("\"content_size\" >= 1"));
+        assert!(query.contains("ORDER BY \"files\".\"content_size\" ASC"));
+    }
+
+    #[test]
+    fn build_search_query_stays_within_credential_prefix() {
+        let query = build_search_query(Uuid::nil(), Some("backups/"), &SearchParams::default())
+            .build(DbBackend::Postgres)
+            .to_string();
+
+        assert!(query.contains("\"file_path\" LIKE 'backups/%'"));
+    }
+
+    #[test]
+    fn build_search_query_takes_the_credential_prefix_literally() {
+        let query = build_search_query(Uuid::nil(), Some("ci_builds/"), &SearchParams::default())
+            .build(DbBackend::Postgres)
+            .to_string();
+
+        assert!(query.contains(r#""file_path" LIKE E'ci\\_builds/%' ESCAPE E'\\'"#), "{}", query);
+    }
+}
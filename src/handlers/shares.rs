@@ -0,0 +1,336 @@
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, Condition, EntityTrait, QueryFilter,
+    QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
+
+use super::get::build_response_headers;
+use crate::audit::AuditRecord;
+use crate::auth::{link, AuthUser, Scope};
+use crate::entities::{share, user};
+use crate::error::AppError;
+use crate::objects::{self, Namespace};
+use crate::users;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateShareRequest {
+    pub key: String,
+    /// Version to share, the link follows the latest version when absent unless `pin_version` is set.
+    pub version_id: Option<String>,
+    #[serde(default)]
+    pub pin_version: bool,
+    pub password: Option<String>,
+    pub expires_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+    pub max_downloads: Option<i32>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ShareAccessParams {
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ShareResponse {
+    pub id: Uuid,
+    pub token: String,
+    pub url: String,
+    pub key: String,
+    pub version_id: Option<String>,
+    pub has_password: bool,
+    pub expires_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+    pub max_downloads: Option<i32>,
+    pub download_count: i32,
+    pub created_at: chrono::DateTime<chrono::FixedOffset>,
+    pub revoked_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+}
+
+impl From<share::Model> for ShareResponse {
+    fn from(share: share::Model) -> Self {
+        Self {
+            id: share.id,
+            url: format!("/s/{}", share.token),
+            token: share.token,
+            key: share.file_path,
+            version_id: share.version_id,
+            has_password: share.password_hash.is_some(),
+            expires_at: share.expires_at,
+            max_downloads: share.max_downloads,
+            download_count: share.download_count,
+            created_at: share.created_at,
+            revoked_at: share.revoked_at,
+        }
+    }
+}
+
+fn validate_new_share(request: &CreateShareRequest) -> Result<(), AppError> {
+    if request.max_downloads.is_some_and(|max| max < 1) {
+        return Err(AppError::BadRequest("max_downloads must be at least 1".to_string()));
+    }
+    if request.password.as_deref().is_some_and(str::is_empty) {
+        return Err(AppError::BadRequest("password cannot be empty".to_string()));
+    }
+    if request.expires_at.is_some_and(|exp| exp <= chrono::Utc::now()) {
+        return Err(AppError::BadRequest("expires_at must be in the future".to_string()));
+    }
+    Ok(())
+}
+
+/// Revoked links look like unknown ones, expired and exhausted links say why they stopped working.
+/// Links of a disabled owner, or of one whose account is being deleted, stop working too.
+fn check_share_usable(
+    share: &share::Model,
+    owner: Option<&user::Model>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<(), AppError> {
+    if share.revoked_at.is_some() {
+        return Err(AppError::NotFound("Share not found".to_string()));
+    }
+    if let Some(owner) = owner {
+        users::require_enabled(owner)?;
+    }
+    if share.expires_at.is_some_and(|exp| exp <= now) {
+        return Err(AppError::Forbidden("Share link has expired".to_string()));
+    }
+    if share.max_downloads.is_some_and(|max| share.download_count >= max) {
+        return Err(AppError::Forbidden("Download limit reached".to_string()));
+    }
+    Ok(())
+}
+
+/// Publishes an object of the caller's default namespace: shares name neither a bucket nor another owner.
+/// Like upload links, making objects public takes the write scope, a read-only key cannot.
+pub async fn create_share(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(request): Json<CreateShareRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require(Scope::Write, &request.key)?;
+    validate_new_share(&request)?;
+
+    let file_meta = objects::find_version(&state.db, auth.user_id, &request.key, request.version_id.as_deref())
+        .await?
+        .ok_or_else(|| AppError::NotFound("File not found".to_string()))?;
+
+    let version_id = (request.version_id.is_some() || request.pin_version).then_some(file_meta.s3_version_id);
+    let password_hash = request.password.as_deref().map(link::hash_password).transpose()?;
+
+    tracing::info!("Creating share of {}:{:?} for user {}", request.key, version_id, auth.user_id);
+
+    let share = share::ActiveModel::new(
+        link::generate_token(),
+        auth.user_id,
+        request.key,
+        version_id,
+        password_hash,
+        request.expires_at,
+        request.max_downloads,
+    )
+    .insert(&state.db)
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(ShareResponse::from(share))))
+}
+
+pub async fn list_shares(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Read)?;
+
+    let mut query = share::Entity::find()
+        .filter(share::Column::UserId.eq(auth.user_id))
+        .order_by_desc(share::Column::CreatedAt);
+    if let Some(ref key_prefix) = auth.path_prefix {
+        query = query.filter(objects::starts_with(share::Column::FilePath, key_prefix));
+    }
+
+    let shares: Vec<ShareResponse> = query
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(ShareResponse::from)
+        .collect();
+
+    Ok((StatusCode::OK, Json(shares)))
+}
+
+pub async fn revoke_share(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let share = share::Entity::find_by_id(id)
+        .filter(share::Column::UserId.eq(auth.user_id))
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Share not found".to_string()))?;
+    auth.require(Scope::Write, &share.file_path)?;
+
+    if share.revoked_at.is_none() {
+        let mut active: share::ActiveModel = share.into();
+        active.revoked_at = Set(Some(chrono::Utc::now().into()));
+        active.update(&state.db).await?;
+        tracing::info!("Revoked share {} of user {}", id, auth.user_id);
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "message": "Share revoked",
+            "id": id,
+        })),
+    ))
+}
+
+/// Unauthenticated download through a share token, the password goes in the
+/// `x-share-password` header or the `password` query parameter.
+pub async fn download_share(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+    Query(params): Query<ShareAccessParams>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, AppError> {
+    let share = share::Entity::find()
+        .filter(share::Column::Token.eq(token.as_str()))
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Share not found".to_string()))?;
+
+    let owner = user::Entity::find_by_id(share.user_id).one(&state.db).await?;
+    check_share_usable(&share, owner.as_ref(), chrono::Utc::now())?;
+    let password = headers
+        .get("x-share-password")
+        .and_then(|v| v.to_str().ok())
+        .or(params.password.as_deref());
+    link::check_password(share.password_hash.as_deref(), password)?;
+
+    let file_meta = objects::find_version(&state.db, share.user_id, &share.file_path, share.version_id.as_deref())
+        .await?
+        .ok_or_else(|| AppError::NotFound("File not found".to_string()))?;
+
+    // counted in the database so that concurrent downloads cannot exceed the limit
+    let counted = share::Entity::update_many()
+        .col_expr(share::Column::DownloadCount, Expr::col(share::Column::DownloadCount).add(1))
+        .filter(share::Column::Id.eq(share.id))
+        .filter(
+            Condition::any()
+                .add(share::Column::MaxDownloads.is_null())
+                .add(Expr::col(share::Column::DownloadCount).lt(Expr::col(share::Column::MaxDownloads))),
+        )
+        .exec(&state.db)
+        .await?;
+    if counted.rows_affected == 0 {
+        return Err(AppError::Forbidden("Download limit reached".to_string()));
+    }
+
+    tracing::info!("SHARE download {} of {} ({} of user {})", share.id, share.file_path, file_meta.s3_version_id, share.user_id);
+
+    let s3_output = state
+        .store_client
+        .get(&file_meta.file_key.to_string(), share.version_id.as_deref())
+        .await?;
+
+    let body = Body::from_stream(ReaderStream::new(s3_output.body.into_async_read()));
+    let response_headers = build_response_headers(&file_meta, s3_output.e_tag);
+
+    let record = AuditRecord::new(&Namespace::own(share.user_id), &file_meta).anonymous();
+    Ok(record.attach((StatusCode::OK, response_headers, body).into_response()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_share() -> share::Model {
+        share::Model {
+            id: Uuid::now_v7(),
+            token: "tok".to_string(),
+            user_id: Uuid::now_v7(),
+            file_path: "docs/report.pdf".to_string(),
+            version_id: None,
+            password_hash: None,
+            expires_at: None,
+            max_downloads: None,
+            download_count: 0,
+            created_at: chrono::Utc::now().into(),
+            revoked_at: None,
+        }
+    }
+
+    #[test]
+    fn check_share_usable_rejects_revoked_expired_and_exhausted() {
+        let now = chrono::Utc::now();
+        assert!(check_share_usable(&sample_share(), None, now).is_ok());
+
+        let mut revoked = sample_share();
+        revoked.revoked_at = Some(now.into());
+        assert!(matches!(check_share_usable(&revoked, None, now), Err(AppError::NotFound(_))));
+
+        let mut expired = sample_share();
+        expired.expires_at = Some((now - chrono::Duration::minutes(1)).into());
+        assert!(matches!(check_share_usable(&expired, None, now), Err(AppError::Forbidden(_))));
+
+        let mut exhausted = sample_share();
+        exhausted.max_downloads = Some(3);
+        exhausted.download_count = 3;
+        assert!(matches!(check_share_usable(&exhausted, None, now), Err(AppError::Forbidden(_))));
+    }
+
+    #[test]
+    fn check_share_usable_rejects_disabled_owner() {
+        let now = chrono::Utc::now();
+        let share = sample_share();
+        let mut owner = user::Model {
+            user_id: share.user_id,
+            total_space_used: 0,
+            quota_bytes: None,
+            updated_at: now.into(),
+            last_auto_sync_at: None,
+            change_seq: 0,
+            created_at: now.into(),
+            disabled_at: None,
+        };
+        assert!(check_share_usable(&share, Some(&owner), now).is_ok());
+
+        owner.disabled_at = Some(now.into());
+        assert!(matches!(check_share_usable(&share, Some(&owner), now), Err(AppError::Forbidden(_))));
+    }
+
+    #[test]
+    fn validate_new_share_checks_limits() {
+        let request = |max_downloads, password: Option<&str>| CreateShareRequest {
+            key: "a.txt".to_string(),
+            version_id: None,
+            pin_version: false,
+            password: password.map(str::to_string),
+            expires_at: None,
+            max_downloads,
+        };
+
+        assert!(validate_new_share(&request(Some(1), Some("pw"))).is_ok());
+        assert!(matches!(validate_new_share(&request(Some(0), None)), Err(AppError::BadRequest(_))));
+        assert!(matches!(validate_new_share(&request(None, Some(""))), Err(AppError::BadRequest(_))));
+    }
+
+    #[test]
+    fn share_response_hides_password_hash() {
+        let mut model = sample_share();
+        model.password_hash = Some("$argon2id$...".to_string());
+
+        let body = serde_json::to_value(ShareResponse::from(model)).unwrap();
+        assert_eq!(body["url"], "/s/tok");
+        assert_eq!(body["has_password"], true);
+        assert!(body.get("password_hash").is_none());
+    }
+}
@@ -0,0 +1,207 @@
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+    Json,
+};
+use sea_orm::{sea_query::Expr, ColumnTrait, EntityTrait, QueryFilter, QuerySelect, Select, Value};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::{access, AuthUser, Scope};
+use crate::entities::file;
+use crate::error::AppError;
+use crate::objects;
+use crate::AppState;
+
+const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct StatsParams {
+    /// Statistics of the objects of an organization of the caller, or shared with them by another user.
+    pub owner_id: Option<Uuid>,
+    /// Bucket to look into, the default bucket of the owner when absent.
+    pub bucket: Option<String>,
+    pub prefix: Option<String>,
+    pub depth: Option<usize>,
+    #[serde(default)]
+    pub all_versions: bool,
+}
+
+#[derive(Debug, Default, Serialize, PartialEq)]
+pub struct PrefixStats {
+    pub prefix: String,
+    pub is_prefix: bool,
+    pub object_count: u64,
+    pub total_size: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatsResponse {
+    pub prefix: String,
+    pub depth: usize,
+    pub all_versions: bool,
+    pub object_count: u64,
+    pub total_size: i64,
+    pub children: Vec<PrefixStats>,
+}
+
+/// Sums the objects of `query` per child entry under `prefix` at the given depth, in the database: a folder
+/// (ending with `/`) when the path goes deeper, or the object itself otherwise.
+fn group_by_child(query: Select<file::Entity>, prefix: &str, depth: usize) -> Select<file::Entity> {
+    // the path below the prefix, `substr` counts characters
+    let relative = "substr(\"file_path\", $1)";
+    let start = prefix.chars().count() as i32 + 1;
+    // the first `depth` segments, when followed by more
+    let folder = format!("^(?:[^/]*/){{{depth}}}");
+
+    query
+        .select_only()
+        .column_as(
+            Expr::cust_with_values(
+                format!("CASE WHEN {relative} ~ $2 THEN $3 || substring({relative} from $2) ELSE \"file_path\" END"),
+                [Value::from(start), Value::from(folder.clone()), Value::from(prefix)],
+            ),
+            "child",
+        )
+        .column_as(
+            Expr::cust_with_values(format!("{relative} ~ $2"), [Value::from(start), Value::from(folder)]),
+            "is_prefix",
+        )
+        .column_as(Expr::cust("COUNT(*)"), "object_count")
+        .column_as(Expr::cust("CAST(SUM(\"content_size\") AS BIGINT)"), "total_size")
+        .group_by(Expr::cust("1"))
+        .group_by(Expr::cust("2"))
+}
+
+/// Biggest first, the way a treemap wants them.
+fn sort_children(children: &mut [PrefixStats]) {
+    children.sort_by(|a, b| b.total_size.cmp(&a.total_size).then(a.prefix.cmp(&b.prefix)));
+}
+
+pub async fn prefix_stats(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Query(params): Query<StatsParams>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Read)?;
+    let (user_id, bucket_id) = match params.bucket {
+        Some(ref name) => {
+            let bucket = objects::find_bucket(&state.db, name).await?;
+            (bucket.owner_id, Some(bucket.id))
+        }
+        None => (params.owner_id.unwrap_or(auth.user_id), None),
+    };
+
+    let depth = params.depth.unwrap_or(1);
+    if depth == 0 || depth > MAX_DEPTH {
+        return Err(AppError::BadRequest(format!(
+            "depth must be between 1 and {}",
+            MAX_DEPTH
+        )));
+    }
+    let prefix = objects::normalize_prefix(params.prefix.as_deref());
+
+    tracing::info!(
+        "STATS request for user {}, prefix '{}' (depth {}, all versions: {})",
+        user_id,
+        prefix,
+        depth,
+        params.all_versions
+    );
+
+    let mut query = file::Entity::find()
+        .filter(file::Column::UserId.eq(user_id))
+        .filter(objects::bucket_condition(bucket_id));
+    if !prefix.is_empty() {
+        query = query.filter(objects::starts_with(file::Column::FilePath, &prefix));
+    }
+    if let Some(ref key_prefix) = auth.path_prefix {
+        query = query.filter(objects::starts_with(file::Column::FilePath, key_prefix));
+    }
+    if !params.all_versions {
+        query = query.filter(file::Column::IsLatest.eq(true));
+    }
+    if let Some(condition) = access::visible_files(&state.db, &auth, user_id, bucket_id).await? {
+        query = query.filter(condition);
+    }
+
+    let mut children: Vec<PrefixStats> = group_by_child(query, &prefix, depth)
+        .into_tuple::<(String, bool, i64, i64)>()
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(|(prefix, is_prefix, object_count, total_size)| PrefixStats {
+            prefix,
+            is_prefix,
+            object_count: object_count as u64,
+            total_size,
+        })
+        .collect();
+    sort_children(&mut children);
+
+    Ok((
+        StatusCode::OK,
+        Json(StatsResponse {
+            prefix,
+            depth,
+            all_versions: params.all_versions,
+            object_count: children.iter().map(|c| c.object_count).sum(),
+            total_size: children.iter().map(|c| c.total_size).sum(),
+            children,
+        }),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use sea_orm::{DbBackend, QueryTrait};
+
+    #[test]
+    fn group_by_child_sums_in_the_database() {
+        let sql = group_by_child(file::Entity::find(), "docs/", 2)
+            .build(DbBackend::Postgres)
+            .to_string();
+
+        assert!(sql.contains(
+            "CASE WHEN substr(\"file_path\", 6) ~ '^(?:[^/]*/){2}' \
+             THEN 'docs/' || substring(substr(\"file_path\", 6) from '^(?:[^/]*/){2}') \
+             ELSE \"file_path\" END AS \"child\""
+        ));
+        assert!(sql.contains("substr(\"file_path\", 6) ~ '^(?:[^/]*/){2}' AS \"is_prefix\""));
+        assert!(sql.contains("COUNT(*) AS \"object_count\""));
+        assert!(sql.contains("CAST(SUM(\"content_size\") AS BIGINT) AS \"total_size\""));
+        assert!(sql.ends_with("GROUP BY 1, 2"));
+        assert!(!sql.contains("\"files\".\"file_path\","));
+    }
+
+    #[test]
+    fn sort_children_puts_biggest_first() {
+        let child = |prefix: &str, is_prefix, object_count, total_size| PrefixStats {
+            prefix: prefix.to_string(),
+            is_prefix,
+            object_count,
+            total_size,
+        };
+        let mut stats = vec![
+            child("docs/c/", true, 1, 5),
+            child("docs/a/", true, 2, 30),
+            child("docs/root.txt", false, 1, 100),
+            child("docs/b/", true, 3, 30),
+        ];
+
+        sort_children(&mut stats);
+
+        assert_eq!(
+            stats,
+            vec![
+                child("docs/root.txt", false, 1, 100),
+                child("docs/a/", true, 2, 30),
+                child("docs/b/", true, 3, 30),
+                child("docs/c/", true, 1, 5),
+            ]
+        );
+    }
+}
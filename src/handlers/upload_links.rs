@@ -0,0 +1,356 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, Condition, EntityTrait, QueryFilter,
+    QueryOrder, Set,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use uuid::Uuid;
+
+use super::put::content_type_from_headers_or_path;
+use crate::audit::AuditRecord;
+use crate::auth::{link, AuthUser, Scope};
+use crate::checksums;
+use crate::entities::upload_link;
+use crate::error::AppError;
+use crate::objects;
+use crate::AppState;
+
+const MAX_FILE_NAME_LEN: usize = 255;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateUploadLinkRequest {
+    pub name: String,
+    pub prefix: String,
+    pub max_file_size: Option<i64>,
+    /// Accepted content types (`application/pdf`, `image/*`), anything is accepted when empty.
+    #[serde(default)]
+    pub content_types: Vec<String>,
+    pub max_files: Option<i32>,
+    pub expires_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UploadLinkResponse {
+    pub id: Uuid,
+    pub token: String,
+    pub url: String,
+    pub name: String,
+    pub prefix: String,
+    pub max_file_size: Option<i64>,
+    pub content_types: Vec<String>,
+    pub max_files: Option<i32>,
+    pub file_count: i32,
+    pub expires_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+    pub created_at: chrono::DateTime<chrono::FixedOffset>,
+    pub revoked_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+}
+
+impl From<upload_link::Model> for UploadLinkResponse {
+    fn from(link: upload_link::Model) -> Self {
+        Self {
+            id: link.id,
+            url: format!("/u/{}", link.token),
+            token: link.token,
+            name: link.name,
+            prefix: link.prefix,
+            max_file_size: link.max_file_size,
+            content_types: link
+                .content_types
+                .map(|types| types.split(',').map(str::to_string).collect())
+                .unwrap_or_default(),
+            max_files: link.max_files,
+            file_count: link.file_count,
+            expires_at: link.expires_at,
+            created_at: link.created_at,
+            revoked_at: link.revoked_at,
+        }
+    }
+}
+
+/// Uploads land in a folder, never the root.
+fn link_prefix(prefix: &str) -> Result<String, AppError> {
+    let prefix = objects::normalize_prefix(Some(prefix));
+    if prefix.is_empty() {
+        return Err(AppError::BadRequest("prefix cannot be empty".to_string()));
+    }
+    Ok(prefix)
+}
+
+fn validate_new_link(request: &CreateUploadLinkRequest) -> Result<(), AppError> {
+    if request.name.trim().is_empty() {
+        return Err(AppError::BadRequest("Upload link name cannot be empty".to_string()));
+    }
+    if request.max_file_size.is_some_and(|max| max < 1) {
+        return Err(AppError::BadRequest("max_file_size must be at least 1".to_string()));
+    }
+    if request.max_files.is_some_and(|max| max < 1) {
+        return Err(AppError::BadRequest("max_files must be at least 1".to_string()));
+    }
+    if request.content_types.iter().any(|ct| !ct.contains('/') || ct.contains(',')) {
+        return Err(AppError::BadRequest("content_types must look like 'type/subtype' or 'type/*'".to_string()));
+    }
+    if request.expires_at.is_some_and(|exp| exp <= chrono::Utc::now()) {
+        return Err(AppError::BadRequest("expires_at must be in the future".to_string()));
+    }
+    Ok(())
+}
+
+/// Uploaded files go straight into the link prefix, names cannot climb out of it.
+fn validate_file_name(name: &str) -> Result<(), AppError> {
+    if name.is_empty()
+        || name == "."
+        || name == ".."
+        || name.len() > MAX_FILE_NAME_LEN
+        || name.contains(['/', '\\'])
+        || name.chars().any(char::is_control)
+    {
+        return Err(AppError::BadRequest("Invalid file name".to_string()));
+    }
+    Ok(())
+}
+
+fn content_type_allowed(allowed: Option<&str>, content_type: &str) -> bool {
+    let Some(allowed) = allowed else {
+        return true;
+    };
+    // parameters such as "; charset=utf-8" do not matter
+    let content_type = content_type.split(';').next().unwrap_or_default().trim().to_ascii_lowercase();
+    allowed.split(',').any(|pattern| match pattern.strip_suffix('*') {
+        Some(type_prefix) => content_type.starts_with(&type_prefix.to_ascii_lowercase()),
+        None => content_type == pattern.to_ascii_lowercase(),
+    })
+}
+
+fn check_link_usable(link: &upload_link::Model, now: chrono::DateTime<chrono::Utc>) -> Result<(), AppError> {
+    if link.revoked_at.is_some() {
+        return Err(AppError::NotFound("Upload link not found".to_string()));
+    }
+    if link.expires_at.is_some_and(|exp| exp <= now) {
+        return Err(AppError::Forbidden("Upload link has expired".to_string()));
+    }
+    if link.max_files.is_some_and(|max| link.file_count >= max) {
+        return Err(AppError::Forbidden("This link does not accept more files".to_string()));
+    }
+    Ok(())
+}
+
+pub async fn create_upload_link(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(request): Json<CreateUploadLinkRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    let prefix = link_prefix(&request.prefix)?;
+    auth.require(Scope::Write, &prefix)?;
+    validate_new_link(&request)?;
+
+    tracing::info!("Creating upload link '{}' into {} for user {}", request.name, prefix, auth.user_id);
+
+    objects::ensure_user(&state, auth.user_id).await?;
+
+    let content_types = (!request.content_types.is_empty()).then(|| request.content_types.join(","));
+    let link = upload_link::ActiveModel::new(
+        link::generate_token(),
+        auth.user_id,
+        request.name.trim().to_string(),
+        prefix,
+        request.max_file_size,
+        content_types,
+        request.max_files,
+        request.expires_at,
+    )
+    .insert(&state.db)
+    .await?;
+
+    Ok((StatusCode::CREATED, Json(UploadLinkResponse::from(link))))
+}
+
+pub async fn list_upload_links(
+    State(state): State<AppState>,
+    auth: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Write)?;
+
+    let mut query = upload_link::Entity::find()
+        .filter(upload_link::Column::UserId.eq(auth.user_id))
+        .order_by_desc(upload_link::Column::CreatedAt);
+    if let Some(ref key_prefix) = auth.path_prefix {
+        query = query.filter(objects::starts_with(upload_link::Column::Prefix, key_prefix));
+    }
+
+    let links: Vec<UploadLinkResponse> = query
+        .all(&state.db)
+        .await?
+        .into_iter()
+        .map(UploadLinkResponse::from)
+        .collect();
+
+    Ok((StatusCode::OK, Json(links)))
+}
+
+pub async fn revoke_upload_link(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let link = upload_link::Entity::find_by_id(id)
+        .filter(upload_link::Column::UserId.eq(auth.user_id))
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Upload link not found".to_string()))?;
+    auth.require(Scope::Write, &link.prefix)?;
+
+    if link.revoked_at.is_none() {
+        let mut active: upload_link::ActiveModel = link.into();
+        active.revoked_at = Set(Some(chrono::Utc::now().into()));
+        active.update(&state.db).await?;
+        tracing::info!("Revoked upload link {} of user {}", id, auth.user_id);
+    }
+
+    Ok((
+        StatusCode::OK,
+        Json(json!({
+            "message": "Upload link revoked",
+            "id": id,
+        })),
+    ))
+}
+
+async fn add_to_file_count(state: &AppState, link: &upload_link::Model, delta: i32) -> Result<bool, AppError> {
+    let mut update = upload_link::Entity::update_many()
+        .col_expr(upload_link::Column::FileCount, Expr::col(upload_link::Column::FileCount).add(delta))
+        .filter(upload_link::Column::Id.eq(link.id));
+    if delta > 0 {
+        update = update.filter(
+            Condition::any()
+                .add(upload_link::Column::MaxFiles.is_null())
+                .add(Expr::col(upload_link::Column::FileCount).lt(Expr::col(upload_link::Column::MaxFiles))),
+        );
+    }
+    Ok(update.exec(&state.db).await?.rows_affected > 0)
+}
+
+/// Unauthenticated upload through an upload link, existing files can neither be read nor replaced.
+pub async fn upload_to_link(
+    State(state): State<AppState>,
+    Path((token, file_name)): Path<(String, String)>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<impl IntoResponse, AppError> {
+    let link = upload_link::Entity::find()
+        .filter(upload_link::Column::Token.eq(token.as_str()))
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Upload link not found".to_string()))?;
+
+    check_link_usable(&link, chrono::Utc::now())?;
+    validate_file_name(&file_name)?;
+
+    let content_size = body.len() as i64;
+    if link.max_file_size.is_some_and(|max| content_size > max) {
+        return Err(AppError::BadRequest(format!(
+            "File is larger than the {} bytes allowed by this link",
+            link.max_file_size.unwrap_or_default()
+        )));
+    }
+    let content_type = content_type_from_headers_or_path(&headers, &file_name);
+    if !content_type_allowed(link.content_types.as_deref(), &content_type) {
+        return Err(AppError::BadRequest(format!("Content type '{}' is not accepted by this link", content_type)));
+    }
+
+    let checksums = checksums::verify_upload(&headers, &body)?;
+
+    let key = format!("{}{}", link.prefix, file_name);
+    objects::check_quota(&state, link.user_id, content_size).await?;
+
+    if !add_to_file_count(&state, &link, 1).await? {
+        return Err(AppError::Forbidden("This link does not accept more files".to_string()));
+    }
+
+    tracing::info!("UPLOAD LINK {} request for key {} ({} bytes)", link.id, key, content_size);
+
+    let ns = objects::Namespace::own(link.user_id);
+    // checked when committing, so that two uploads of the same name cannot both get in
+    let conditions = objects::CommitConditions { no_overwrite: true, ..Default::default() };
+    let new_file = match objects::store_object(&state, ns, &key, content_type, body, &checksums, conditions).await {
+        Ok(new_file) => new_file,
+        Err(err) => {
+            add_to_file_count(&state, &link, -1).await?;
+            return Err(err);
+        }
+    };
+
+    let response = (
+        StatusCode::CREATED,
+        Json(json!({
+            "message": "File uploaded successfully",
+            "name": file_name,
+            "size": content_size,
+            "checksums": checksums.to_json(),
+        })),
+    );
+    Ok(AuditRecord::new(&ns, &new_file).anonymous().attach(response.into_response()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn link_prefix_adds_trailing_slash() {
+        assert_eq!(link_prefix("/partners/acme").unwrap(), "partners/acme/");
+        assert_eq!(link_prefix("inbox/").unwrap(), "inbox/");
+        assert!(link_prefix("/").is_err());
+    }
+
+    #[test]
+    fn validate_file_name_rejects_paths() {
+        assert!(validate_file_name("invoice 2026.pdf").is_ok());
+        for name in ["", ".", "..", "../secret", "a/b", "a\\b", "line\nbreak"] {
+            assert!(validate_file_name(name).is_err(), "{:?} should be rejected", name);
+        }
+    }
+
+    #[test]
+    fn content_type_allowed_matches_wildcards() {
+        let allowed = Some("application/pdf,image/*");
+
+        assert!(content_type_allowed(allowed, "application/pdf"));
+        assert!(content_type_allowed(allowed, "image/PNG"));
+        assert!(content_type_allowed(allowed, "application/pdf; charset=binary"));
+        assert!(!content_type_allowed(allowed, "text/plain"));
+        assert!(content_type_allowed(None, "text/plain"));
+    }
+
+    #[test]
+    fn check_link_usable_enforces_file_count() {
+        let now = chrono::Utc::now();
+        let mut link = upload_link::Model {
+            id: Uuid::now_v7(),
+            token: "tok".to_string(),
+            user_id: Uuid::now_v7(),
+            name: "acme".to_string(),
+            prefix: "partners/acme/".to_string(),
+            max_file_size: None,
+            content_types: None,
+            max_files: Some(2),
+            file_count: 1,
+            expires_at: None,
+            created_at: now.into(),
+            revoked_at: None,
+        };
+        assert!(check_link_usable(&link, now).is_ok());
+
+        link.file_count = 2;
+        assert!(matches!(check_link_usable(&link, now), Err(AppError::Forbidden(_))));
+
+        link.revoked_at = Some(now.into());
+        assert!(matches!(check_link_usable(&link, now), Err(AppError::NotFound(_))));
+    }
+}
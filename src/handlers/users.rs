@@ -0,0 +1,274 @@
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use sea_orm::{
+    sea_query::Expr, ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, TransactionTrait,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::{AuthUser, Scope};
+use crate::auth::org::Role;
+use crate::entities::{account_deletion, org_member, organization, user};
+use crate::error::AppError;
+use crate::transaction;
+use crate::users;
+use crate::AppState;
+
+#[derive(Debug, Deserialize)]
+pub struct CreateUserRequest {
+    pub user_id: Uuid,
+    /// `DEFAULT_QUOTA_BYTES` when missing.
+    pub quota_bytes: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserResponse {
+    pub user_id: Uuid,
+    pub created_at: chrono::DateTime<chrono::FixedOffset>,
+    pub disabled_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+    pub total_space_used: i64,
+    pub quota_bytes: Option<i64>,
+}
+
+impl From<user::Model> for UserResponse {
+    fn from(user: user::Model) -> Self {
+        Self {
+            user_id: user.user_id,
+            created_at: user.created_at,
+            disabled_at: user.disabled_at,
+            total_space_used: user.total_space_used,
+            quota_bytes: user.quota_bytes,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccountDeletionResponse {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub requested_by: Uuid,
+    pub status: &'static str,
+    pub versions_deleted: i64,
+    pub bytes_deleted: i64,
+    pub last_error: Option<String>,
+    pub created_at: chrono::DateTime<chrono::FixedOffset>,
+    pub completed_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+}
+
+impl From<account_deletion::Model> for AccountDeletionResponse {
+    fn from(job: account_deletion::Model) -> Self {
+        Self {
+            id: job.id,
+            user_id: job.user_id,
+            requested_by: job.requested_by,
+            status: if job.completed_at.is_some() { "completed" } else { "in_progress" },
+            versions_deleted: job.versions_deleted,
+            bytes_deleted: job.bytes_deleted,
+            last_error: job.last_error,
+            created_at: job.created_at,
+            completed_at: job.completed_at,
+        }
+    }
+}
+
+async fn find_user(state: &AppState, user_id: Uuid) -> Result<user::Model, AppError> {
+    user::Entity::find_by_id(user_id)
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("User not found".to_string()))
+}
+
+/// Provisions a user ahead of their first request, instance administrators only.
+pub async fn create_user(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Json(request): Json<CreateUserRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_instance_admin(&state.config.admin_user_ids)?;
+    if request.quota_bytes.is_some_and(|quota| quota < 0) {
+        return Err(AppError::BadRequest("quota_bytes must not be negative".to_string()));
+    }
+
+    let quota_bytes = request.quota_bytes.or(state.config.default_quota_bytes);
+    if !users::provision(&state.db, request.user_id, quota_bytes).await? {
+        return Err(AppError::Conflict(format!("User {} already exists", request.user_id)));
+    }
+    tracing::info!("User {} created user profile {}", auth.user_id, request.user_id);
+
+    let user = find_user(&state, request.user_id).await?;
+    Ok((StatusCode::CREATED, Json(UserResponse::from(user))))
+}
+
+/// Profile of the caller, or of anyone for instance administrators.
+pub async fn get_user(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Read)?;
+    if user_id != auth.user_id {
+        auth.require_instance_admin(&state.config.admin_user_ids)?;
+    }
+
+    let user = find_user(&state, user_id).await?;
+    Ok((StatusCode::OK, Json(UserResponse::from(user))))
+}
+
+/// Refuses every further request of the user, their objects and keys are kept.
+pub async fn disable_user(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_instance_admin(&state.config.admin_user_ids)?;
+    if user_id == auth.user_id {
+        return Err(AppError::BadRequest("Administrators cannot disable themselves".to_string()));
+    }
+
+    set_disabled_at(&state, user_id, Some(chrono::Utc::now().into())).await?;
+    tracing::info!("User {} disabled user {}", auth.user_id, user_id);
+
+    let user = find_user(&state, user_id).await?;
+    Ok((StatusCode::OK, Json(UserResponse::from(user))))
+}
+
+pub async fn enable_user(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_instance_admin(&state.config.admin_user_ids)?;
+    let deleting = account_deletion::Entity::find()
+        .filter(account_deletion::Column::UserId.eq(user_id))
+        .filter(account_deletion::Column::CompletedAt.is_null())
+        .count(&state.db)
+        .await?;
+    if deleting > 0 {
+        return Err(AppError::Conflict("The account of this user is being deleted".to_string()));
+    }
+
+    set_disabled_at(&state, user_id, None).await?;
+    tracing::info!("User {} enabled user {}", auth.user_id, user_id);
+
+    let user = find_user(&state, user_id).await?;
+    Ok((StatusCode::OK, Json(UserResponse::from(user))))
+}
+
+async fn set_disabled_at(
+    state: &AppState,
+    user_id: Uuid,
+    disabled_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+) -> Result<(), AppError> {
+    let updated = user::Entity::update_many()
+        .col_expr(user::Column::DisabledAt, Expr::value(disabled_at))
+        .col_expr(user::Column::UpdatedAt, Expr::value(chrono::Utc::now()))
+        .filter(user::Column::UserId.eq(user_id))
+        .exec(&state.db)
+        .await?;
+    if updated.rows_affected == 0 {
+        return Err(AppError::NotFound("User not found".to_string()));
+    }
+    Ok(())
+}
+
+/// Disables the user at once, then the deletion worker removes every version they own from the store and the
+/// database, and their profile last. Users can delete their own account, instance administrators any.
+pub async fn delete_user(
+    State(state): State<AppState>,
+    auth: AuthUser,
+    Path(user_id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    auth.require_scope(Scope::Admin)?;
+    if user_id != auth.user_id {
+        auth.require_instance_admin(&state.config.admin_user_ids)?;
+    }
+
+    find_user(&state, user_id).await?;
+    if organization::Entity::find_by_id(user_id).one(&state.db).await?.is_some() {
+        return Err(AppError::BadRequest("Organizations are deleted with DELETE /orgs/{id}".to_string()));
+    }
+    ensure_no_sole_ownership(&state, user_id).await?;
+
+    let existing = account_deletion::Entity::find()
+        .filter(account_deletion::Column::UserId.eq(user_id))
+        .filter(account_deletion::Column::CompletedAt.is_null())
+        .one(&state.db)
+        .await?;
+    let job = match existing {
+        Some(job) => job,
+        None => {
+            let (state, auth) = (&state, &auth);
+            transaction::retry(|| async move {
+                let txn = state.db.begin().await?;
+                user::Entity::update_many()
+                    .col_expr(user::Column::DisabledAt, Expr::value(chrono::Utc::now()))
+                    .col_expr(user::Column::UpdatedAt, Expr::value(chrono::Utc::now()))
+                    .filter(user::Column::UserId.eq(user_id))
+                    .filter(user::Column::DisabledAt.is_null())
+                    .exec(&txn)
+                    .await?;
+                let job = account_deletion::ActiveModel::new(user_id, auth.user_id).insert(&txn).await?;
+                txn.commit().await?;
+                Ok(job)
+            })
+            .await?
+        }
+    };
+    tracing::info!("User {} requested the deletion of user {} ({})", auth.user_id, user_id, job.id);
+
+    Ok((StatusCode::ACCEPTED, Json(AccountDeletionResponse::from(job))))
+}
+
+/// Organizations would be left without an owner.
+async fn ensure_no_sole_ownership(state: &AppState, user_id: Uuid) -> Result<(), AppError> {
+    let owned = org_member::Entity::find()
+        .filter(org_member::Column::UserId.eq(user_id))
+        .filter(org_member::Column::Role.eq(Role::Owner.as_str()))
+        .all(&state.db)
+        .await?;
+    for membership in owned {
+        let other_owners = org_member::Entity::find()
+            .filter(org_member::Column::OrgId.eq(membership.org_id))
+            .filter(org_member::Column::Role.eq(Role::Owner.as_str()))
+            .filter(org_member::Column::UserId.ne(user_id))
+            .count(&state.db)
+            .await?;
+        if other_owners == 0 {
+            return Err(AppError::Conflict(format!(
+                "User is the only owner of organization {}, transfer or delete it first",
+                membership.org_id
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Progress of an account deletion, and its receipt once completed.
+///
+/// The account is disabled as soon as its deletion starts, its owner can still follow their own job.
+pub async fn get_account_deletion(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let auth = state.auth.authenticate(&headers, &state.db).await?;
+    auth.require_scope(Scope::Read)?;
+
+    let job = account_deletion::Entity::find_by_id(id)
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::NotFound("Account deletion not found".to_string()))?;
+    if job.user_id != auth.user_id {
+        users::check_enabled(&state.db, auth.user_id).await?;
+        if job.requested_by != auth.user_id {
+            auth.require_instance_admin(&state.config.admin_user_ids)
+                .map_err(|_| AppError::NotFound("Account deletion not found".to_string()))?;
+        }
+    }
+
+    Ok((StatusCode::OK, Json(AccountDeletionResponse::from(job))))
+}
@@ -1,13 +1,36 @@
+mod archive;
+mod audit;
+mod auth;
+mod changes;
+mod checksums;
+mod cleanup;
 mod config;
+mod deletion;
 mod error;
+mod events;
+mod export;
+mod fsck;
 mod handlers;
+mod objects;
+mod s3api;
+mod scrub;
 mod storage;
+mod transaction;
+mod unpack;
+mod users;
 mod entities;
 
 use axum::{
-    routing::{get, head, put, delete},
+    extract::DefaultBodyLimit,
+    middleware,
+    routing::{get, head, put, delete, patch, post},
     Router,
 };
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use auth::Authenticator;
+use events::EventBus;
 use storage::S3Client;
 use sea_orm::{Database, DatabaseConnection};
 use tower::{ServiceBuilder};
@@ -20,6 +43,8 @@ pub struct AppState {
     pub store_client: S3Client,
     pub db: DatabaseConnection,
     pub config: Config,
+    pub auth: Arc<Authenticator>,
+    pub events: Arc<EventBus>,
 }
 
 #[tokio::main]
@@ -41,28 +66,122 @@ async fn main() -> anyhow::Result<()> {
     let store_client = S3Client::new(&config).await;
     tracing::info!("Object Store initialized");
 
+    let auth = Arc::new(Authenticator::from_config(&config)?);
+    tracing::info!("Authentication configured");
+
     let db = Database::connect(&config.db_url).await?;
     tracing::info!("Database connected");
 
+    let events = Arc::new(EventBus::from_config(&config, &db).await?);
+    tracing::info!("Event sinks configured");
+
     let state = AppState {
         store_client,
         db,
         config: config.clone(),
+        auth,
+        events,
     };
 
-    let app = Router::new()
+    // uploads, archives to extract included, go past axum's 2 MiB default
+    let upload_limit = DefaultBodyLimit::max(config.extract_max_bytes as usize);
+
+    // every object operation lands in the audit log
+    let object_routes = Router::new()
         .route("/objects/{*key}", get(handlers::get_object))
         .route("/objects/{*key}", head(handlers::head_object))
-        .route("/objects/{*key}", put(handlers::put_object))
+        .route("/objects/{*key}", put(handlers::put_object).layer(upload_limit))
         .route("/objects/{*key}", delete(handlers::delete_object))
+        .route("/restore/{*key}", post(handlers::restore_object))
+        .route("/move/{*key}", post(handlers::move_object))
+        .route("/archive/{*prefix}", get(handlers::download_archive))
+        .route("/buckets/{bucket}/objects/{*key}", get(handlers::get_bucket_object))
+        .route("/buckets/{bucket}/objects/{*key}", head(handlers::head_bucket_object))
+        .route("/buckets/{bucket}/objects/{*key}", put(handlers::put_bucket_object).layer(upload_limit))
+        .route("/buckets/{bucket}/objects/{*key}", delete(handlers::delete_bucket_object))
+        .route("/uploads/{id}/commit", post(handlers::commit_upload))
+        .route("/s/{token}", get(handlers::download_share))
+        .route("/u/{token}/{name}", put(handlers::upload_to_link).layer(upload_limit))
+        .layer(middleware::from_fn_with_state(state.clone(), audit::record));
+
+    let app = Router::new()
+        .merge(object_routes)
+        .route("/buckets", post(handlers::create_bucket))
+        .route("/buckets", get(handlers::list_buckets))
+        .route("/buckets/{bucket}", get(handlers::get_bucket))
+        .route("/buckets/{bucket}", patch(handlers::update_bucket))
+        .route("/buckets/{bucket}", delete(handlers::delete_bucket))
+        .route("/presign/{*key}", get(handlers::presign_download))
+        .route("/presign/{*key}", post(handlers::presign_upload))
+        .route("/shares", post(handlers::create_share))
+        .route("/shares", get(handlers::list_shares))
+        .route("/shares/{id}", delete(handlers::revoke_share))
+        .route("/upload-links", post(handlers::create_upload_link))
+        .route("/upload-links", get(handlers::list_upload_links))
+        .route("/upload-links/{id}", delete(handlers::revoke_upload_link))
+        .route("/grants", post(handlers::create_grant))
+        .route("/grants", get(handlers::list_grants))
+        .route("/grants/shared-with-me", get(handlers::list_shared_with_me))
+        .route("/grants/{id}", delete(handlers::revoke_grant))
+        .route("/orgs", post(handlers::create_org))
+        .route("/orgs", get(handlers::list_orgs))
+        .route("/orgs/{id}", get(handlers::get_org))
+        .route("/orgs/{id}", delete(handlers::delete_org))
+        .route("/orgs/{id}/members", get(handlers::list_members))
+        .route("/orgs/{id}/members/{user_id}", put(handlers::set_member))
+        .route("/orgs/{id}/members/{user_id}", delete(handlers::remove_member))
+        .route("/search", get(handlers::search_objects))
+        .route("/stats", get(handlers::prefix_stats))
+        .route("/changes", get(handlers::list_changes))
+        .route("/events", get(handlers::stream_events))
+        .route("/audit", get(handlers::list_audit_events))
+        .route("/admin/fsck", post(handlers::run_fsck))
+        .route("/admin/scrub", get(handlers::scrub_report))
+        .route("/users", post(handlers::create_user))
+        .route("/users/{user_id}", get(handlers::get_user))
+        .route("/users/{user_id}", delete(handlers::delete_user))
+        .route("/users/{user_id}/disable", post(handlers::disable_user))
+        .route("/users/{user_id}/enable", post(handlers::enable_user))
+        .route("/account-deletions/{id}", get(handlers::get_account_deletion))
+        .route("/exports", post(handlers::create_export))
+        .route("/exports", get(handlers::list_exports))
+        .route("/exports/{id}", get(handlers::get_export))
+        .route("/api-keys", post(handlers::create_api_key))
+        .route("/api-keys", get(handlers::list_api_keys))
+        .route("/api-keys/{id}", delete(handlers::revoke_api_key))
+        .route("/s3-keys", post(handlers::create_s3_key))
+        .route("/s3-keys", get(handlers::list_s3_keys))
+        .route("/s3-keys/{access_key_id}", delete(handlers::revoke_s3_key))
         .layer(ServiceBuilder::new().layer(TraceLayer::new_for_http()))
-        .with_state(state);
+        .with_state(state.clone());
+
+    audit::spawn_retention(state.clone());
+    cleanup::spawn_sweeper(state.clone());
+    scrub::spawn_scrubber(state.clone());
+    deletion::spawn_worker(state.clone());
+    export::spawn_worker(state.clone());
 
     let addr = format!("{}:{}", config.server_host, config.server_port);
     let listener = tokio::net::TcpListener::bind(&addr).await?;
     tracing::info!("Server listening on {}", addr);
 
-    axum::serve(listener, app).await?;
+    // the S3 compatible front end gets its own listener, S3 clients expect buckets at the root
+    if let Some(s3_api_port) = config.s3_api_port {
+        let s3_addr = format!("{}:{}", config.server_host, s3_api_port);
+        let s3_listener = tokio::net::TcpListener::bind(&s3_addr).await?;
+        tracing::info!("S3 API listening on {} (bucket '{}')", s3_addr, config.s3_api_bucket);
+
+        tokio::try_join!(
+            axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).into_future(),
+            axum::serve(
+                s3_listener,
+                s3api::router(state).into_make_service_with_connect_info::<SocketAddr>()
+            )
+            .into_future(),
+        )?;
+    } else {
+        axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).await?;
+    }
 
     Ok(())
 }
\ No newline at end of file
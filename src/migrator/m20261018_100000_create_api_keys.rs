@@ -0,0 +1,82 @@
+use sea_orm_migration::{async_trait, prelude::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(ApiKeys::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(ApiKeys::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(ApiKeys::UserId).uuid().not_null())
+                .col(ColumnDef::new(ApiKeys::Name).string().not_null())
+                .col(ColumnDef::new(ApiKeys::KeyHash).string().not_null().unique_key())
+                .col(ColumnDef::new(ApiKeys::Scopes).string().not_null())
+                .col(ColumnDef::new(ApiKeys::PathPrefix).string().null())
+                .col(ColumnDef::new(ApiKeys::ExpiresAt).timestamp_with_time_zone().null())
+                .col(ColumnDef::new(ApiKeys::LastUsedAt).timestamp_with_time_zone().null())
+                .col(
+                    ColumnDef::new(ApiKeys::CreatedAt)
+                        .timestamp_with_time_zone()
+                        .not_null()
+                        .default(Expr::current_timestamp()),
+                )
+                .col(ColumnDef::new(ApiKeys::RevokedAt).timestamp_with_time_zone().null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_api_keys_user_id")
+                        .from(ApiKeys::Table, ApiKeys::UserId)
+                        .to(Users::Table, Users::UserId)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_api_keys_user_id")
+                .table(ApiKeys::Table)
+                .col(ApiKeys::UserId)
+                .to_owned(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(ApiKeys::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum ApiKeys {
+    Table,
+    Id,
+    UserId,
+    Name,
+    KeyHash,
+    Scopes,
+    PathPrefix,
+    ExpiresAt,
+    LastUsedAt,
+    CreatedAt,
+    RevokedAt,
+}
@@ -0,0 +1,135 @@
+use sea_orm_migration::{async_trait, prelude::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(S3Credentials::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(S3Credentials::AccessKeyId)
+                        .string()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(S3Credentials::UserId).uuid().not_null())
+                .col(ColumnDef::new(S3Credentials::Name).string().not_null())
+                .col(ColumnDef::new(S3Credentials::SecretAccessKey).string().not_null())
+                .col(ColumnDef::new(S3Credentials::Scopes).string().not_null())
+                .col(ColumnDef::new(S3Credentials::PathPrefix).string().null())
+                .col(ColumnDef::new(S3Credentials::ExpiresAt).timestamp_with_time_zone().null())
+                .col(
+                    ColumnDef::new(S3Credentials::CreatedAt)
+                        .timestamp_with_time_zone()
+                        .not_null()
+                        .default(Expr::current_timestamp()),
+                )
+                .col(ColumnDef::new(S3Credentials::RevokedAt).timestamp_with_time_zone().null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_s3_credentials_user_id")
+                        .from(S3Credentials::Table, S3Credentials::UserId)
+                        .to(Users::Table, Users::UserId)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_table(
+            Table::create()
+                .table(MultipartUploads::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(MultipartUploads::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(MultipartUploads::UserId).uuid().not_null())
+                .col(ColumnDef::new(MultipartUploads::FilePath).string().not_null())
+                .col(ColumnDef::new(MultipartUploads::FileKey).uuid().not_null())
+                .col(ColumnDef::new(MultipartUploads::BackendUploadId).string().not_null())
+                .col(ColumnDef::new(MultipartUploads::ContentType).string().not_null())
+                .col(
+                    ColumnDef::new(MultipartUploads::CreatedAt)
+                        .timestamp_with_time_zone()
+                        .not_null()
+                        .default(Expr::current_timestamp()),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_multipart_uploads_user_id")
+                        .from(MultipartUploads::Table, MultipartUploads::UserId)
+                        .to(Users::Table, Users::UserId)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_s3_credentials_user_id")
+                .table(S3Credentials::Table)
+                .col(S3Credentials::UserId)
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_multipart_uploads_user_id")
+                .table(MultipartUploads::Table)
+                .col(MultipartUploads::UserId)
+                .to_owned(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(MultipartUploads::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(S3Credentials::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum S3Credentials {
+    Table,
+    AccessKeyId,
+    UserId,
+    Name,
+    SecretAccessKey,
+    Scopes,
+    PathPrefix,
+    ExpiresAt,
+    CreatedAt,
+    RevokedAt,
+}
+
+#[derive(DeriveIden)]
+enum MultipartUploads {
+    Table,
+    Id,
+    UserId,
+    FilePath,
+    FileKey,
+    BackendUploadId,
+    ContentType,
+    CreatedAt,
+}
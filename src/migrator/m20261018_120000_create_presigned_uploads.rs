@@ -0,0 +1,76 @@
+use sea_orm_migration::{async_trait, prelude::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(PresignedUploads::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(PresignedUploads::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(PresignedUploads::UserId).uuid().not_null())
+                .col(ColumnDef::new(PresignedUploads::FilePath).string().not_null())
+                .col(ColumnDef::new(PresignedUploads::FileKey).uuid().not_null())
+                .col(ColumnDef::new(PresignedUploads::ContentType).string().not_null())
+                .col(ColumnDef::new(PresignedUploads::ExpiresAt).timestamp_with_time_zone().not_null())
+                .col(
+                    ColumnDef::new(PresignedUploads::CreatedAt)
+                        .timestamp_with_time_zone()
+                        .not_null()
+                        .default(Expr::current_timestamp()),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_presigned_uploads_user_id")
+                        .from(PresignedUploads::Table, PresignedUploads::UserId)
+                        .to(Users::Table, Users::UserId)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_presigned_uploads_user_id")
+                .table(PresignedUploads::Table)
+                .col(PresignedUploads::UserId)
+                .to_owned(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(PresignedUploads::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum PresignedUploads {
+    Table,
+    Id,
+    UserId,
+    FilePath,
+    FileKey,
+    ContentType,
+    ExpiresAt,
+    CreatedAt,
+}
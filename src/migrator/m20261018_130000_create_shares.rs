@@ -0,0 +1,84 @@
+use sea_orm_migration::{async_trait, prelude::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(Shares::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(Shares::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(Shares::Token).string().not_null().unique_key())
+                .col(ColumnDef::new(Shares::UserId).uuid().not_null())
+                .col(ColumnDef::new(Shares::FilePath).string().not_null())
+                .col(ColumnDef::new(Shares::VersionId).string().null())
+                .col(ColumnDef::new(Shares::PasswordHash).string().null())
+                .col(ColumnDef::new(Shares::ExpiresAt).timestamp_with_time_zone().null())
+                .col(ColumnDef::new(Shares::MaxDownloads).integer().null())
+                .col(ColumnDef::new(Shares::DownloadCount).integer().not_null().default(0))
+                .col(
+                    ColumnDef::new(Shares::CreatedAt)
+                        .timestamp_with_time_zone()
+                        .not_null()
+                        .default(Expr::current_timestamp()),
+                )
+                .col(ColumnDef::new(Shares::RevokedAt).timestamp_with_time_zone().null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_shares_user_id")
+                        .from(Shares::Table, Shares::UserId)
+                        .to(Users::Table, Users::UserId)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_shares_user_id")
+                .table(Shares::Table)
+                .col(Shares::UserId)
+                .to_owned(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Shares::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum Shares {
+    Table,
+    Id,
+    Token,
+    UserId,
+    FilePath,
+    VersionId,
+    PasswordHash,
+    ExpiresAt,
+    MaxDownloads,
+    DownloadCount,
+    CreatedAt,
+    RevokedAt,
+}
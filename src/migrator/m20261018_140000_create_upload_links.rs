@@ -0,0 +1,112 @@
+use sea_orm_migration::{async_trait, prelude::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(Users::Table)
+                .add_column_if_not_exists(ColumnDef::new(Users::QuotaBytes).big_integer().null())
+                .to_owned(),
+        )
+        .await?;
+
+        // total_space_used is maintained from now on, start from what is already stored
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "UPDATE users SET total_space_used = COALESCE(
+                    (SELECT SUM(files.content_size) FROM files WHERE files.user_id = users.user_id), 0
+                )",
+            )
+            .await?;
+
+        manager.create_table(
+            Table::create()
+                .table(UploadLinks::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(UploadLinks::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(UploadLinks::Token).string().not_null().unique_key())
+                .col(ColumnDef::new(UploadLinks::UserId).uuid().not_null())
+                .col(ColumnDef::new(UploadLinks::Name).string().not_null())
+                .col(ColumnDef::new(UploadLinks::Prefix).string().not_null())
+                .col(ColumnDef::new(UploadLinks::MaxFileSize).big_integer().null())
+                .col(ColumnDef::new(UploadLinks::ContentTypes).string().null())
+                .col(ColumnDef::new(UploadLinks::MaxFiles).integer().null())
+                .col(ColumnDef::new(UploadLinks::FileCount).integer().not_null().default(0))
+                .col(ColumnDef::new(UploadLinks::ExpiresAt).timestamp_with_time_zone().null())
+                .col(
+                    ColumnDef::new(UploadLinks::CreatedAt)
+                        .timestamp_with_time_zone()
+                        .not_null()
+                        .default(Expr::current_timestamp()),
+                )
+                .col(ColumnDef::new(UploadLinks::RevokedAt).timestamp_with_time_zone().null())
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_upload_links_user_id")
+                        .from(UploadLinks::Table, UploadLinks::UserId)
+                        .to(Users::Table, Users::UserId)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_upload_links_user_id")
+                .table(UploadLinks::Table)
+                .col(UploadLinks::UserId)
+                .to_owned(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(UploadLinks::Table).to_owned()).await?;
+        manager.alter_table(
+            Table::alter()
+                .table(Users::Table)
+                .drop_column(Users::QuotaBytes)
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    UserId,
+    QuotaBytes,
+}
+
+#[derive(DeriveIden)]
+enum UploadLinks {
+    Table,
+    Id,
+    Token,
+    UserId,
+    Name,
+    Prefix,
+    MaxFileSize,
+    ContentTypes,
+    MaxFiles,
+    FileCount,
+    ExpiresAt,
+    CreatedAt,
+    RevokedAt,
+}
@@ -0,0 +1,116 @@
+use sea_orm_migration::{async_trait, prelude::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(Files::Table)
+                .add_column_if_not_exists(ColumnDef::new(Files::UploadedBy).uuid().null())
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_table(
+            Table::create()
+                .table(Grants::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(Grants::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(Grants::OwnerId).uuid().not_null())
+                .col(ColumnDef::new(Grants::GranteeId).uuid().not_null())
+                .col(ColumnDef::new(Grants::Prefix).string().not_null())
+                .col(ColumnDef::new(Grants::Permission).string().not_null())
+                .col(
+                    ColumnDef::new(Grants::CreatedAt)
+                        .timestamp_with_time_zone()
+                        .not_null()
+                        .default(Expr::current_timestamp()),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_grants_owner_id")
+                        .from(Grants::Table, Grants::OwnerId)
+                        .to(Users::Table, Users::UserId)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_grants_grantee_id")
+                        .from(Grants::Table, Grants::GranteeId)
+                        .to(Users::Table, Users::UserId)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_grants_owner_id")
+                .table(Grants::Table)
+                .col(Grants::OwnerId)
+                .to_owned(),
+        )
+        .await?;
+
+        // one grant per owner, grantee and prefix, granting again updates the permission
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_grants_grantee_owner_prefix")
+                .table(Grants::Table)
+                .col(Grants::GranteeId)
+                .col(Grants::OwnerId)
+                .col(Grants::Prefix)
+                .unique()
+                .to_owned(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Grants::Table).to_owned()).await?;
+        manager.alter_table(
+            Table::alter()
+                .table(Files::Table)
+                .drop_column(Files::UploadedBy)
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum Files {
+    Table,
+    UploadedBy,
+}
+
+#[derive(DeriveIden)]
+enum Grants {
+    Table,
+    Id,
+    OwnerId,
+    GranteeId,
+    Prefix,
+    Permission,
+    CreatedAt,
+}
@@ -0,0 +1,139 @@
+use sea_orm_migration::{async_trait, prelude::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // the organization id is the users row of its namespace
+        manager.create_table(
+            Table::create()
+                .table(Organizations::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(Organizations::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(Organizations::Name).string().not_null())
+                .col(ColumnDef::new(Organizations::CreatedBy).uuid().not_null())
+                .col(
+                    ColumnDef::new(Organizations::CreatedAt)
+                        .timestamp_with_time_zone()
+                        .not_null()
+                        .default(Expr::current_timestamp()),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_organizations_id")
+                        .from(Organizations::Table, Organizations::Id)
+                        .to(Users::Table, Users::UserId)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_table(
+            Table::create()
+                .table(OrgMembers::Table)
+                .if_not_exists()
+                .col(ColumnDef::new(OrgMembers::OrgId).uuid().not_null())
+                .col(ColumnDef::new(OrgMembers::UserId).uuid().not_null())
+                .col(ColumnDef::new(OrgMembers::Role).string().not_null())
+                .col(
+                    ColumnDef::new(OrgMembers::CreatedAt)
+                        .timestamp_with_time_zone()
+                        .not_null()
+                        .default(Expr::current_timestamp()),
+                )
+                .primary_key(
+                    Index::create()
+                        .col(OrgMembers::OrgId)
+                        .col(OrgMembers::UserId),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_org_members_org_id")
+                        .from(OrgMembers::Table, OrgMembers::OrgId)
+                        .to(Organizations::Table, Organizations::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_org_members_user_id")
+                        .from(OrgMembers::Table, OrgMembers::UserId)
+                        .to(Users::Table, Users::UserId)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_org_members_user_id")
+                .table(OrgMembers::Table)
+                .col(OrgMembers::UserId)
+                .to_owned(),
+        )
+        .await?;
+
+        // presigned uploads can target the namespace of an organization
+        manager.alter_table(
+            Table::alter()
+                .table(PresignedUploads::Table)
+                .add_column_if_not_exists(ColumnDef::new(PresignedUploads::OwnerId).uuid().null())
+                .to_owned(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(PresignedUploads::Table)
+                .drop_column(PresignedUploads::OwnerId)
+                .to_owned(),
+        )
+        .await?;
+        manager.drop_table(Table::drop().table(OrgMembers::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(Organizations::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum Organizations {
+    Table,
+    Id,
+    Name,
+    CreatedBy,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum OrgMembers {
+    Table,
+    OrgId,
+    UserId,
+    Role,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum PresignedUploads {
+    Table,
+    OwnerId,
+}
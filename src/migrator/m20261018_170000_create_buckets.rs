@@ -0,0 +1,121 @@
+use sea_orm_migration::{async_trait, prelude::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(Buckets::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(Buckets::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(Buckets::Name).string().not_null().unique_key())
+                .col(ColumnDef::new(Buckets::OwnerId).uuid().not_null())
+                .col(ColumnDef::new(Buckets::Versioning).boolean().not_null().default(true))
+                .col(ColumnDef::new(Buckets::DefaultRetentionDays).integer().null())
+                .col(ColumnDef::new(Buckets::Public).boolean().not_null().default(false))
+                .col(
+                    ColumnDef::new(Buckets::CreatedAt)
+                        .timestamp_with_time_zone()
+                        .not_null()
+                        .default(Expr::current_timestamp()),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_buckets_owner_id")
+                        .from(Buckets::Table, Buckets::OwnerId)
+                        .to(Users::Table, Users::UserId)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_buckets_owner_id")
+                .table(Buckets::Table)
+                .col(Buckets::OwnerId)
+                .to_owned(),
+        )
+        .await?;
+
+        // existing files stay in the default bucket of their owner
+        manager.alter_table(
+            Table::alter()
+                .table(Files::Table)
+                .add_column_if_not_exists(ColumnDef::new(Files::BucketId).uuid().null())
+                .add_column_if_not_exists(ColumnDef::new(Files::RetainUntil).timestamp_with_time_zone().null())
+                .add_foreign_key(
+                    TableForeignKey::new()
+                        .name("fk_files_bucket_id")
+                        .from_tbl(Files::Table)
+                        .from_col(Files::BucketId)
+                        .to_tbl(Buckets::Table)
+                        .to_col(Buckets::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_files_bucket_id")
+                .table(Files::Table)
+                .col(Files::BucketId)
+                .to_owned(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(Files::Table)
+                .drop_foreign_key(Alias::new("fk_files_bucket_id"))
+                .drop_column(Files::BucketId)
+                .drop_column(Files::RetainUntil)
+                .to_owned(),
+        )
+        .await?;
+        manager.drop_table(Table::drop().table(Buckets::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    UserId,
+}
+
+#[derive(DeriveIden)]
+enum Files {
+    Table,
+    BucketId,
+    RetainUntil,
+}
+
+#[derive(DeriveIden)]
+enum Buckets {
+    Table,
+    Id,
+    Name,
+    OwnerId,
+    Versioning,
+    DefaultRetentionDays,
+    Public,
+    CreatedAt,
+}
@@ -0,0 +1,99 @@
+use sea_orm_migration::{async_trait, prelude::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(AuditEvents::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(AuditEvents::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(
+                    ColumnDef::new(AuditEvents::OccurredAt)
+                        .timestamp_with_time_zone()
+                        .not_null()
+                        .default(Expr::current_timestamp()),
+                )
+                .col(ColumnDef::new(AuditEvents::RequestId).string().not_null())
+                .col(ColumnDef::new(AuditEvents::Action).string().not_null())
+                .col(ColumnDef::new(AuditEvents::Path).string().not_null())
+                .col(ColumnDef::new(AuditEvents::ActorId).uuid().null())
+                .col(ColumnDef::new(AuditEvents::OwnerId).uuid().null())
+                .col(ColumnDef::new(AuditEvents::BucketId).uuid().null())
+                .col(ColumnDef::new(AuditEvents::FilePath).string().null())
+                .col(ColumnDef::new(AuditEvents::VersionId).string().null())
+                .col(ColumnDef::new(AuditEvents::Bytes).big_integer().null())
+                .col(ColumnDef::new(AuditEvents::ClientIp).string().null())
+                .col(ColumnDef::new(AuditEvents::UserAgent).string().null())
+                .col(ColumnDef::new(AuditEvents::Status).integer().not_null())
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_audit_events_occurred_at")
+                .table(AuditEvents::Table)
+                .col(AuditEvents::OccurredAt)
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_audit_events_owner_id_occurred_at")
+                .table(AuditEvents::Table)
+                .col(AuditEvents::OwnerId)
+                .col(AuditEvents::OccurredAt)
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_audit_events_actor_id_occurred_at")
+                .table(AuditEvents::Table)
+                .col(AuditEvents::ActorId)
+                .col(AuditEvents::OccurredAt)
+                .to_owned(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(AuditEvents::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum AuditEvents {
+    Table,
+    Id,
+    OccurredAt,
+    RequestId,
+    Action,
+    Path,
+    ActorId,
+    OwnerId,
+    BucketId,
+    FilePath,
+    VersionId,
+    Bytes,
+    ClientIp,
+    UserAgent,
+    Status,
+}
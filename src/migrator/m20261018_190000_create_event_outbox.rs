@@ -0,0 +1,69 @@
+use sea_orm_migration::{async_trait, prelude::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(EventOutbox::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(EventOutbox::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(EventOutbox::Payload).json_binary().not_null())
+                .col(ColumnDef::new(EventOutbox::Attempts).integer().not_null().default(0))
+                .col(
+                    ColumnDef::new(EventOutbox::NextAttemptAt)
+                        .timestamp_with_time_zone()
+                        .not_null(),
+                )
+                .col(ColumnDef::new(EventOutbox::DeliveredAt).timestamp_with_time_zone().null())
+                .col(ColumnDef::new(EventOutbox::FailedAt).timestamp_with_time_zone().null())
+                .col(ColumnDef::new(EventOutbox::LastError).string().null())
+                .col(
+                    ColumnDef::new(EventOutbox::CreatedAt)
+                        .timestamp_with_time_zone()
+                        .not_null()
+                        .default(Expr::current_timestamp()),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_event_outbox_next_attempt_at")
+                .table(EventOutbox::Table)
+                .col(EventOutbox::NextAttemptAt)
+                .to_owned(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(EventOutbox::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum EventOutbox {
+    Table,
+    Id,
+    Payload,
+    Attempts,
+    NextAttemptAt,
+    DeliveredAt,
+    FailedAt,
+    LastError,
+    CreatedAt,
+}
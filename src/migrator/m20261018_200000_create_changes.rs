@@ -0,0 +1,94 @@
+use sea_orm_migration::{async_trait, prelude::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // last sequence number given to a change of the namespace, bumped under the row lock
+        manager.alter_table(
+            Table::alter()
+                .table(Users::Table)
+                .add_column(
+                    ColumnDef::new(Users::ChangeSeq)
+                        .big_integer()
+                        .not_null()
+                        .default(0),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_table(
+            Table::create()
+                .table(Changes::Table)
+                .if_not_exists()
+                .col(ColumnDef::new(Changes::OwnerId).uuid().not_null())
+                .col(ColumnDef::new(Changes::Seq).big_integer().not_null())
+                .col(ColumnDef::new(Changes::Kind).string().not_null())
+                .col(ColumnDef::new(Changes::BucketId).uuid().null())
+                .col(ColumnDef::new(Changes::FilePath).string().not_null())
+                .col(ColumnDef::new(Changes::FromPath).string().null())
+                .col(ColumnDef::new(Changes::VersionId).string().not_null())
+                .col(ColumnDef::new(Changes::ContentSize).big_integer().not_null())
+                .col(ColumnDef::new(Changes::ActorId).uuid().not_null())
+                .col(
+                    ColumnDef::new(Changes::OccurredAt)
+                        .timestamp_with_time_zone()
+                        .not_null()
+                        .default(Expr::current_timestamp()),
+                )
+                .primary_key(
+                    Index::create()
+                        .col(Changes::OwnerId)
+                        .col(Changes::Seq),
+                )
+                .foreign_key(
+                    ForeignKey::create()
+                        .name("fk_changes_owner_id")
+                        .from(Changes::Table, Changes::OwnerId)
+                        .to(Users::Table, Users::UserId)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Changes::Table).to_owned()).await?;
+        manager.alter_table(
+            Table::alter()
+                .table(Users::Table)
+                .drop_column(Users::ChangeSeq)
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Users {
+    Table,
+    UserId,
+    ChangeSeq,
+}
+
+#[derive(DeriveIden)]
+enum Changes {
+    Table,
+    OwnerId,
+    Seq,
+    Kind,
+    BucketId,
+    FilePath,
+    FromPath,
+    VersionId,
+    ContentSize,
+    ActorId,
+    OccurredAt,
+}
@@ -0,0 +1,75 @@
+use sea_orm_migration::{async_trait, prelude::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(PendingUploads::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(PendingUploads::FileKey)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(PendingUploads::OwnerId).uuid().not_null())
+                .col(ColumnDef::new(PendingUploads::FilePath).string().not_null())
+                .col(
+                    ColumnDef::new(PendingUploads::CreatedAt)
+                        .timestamp_with_time_zone()
+                        .not_null()
+                        .default(Expr::current_timestamp()),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_pending_uploads_created_at")
+                .table(PendingUploads::Table)
+                .col(PendingUploads::CreatedAt)
+                .to_owned(),
+        )
+        .await?;
+
+        // the sweeper checks that nothing references an object before removing it
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_files_file_key")
+                .table(Files::Table)
+                .col(Files::FileKey)
+                .to_owned(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_index(Index::drop().name("idx_files_file_key").table(Files::Table).to_owned()).await?;
+        manager.drop_table(Table::drop().table(PendingUploads::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum PendingUploads {
+    Table,
+    FileKey,
+    OwnerId,
+    FilePath,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Files {
+    Table,
+    FileKey,
+}
@@ -0,0 +1,54 @@
+use sea_orm_migration::{async_trait, prelude::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.alter_table(
+            Table::alter()
+                .table(Files::Table)
+                .add_column(ColumnDef::new(Files::ChecksumSha256).string().null())
+                .add_column(ColumnDef::new(Files::VerifiedAt).timestamp_with_time_zone().null())
+                .add_column(ColumnDef::new(Files::CorruptedAt).timestamp_with_time_zone().null())
+                .to_owned(),
+        )
+        .await?;
+
+        // the scrubber goes through the versions verified the longest time ago first
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_files_verified_at")
+                .table(Files::Table)
+                .col(Files::VerifiedAt)
+                .to_owned(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_index(Index::drop().name("idx_files_verified_at").table(Files::Table).to_owned()).await?;
+        manager.alter_table(
+            Table::alter()
+                .table(Files::Table)
+                .drop_column(Files::ChecksumSha256)
+                .drop_column(Files::VerifiedAt)
+                .drop_column(Files::CorruptedAt)
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Files {
+    Table,
+    ChecksumSha256,
+    VerifiedAt,
+    CorruptedAt,
+}
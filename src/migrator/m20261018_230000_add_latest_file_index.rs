@@ -0,0 +1,47 @@
+use sea_orm_migration::{async_trait, prelude::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // concurrent uploads may already have left several latest versions of a path, the newest one stays
+        db.execute_unprepared(
+            "UPDATE files SET is_latest = false
+            WHERE is_latest AND EXISTS (
+                SELECT 1 FROM files AS newer
+                WHERE newer.is_latest
+                    AND newer.user_id = files.user_id
+                    AND newer.bucket_id IS NOT DISTINCT FROM files.bucket_id
+                    AND newer.file_path = files.file_path
+                    AND (newer.added_at, newer.id) > (files.added_at, files.id)
+            )",
+        )
+        .await?;
+
+        // the default bucket has a NULL bucket_id, which a plain unique index would never compare equal
+        db.execute_unprepared(
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_files_latest_path ON files (
+                user_id,
+                (COALESCE(bucket_id, '00000000-0000-0000-0000-000000000000'::UUID)),
+                file_path
+            ) WHERE is_latest",
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_index(Index::drop().name("idx_files_latest_path").table(Files::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Files {
+    Table,
+}
\ No newline at end of file
@@ -0,0 +1,72 @@
+use sea_orm_migration::{async_trait, prelude::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(AccountDeletions::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(AccountDeletions::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(AccountDeletions::UserId).uuid().not_null())
+                .col(ColumnDef::new(AccountDeletions::RequestedBy).uuid().not_null())
+                .col(ColumnDef::new(AccountDeletions::VersionsDeleted).big_integer().not_null().default(0))
+                .col(ColumnDef::new(AccountDeletions::BytesDeleted).big_integer().not_null().default(0))
+                .col(ColumnDef::new(AccountDeletions::LastError).string().null())
+                .col(
+                    ColumnDef::new(AccountDeletions::CreatedAt)
+                        .timestamp_with_time_zone()
+                        .not_null()
+                        .default(Expr::current_timestamp()),
+                )
+                .col(
+                    ColumnDef::new(AccountDeletions::UpdatedAt)
+                        .timestamp_with_time_zone()
+                        .not_null()
+                        .default(Expr::current_timestamp()),
+                )
+                .col(ColumnDef::new(AccountDeletions::CompletedAt).timestamp_with_time_zone().null())
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_account_deletions_user_id")
+                .table(AccountDeletions::Table)
+                .col(AccountDeletions::UserId)
+                .to_owned(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(AccountDeletions::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum AccountDeletions {
+    Table,
+    Id,
+    UserId,
+    RequestedBy,
+    VersionsDeleted,
+    BytesDeleted,
+    LastError,
+    CreatedAt,
+    UpdatedAt,
+    CompletedAt,
+}
\ No newline at end of file
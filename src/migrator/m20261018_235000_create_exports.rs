@@ -0,0 +1,86 @@
+use sea_orm_migration::{async_trait, prelude::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.create_table(
+            Table::create()
+                .table(Exports::Table)
+                .if_not_exists()
+                .col(
+                    ColumnDef::new(Exports::Id)
+                        .uuid()
+                        .not_null()
+                        .primary_key(),
+                )
+                .col(ColumnDef::new(Exports::UserId).uuid().not_null())
+                .col(ColumnDef::new(Exports::RequestedBy).uuid().not_null())
+                .col(ColumnDef::new(Exports::AllVersions).boolean().not_null().default(false))
+                .col(ColumnDef::new(Exports::ObjectKey).string().not_null())
+                .col(ColumnDef::new(Exports::BackendUploadId).string().null())
+                .col(ColumnDef::new(Exports::ObjectVersionId).string().null())
+                .col(ColumnDef::new(Exports::SizeBytes).big_integer().null())
+                .col(ColumnDef::new(Exports::FileCount).big_integer().null())
+                .col(ColumnDef::new(Exports::Attempts).integer().not_null().default(0))
+                .col(ColumnDef::new(Exports::LastError).string().null())
+                .col(
+                    ColumnDef::new(Exports::CreatedAt)
+                        .timestamp_with_time_zone()
+                        .not_null()
+                        .default(Expr::current_timestamp()),
+                )
+                .col(
+                    ColumnDef::new(Exports::UpdatedAt)
+                        .timestamp_with_time_zone()
+                        .not_null()
+                        .default(Expr::current_timestamp()),
+                )
+                .col(ColumnDef::new(Exports::CompletedAt).timestamp_with_time_zone().null())
+                .col(ColumnDef::new(Exports::FailedAt).timestamp_with_time_zone().null())
+                .col(ColumnDef::new(Exports::ExpiresAt).timestamp_with_time_zone().null())
+                .to_owned(),
+        )
+        .await?;
+
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_exports_user_id")
+                .table(Exports::Table)
+                .col(Exports::UserId)
+                .to_owned(),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager.drop_table(Table::drop().table(Exports::Table).to_owned()).await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Exports {
+    Table,
+    Id,
+    UserId,
+    RequestedBy,
+    AllVersions,
+    ObjectKey,
+    BackendUploadId,
+    ObjectVersionId,
+    SizeBytes,
+    FileCount,
+    Attempts,
+    LastError,
+    CreatedAt,
+    UpdatedAt,
+    CompletedAt,
+    FailedAt,
+    ExpiresAt,
+}
\ No newline at end of file
@@ -0,0 +1,93 @@
+use sea_orm_migration::{async_trait, prelude::*};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // existing grants cover the default bucket of their owner
+        manager.alter_table(
+            Table::alter()
+                .table(Grants::Table)
+                .add_column_if_not_exists(ColumnDef::new(Grants::BucketId).uuid().null())
+                .add_foreign_key(
+                    TableForeignKey::new()
+                        .name("fk_grants_bucket_id")
+                        .from_tbl(Grants::Table)
+                        .from_col(Grants::BucketId)
+                        .to_tbl(Buckets::Table)
+                        .to_col(Buckets::Id)
+                        .on_delete(ForeignKeyAction::Cascade),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+        manager.drop_index(
+            Index::drop()
+                .if_exists()
+                .name("idx_grants_grantee_owner_prefix")
+                .table(Grants::Table)
+                .to_owned(),
+        )
+        .await?;
+
+        // one grant per owner, grantee, bucket and prefix, with the default bucket (NULL) compared equal
+        manager
+            .get_connection()
+            .execute_unprepared(
+                "CREATE UNIQUE INDEX IF NOT EXISTS idx_grants_grantee_owner_bucket_prefix ON grants (
+                    grantee_id,
+                    owner_id,
+                    (COALESCE(bucket_id, '00000000-0000-0000-0000-000000000000'::UUID)),
+                    prefix
+                )",
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(Index::drop().name("idx_grants_grantee_owner_bucket_prefix").table(Grants::Table).to_owned())
+            .await?;
+        manager.alter_table(
+            Table::alter()
+                .table(Grants::Table)
+                .drop_foreign_key(Alias::new("fk_grants_bucket_id"))
+                .drop_column(Grants::BucketId)
+                .to_owned(),
+        )
+        .await?;
+        manager.create_index(
+            Index::create()
+                .if_not_exists()
+                .name("idx_grants_grantee_owner_prefix")
+                .table(Grants::Table)
+                .col(Grants::GranteeId)
+                .col(Grants::OwnerId)
+                .col(Grants::Prefix)
+                .unique()
+                .to_owned(),
+        )
+        .await?;
+        Ok(())
+    }
+}
+
+#[derive(DeriveIden)]
+enum Grants {
+    Table,
+    GranteeId,
+    OwnerId,
+    BucketId,
+    Prefix,
+}
+
+#[derive(DeriveIden)]
+enum Buckets {
+    Table,
+    Id,
+}
\ No newline at end of file
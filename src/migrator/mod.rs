@@ -6,9 +6,45 @@ pub struct Migrator;
 impl MigratorTrait for Migrator {
     fn migrations() -> Vec<Box<dyn MigrationTrait>> {
         vec![
-            Box::new(m20251128_165000_create_tables::Migration)
+            Box::new(m20251128_165000_create_tables::Migration),
+            Box::new(m20261018_100000_create_api_keys::Migration),
+            Box::new(m20261018_110000_create_s3_tables::Migration),
+            Box::new(m20261018_120000_create_presigned_uploads::Migration),
+            Box::new(m20261018_130000_create_shares::Migration),
+            Box::new(m20261018_140000_create_upload_links::Migration),
+            Box::new(m20261018_150000_create_grants::Migration),
+            Box::new(m20261018_160000_create_organizations::Migration),
+            Box::new(m20261018_170000_create_buckets::Migration),
+            Box::new(m20261018_180000_create_audit_events::Migration),
+            Box::new(m20261018_190000_create_event_outbox::Migration),
+            Box::new(m20261018_200000_create_changes::Migration),
+            Box::new(m20261018_210000_create_pending_uploads::Migration),
+            Box::new(m20261018_220000_add_file_checksums::Migration),
+            Box::new(m20261018_230000_add_latest_file_index::Migration),
+            Box::new(m20261018_233000_add_user_lifecycle::Migration),
+            Box::new(m20261018_234000_create_account_deletions::Migration),
+            Box::new(m20261018_235000_create_exports::Migration),
+            Box::new(m20261018_236000_add_grant_bucket::Migration),
         ]
     }
 }
 
-pub mod m20251128_165000_create_tables;
\ No newline at end of file
+pub mod m20251128_165000_create_tables;
+pub mod m20261018_100000_create_api_keys;
+pub mod m20261018_110000_create_s3_tables;
+pub mod m20261018_120000_create_presigned_uploads;
+pub mod m20261018_130000_create_shares;
+pub mod m20261018_140000_create_upload_links;
+pub mod m20261018_150000_create_grants;
+pub mod m20261018_160000_create_organizations;
+pub mod m20261018_170000_create_buckets;
+pub mod m20261018_180000_create_audit_events;
+pub mod m20261018_190000_create_event_outbox;
+pub mod m20261018_200000_create_changes;
+pub mod m20261018_210000_create_pending_uploads;
+pub mod m20261018_220000_add_file_checksums;
+pub mod m20261018_230000_add_latest_file_index;
+pub mod m20261018_233000_add_user_lifecycle;
+pub mod m20261018_234000_create_account_deletions;
+pub mod m20261018_235000_create_exports;
+pub mod m20261018_236000_add_grant_bucket;
\ No newline at end of file
@@ -0,0 +1,535 @@
+use bytes::Bytes;
+use sea_orm::{
+    sea_query::{Expr, LikeExpr, SimpleExpr},
+    ActiveModelTrait, ColumnTrait, Condition, ConnectionTrait, DbErr, EntityTrait,
+    ModelTrait, QueryFilter, Set, TransactionTrait,
+};
+use uuid::Uuid;
+
+use crate::changes::{self, ChangeKind};
+use crate::checksums::Checksums;
+use crate::entities::{bucket, file, pending_upload, presigned_upload, user};
+use crate::error::AppError;
+use crate::events::{EventKind, ObjectEvent};
+use crate::transaction;
+use crate::users;
+use crate::AppState;
+
+/// Whose objects an operation touches, in which bucket, and who performs it.
+///
+/// Owner and actor are the same user unless the operation goes through a grant: the owner is charged
+/// for the storage while the version is attributed to the actor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Namespace {
+    pub owner_id: Uuid,
+    pub actor_id: Uuid,
+    /// The owner's default bucket when empty.
+    pub bucket_id: Option<Uuid>,
+    pub versioning: bool,
+    pub default_retention_days: Option<i32>,
+}
+
+impl Namespace {
+    pub fn own(user_id: Uuid) -> Self {
+        Self::acting(user_id, user_id)
+    }
+
+    /// Default bucket of `owner_id`, keeps every version with no retention.
+    pub fn acting(owner_id: Uuid, actor_id: Uuid) -> Self {
+        Self {
+            owner_id,
+            actor_id,
+            bucket_id: None,
+            versioning: true,
+            default_retention_days: None,
+        }
+    }
+
+    pub fn in_bucket(self, bucket: &bucket::Model) -> Self {
+        Self {
+            bucket_id: Some(bucket.id),
+            versioning: bucket.versioning,
+            default_retention_days: bucket.default_retention_days,
+            ..self
+        }
+    }
+
+    fn retain_until(&self) -> Option<chrono::DateTime<chrono::FixedOffset>> {
+        self.default_retention_days
+            .map(|days| (chrono::Utc::now() + chrono::Duration::days(days.into())).into())
+    }
+}
+
+/// Latest version of `key` in the owner's default bucket, or the given version when `version_id` is set.
+pub async fn find_version<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    key: &str,
+    version_id: Option<&str>,
+) -> Result<Option<file::Model>, DbErr> {
+    find_in_bucket(db, user_id, None, key, version_id).await
+}
+
+/// Same as `find_version` in the given bucket.
+pub async fn find_in_bucket<C: ConnectionTrait>(
+    db: &C,
+    user_id: Uuid,
+    bucket_id: Option<Uuid>,
+    key: &str,
+    version_id: Option<&str>,
+) -> Result<Option<file::Model>, DbErr> {
+    let mut query = file::Entity::find()
+        .filter(file::Column::UserId.eq(user_id))
+        .filter(bucket_condition(bucket_id))
+        .filter(file::Column::FilePath.eq(key));
+
+    if let Some(vid) = version_id {
+        query = query.filter(file::Column::S3VersionId.eq(vid));
+    } else {
+        query = query.filter(file::Column::IsLatest.eq(true));
+    }
+
+    query.one(db).await
+}
+
+/// Files of the given bucket, the default one when `None`.
+pub fn bucket_condition(bucket_id: Option<Uuid>) -> Condition {
+    match bucket_id {
+        Some(id) => Condition::all().add(file::Column::BucketId.eq(id)),
+        None => Condition::all().add(file::Column::BucketId.is_null()),
+    }
+}
+
+/// "docs" and "docs/" both mean the docs folder, "" is the root.
+pub fn normalize_prefix(prefix: Option<&str>) -> String {
+    match prefix.map(|p| p.trim_start_matches('/')) {
+        None | Some("") => String::new(),
+        Some(p) if p.ends_with('/') => p.to_string(),
+        Some(p) => format!("{}/", p),
+    }
+}
+
+/// Escapes the `LIKE` wildcards of user input, to be used with `ESCAPE '\\'`.
+pub fn escape_like(input: &str) -> String {
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// `column` starts with `prefix`, taken literally: unlike `ColumnTrait::starts_with`, `%` and `_` in it are
+/// no wildcards.
+pub fn starts_with<C: ColumnTrait>(column: C, prefix: &str) -> SimpleExpr {
+    Expr::col(column.as_column_ref()).like(LikeExpr::new(format!("{}%", escape_like(prefix))).escape('\\'))
+}
+
+pub async fn find_bucket<C: ConnectionTrait>(db: &C, name: &str) -> Result<bucket::Model, AppError> {
+    bucket::Entity::find()
+        .filter(bucket::Column::Name.eq(name))
+        .one(db)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Bucket '{}' not found", name)))
+}
+
+fn check_retention(file_meta: &file::Model) -> Result<(), AppError> {
+    match file_meta.retain_until {
+        Some(until) if until > chrono::Utc::now() => Err(AppError::Forbidden(format!(
+            "Version {} of {} is retained until {}",
+            file_meta.s3_version_id, file_meta.file_path, until
+        ))),
+        _ => Ok(()),
+    }
+}
+
+pub fn file_name_from_key(key: &str) -> String {
+    key.rsplit('/').next().unwrap_or(key).to_string()
+}
+
+/// Profile of `user_id`, provisioned with the default quota on first use unless `AUTO_PROVISION_USERS` is off.
+pub async fn ensure_user(state: &AppState, user_id: Uuid) -> Result<user::Model, AppError> {
+    if state.config.auto_provision_users && users::provision(&state.db, user_id, state.config.default_quota_bytes).await? {
+        tracing::info!("Created new user profile {}", user_id);
+    }
+    let user = user::Entity::find_by_id(user_id)
+        .one(&state.db)
+        .await?
+        .ok_or_else(|| AppError::Forbidden(format!("User {} has no profile", user_id)))?;
+    users::require_enabled(&user)?;
+    Ok(user)
+}
+
+fn quota_exceeded(quota_bytes: Option<i64>) -> AppError {
+    AppError::QuotaExceeded(format!(
+        "Storage quota of {} bytes exceeded",
+        quota_bytes.unwrap_or_default()
+    ))
+}
+
+/// Early check before sending `size` bytes to the store, the authoritative one is done by `commit_version`.
+pub async fn check_quota(state: &AppState, user_id: Uuid, size: i64) -> Result<(), AppError> {
+    let user = user::Entity::find_by_id(user_id).one(&state.db).await?;
+    match user {
+        Some(user) if user.quota_bytes.is_some_and(|quota| user.total_space_used + size > quota) => {
+            Err(quota_exceeded(user.quota_bytes))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Adds `size` bytes to the space used by the user, unless it goes over their quota.
+async fn reserve_space<C: ConnectionTrait>(db: &C, user_id: Uuid, size: i64) -> Result<bool, DbErr> {
+    let reserved = user::Entity::update_many()
+        .col_expr(user::Column::TotalSpaceUsed, Expr::col(user::Column::TotalSpaceUsed).add(size))
+        .col_expr(user::Column::UpdatedAt, Expr::value(chrono::Utc::now()))
+        .filter(user::Column::UserId.eq(user_id))
+        .filter(
+            Condition::any()
+                .add(user::Column::QuotaBytes.is_null())
+                .add(Expr::col(user::Column::TotalSpaceUsed).lte(Expr::col(user::Column::QuotaBytes).sub(size))),
+        )
+        .exec(db)
+        .await?;
+    Ok(reserved.rows_affected > 0)
+}
+
+async fn release_space<C: ConnectionTrait>(db: &C, user_id: Uuid, size: i64) -> Result<(), DbErr> {
+    user::Entity::update_many()
+        .col_expr(user::Column::TotalSpaceUsed, Expr::col(user::Column::TotalSpaceUsed).sub(size))
+        .col_expr(user::Column::UpdatedAt, Expr::value(chrono::Utc::now()))
+        .filter(user::Column::UserId.eq(user_id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+/// Checks made in the transaction that records a new version, where concurrent commits cannot slip past them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CommitConditions {
+    /// Fails with a conflict when `key` already has a latest version.
+    pub no_overwrite: bool,
+    /// Presigned upload consumed by the commit, fails when another commit already consumed it.
+    pub presigned_upload: Option<Uuid>,
+}
+
+/// Records an object already written in the store at `file_key` as the latest version of `key`.
+#[allow(clippy::too_many_arguments)]
+pub async fn commit_version(
+    state: &AppState,
+    ns: Namespace,
+    key: &str,
+    file_key: Uuid,
+    content_type: String,
+    content_size: i64,
+    s3_version_id: String,
+    checksum_sha256: Option<String>,
+    conditions: CommitConditions,
+) -> Result<file::Model, AppError> {
+    let user_id = ns.owner_id;
+
+    // a concurrent upload of the same key makes the insert fail on the latest version index, the retry demotes its row
+    let (new_file, replaced, event) = transaction::retry(|| {
+        let (content_type, s3_version_id, checksum_sha256) =
+            (content_type.clone(), s3_version_id.clone(), checksum_sha256.clone());
+        async move {
+            let txn = state.db.begin().await?;
+
+            if conditions.no_overwrite && find_in_bucket(&txn, user_id, ns.bucket_id, key, None).await?.is_some() {
+                txn.rollback().await?;
+                abandon_upload(state, file_key, &s3_version_id).await;
+                return Err(AppError::Conflict("A file with this name already exists".to_string()));
+            }
+            if let Some(id) = conditions.presigned_upload {
+                // the object stays, it belongs to the commit that removed the row
+                if presigned_upload::Entity::delete_by_id(id).exec(&txn).await?.rows_affected == 0 {
+                    txn.rollback().await?;
+                    return Err(AppError::Conflict("Upload has already been committed".to_string()));
+                }
+            }
+
+            // without versioning the new version replaces every previous one, and frees their space first
+            let replaced = if ns.versioning {
+                Vec::new()
+            } else {
+                let replaced = file::Entity::find()
+                    .filter(file::Column::UserId.eq(user_id))
+                    .filter(bucket_condition(ns.bucket_id))
+                    .filter(file::Column::FilePath.eq(key))
+                    .all(&txn)
+                    .await?;
+                if let Err(err) = replaced.iter().try_for_each(check_retention) {
+                    txn.rollback().await?;
+                    abandon_upload(state, file_key, &s3_version_id).await;
+                    return Err(err);
+                }
+                for old_file in &replaced {
+                    changes::record(&txn, &ns, ChangeKind::Delete, old_file, None).await?;
+                    old_file.clone().delete(&txn).await?;
+                    release_space(&txn, user_id, old_file.content_size).await?;
+                }
+                replaced
+            };
+
+            if !reserve_space(&txn, user_id, content_size).await? {
+                txn.rollback().await?;
+                // the object is already in the store, it would never be reachable
+                abandon_upload(state, file_key, &s3_version_id).await;
+                let user = user::Entity::find_by_id(user_id).one(&state.db).await?;
+                return Err(quota_exceeded(user.and_then(|u| u.quota_bytes)));
+            }
+
+            let existing_active_file = find_in_bucket(&txn, user_id, ns.bucket_id, key, None).await?;
+            if let Some(old_file) = existing_active_file {
+                let mut old_active: file::ActiveModel = old_file.into();
+                old_active.is_latest = Set(false);
+                old_active.update(&txn).await?;
+            }
+            let mut new_file_entry = file::ActiveModel::new(
+                file_key,
+                user_id,
+                file_name_from_key(key),
+                key.to_string(),
+                content_type,
+                content_size,
+                s3_version_id,
+            );
+            new_file_entry.uploaded_by = Set(Some(ns.actor_id));
+            new_file_entry.bucket_id = Set(ns.bucket_id);
+            new_file_entry.retain_until = Set(ns.retain_until());
+            new_file_entry.checksum_sha256 = Set(checksum_sha256);
+            let new_file = new_file_entry.insert(&txn).await?;
+            changes::record(&txn, &ns, ChangeKind::Create, &new_file, None).await?;
+            let event = ObjectEvent::new(EventKind::ObjectCreated, &ns, &new_file);
+            state.events.stage(&txn, &event).await?;
+            // the object is referenced from now on, the sweeper must leave it alone
+            pending_upload::Entity::delete_by_id(file_key).exec(&txn).await?;
+
+            txn.commit().await?;
+            Ok((new_file, replaced, event))
+        }
+    })
+    .await?;
+
+    for old_file in replaced {
+        discard_object(state, old_file.file_key, &old_file.s3_version_id).await;
+    }
+
+    state.events.publish(event).await;
+
+    Ok(new_file)
+}
+
+async fn discard_object(state: &AppState, file_key: Uuid, s3_version_id: &str) -> bool {
+    match state.store_client.delete(&file_key.to_string(), Some(s3_version_id)).await {
+        Ok(_) => true,
+        Err(err) => {
+            tracing::error!("Failed to remove object {} from the store: {:?}", file_key, err);
+            false
+        }
+    }
+}
+
+/// Removes an object that will never be committed, the sweeper retries when the store fails.
+async fn abandon_upload(state: &AppState, file_key: Uuid, s3_version_id: &str) {
+    if !discard_object(state, file_key, s3_version_id).await {
+        return;
+    }
+    if let Err(err) = pending_upload::Entity::delete_by_id(file_key).exec(&state.db).await {
+        tracing::error!("Failed to forget pending upload {}: {:?}", file_key, err);
+    }
+}
+
+/// Uploads `body` under a new storage key and records it as the latest version of `key`.
+///
+/// `checksums` must come from `checksums::verify_upload`, they are forwarded so the store checks the body again.
+pub async fn store_object(
+    state: &AppState,
+    ns: Namespace,
+    key: &str,
+    content_type: String,
+    body: Bytes,
+    checksums: &Checksums,
+    conditions: CommitConditions,
+) -> Result<file::Model, AppError> {
+    let content_size = body.len() as i64;
+
+    ensure_user(state, ns.owner_id).await?;
+    check_quota(state, ns.owner_id, content_size).await?;
+
+    // recorded before writing to the store, so that a failure at any step leaves a trace for the sweeper
+    let file_key = Uuid::now_v7();
+    pending_upload::ActiveModel::new(file_key, ns.owner_id, key.to_string())
+        .insert(&state.db)
+        .await?;
+    let s3_output = state.store_client.put(&file_key.to_string(), body, checksums).await?;
+    let s3_version_id = s3_output.version_id.unwrap_or_else(|| "null".to_string());
+
+    let checksum_sha256 = Some(checksums.sha256_hex());
+    commit_version(state, ns, key, file_key, content_type, content_size, s3_version_id, checksum_sha256, conditions).await
+}
+
+/// Removes one version from the store and from the files table, unless it is still retained.
+pub async fn delete_version(state: &AppState, ns: Namespace, file_meta: file::Model) -> Result<(), AppError> {
+    check_retention(&file_meta)?;
+
+    // delete from s3 storage
+    state
+        .store_client
+        .delete(&file_meta.file_key.to_string(), Some(&file_meta.s3_version_id))
+        .await?;
+
+    // delete from db
+    let event = ObjectEvent::new(EventKind::ObjectDeleted, &ns, &file_meta);
+    let (file_meta, event_ref) = (&file_meta, &event);
+    transaction::retry(|| async move {
+        let txn = state.db.begin().await?;
+        changes::record(&txn, &ns, ChangeKind::Delete, file_meta, None).await?;
+        state.events.stage(&txn, event_ref).await?;
+        file::Entity::delete_by_id(file_meta.id).exec(&txn).await?;
+        release_space(&txn, file_meta.user_id, file_meta.content_size).await?;
+        txn.commit().await?;
+        Ok(())
+    })
+    .await?;
+
+    state.events.publish(event).await;
+
+    Ok(())
+}
+
+/// Makes an older version the latest one of its key again, nothing changes in the store.
+pub async fn restore_version(state: &AppState, ns: Namespace, file_meta: file::Model) -> Result<file::Model, AppError> {
+    if file_meta.is_latest {
+        return Ok(file_meta);
+    }
+
+    let file_meta = &file_meta;
+    let (restored, event) = transaction::retry(|| async move {
+        let txn = state.db.begin().await?;
+        file::Entity::update_many()
+            .col_expr(file::Column::IsLatest, Expr::value(false))
+            .filter(file::Column::UserId.eq(file_meta.user_id))
+            .filter(bucket_condition(file_meta.bucket_id))
+            .filter(file::Column::FilePath.eq(file_meta.file_path.clone()))
+            .filter(file::Column::IsLatest.eq(true))
+            .exec(&txn)
+            .await?;
+        let mut restored: file::ActiveModel = file_meta.clone().into();
+        restored.is_latest = Set(true);
+        let restored = restored.update(&txn).await?;
+        // the restored version becomes the content of the key, like a new upload
+        changes::record(&txn, &ns, ChangeKind::Create, &restored, None).await?;
+        let event = ObjectEvent::new(EventKind::VersionRestored, &ns, &restored);
+        state.events.stage(&txn, &event).await?;
+        txn.commit().await?;
+        Ok((restored, event))
+    })
+    .await?;
+
+    state.events.publish(event).await;
+
+    Ok(restored)
+}
+
+/// Renames every version of `from` to `to`, the objects stay where they are in the store.
+pub async fn move_object(state: &AppState, ns: Namespace, from: &str, to: &str) -> Result<file::Model, AppError> {
+    if from == to {
+        return Err(AppError::BadRequest("Source and destination are the same key".to_string()));
+    }
+
+    let (moved, event) = transaction::retry(|| async move {
+        let txn = state.db.begin().await?;
+        let versions = file::Entity::find()
+            .filter(file::Column::UserId.eq(ns.owner_id))
+            .filter(bucket_condition(ns.bucket_id))
+            .filter(file::Column::FilePath.eq(from))
+            .all(&txn)
+            .await?;
+        let latest = versions
+            .iter()
+            .find(|f| f.is_latest)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound("File not found".to_string()))?;
+        // retained versions stay under the key they were written to
+        versions.iter().try_for_each(check_retention)?;
+        if find_in_bucket(&txn, ns.owner_id, ns.bucket_id, to, None).await?.is_some() {
+            return Err(AppError::Conflict(format!("Object '{}' already exists", to)));
+        }
+
+        file::Entity::update_many()
+            .col_expr(file::Column::FilePath, Expr::value(to))
+            .col_expr(file::Column::FileName, Expr::value(file_name_from_key(to)))
+            .filter(file::Column::UserId.eq(ns.owner_id))
+            .filter(bucket_condition(ns.bucket_id))
+            .filter(file::Column::FilePath.eq(from))
+            .exec(&txn)
+            .await?;
+        let moved = file::Model {
+            file_path: to.to_string(),
+            file_name: file_name_from_key(to),
+            ..latest
+        };
+        changes::record(&txn, &ns, ChangeKind::Move, &moved, Some(from.to_string())).await?;
+        let mut event = ObjectEvent::new(EventKind::ObjectMoved, &ns, &moved);
+        event.from_key = Some(from.to_string());
+        state.events.stage(&txn, &event).await?;
+        txn.commit().await?;
+        Ok((moved, event))
+    })
+    .await?;
+
+    state.events.publish(event).await;
+
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_prefix_adds_trailing_slash() {
+        assert_eq!(normalize_prefix(None), "");
+        assert_eq!(normalize_prefix(Some("")), "");
+        assert_eq!(normalize_prefix(Some("/docs")), "docs/");
+        assert_eq!(normalize_prefix(Some("docs/")), "docs/");
+    }
+
+    #[test]
+    fn escape_like_escapes_wildcards() {
+        assert_eq!(escape_like("50%_off\\"), "50\\%\\_off\\\\");
+        assert_eq!(escape_like("report"), "report");
+    }
+
+    #[test]
+    fn file_name_from_key_extracts_last_segment() {
+        assert_eq!(file_name_from_key("a/b/c.txt"), "c.txt");
+        assert_eq!(file_name_from_key("single"), "single");
+    }
+
+    #[test]
+    fn namespace_in_bucket_takes_bucket_settings() {
+        let owner = Uuid::now_v7();
+        let bucket = bucket::Model {
+            id: Uuid::now_v7(),
+            name: "archive".to_string(),
+            owner_id: owner,
+            versioning: false,
+            default_retention_days: Some(30),
+            public: false,
+            created_at: chrono::Utc::now().into(),
+        };
+
+        let default = Namespace::own(owner);
+        assert!(default.versioning);
+        assert_eq!(default.retain_until(), None);
+
+        let ns = default.in_bucket(&bucket);
+        assert_eq!(ns.bucket_id, Some(bucket.id));
+        assert!(!ns.versioning);
+        assert!(ns.retain_until().is_some_and(|until| until > chrono::Utc::now() + chrono::Duration::days(29)));
+    }
+}
@@ -0,0 +1,489 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use sea_orm::{
+    prelude::DateTimeWithTimeZone, ColumnTrait, Condition, EntityTrait, QueryFilter, QueryOrder,
+    QuerySelect,
+};
+use uuid::Uuid;
+
+use super::sigv4::uri_encode;
+use super::xml::{
+    self, CommonPrefix, ListBucketResultV1, ListBucketResultV2, ListVersionsResult, ObjectEntry,
+    S3Error, VersionEntry, S3_NAMESPACE,
+};
+use crate::auth::AuthUser;
+use crate::entities::file;
+use crate::objects;
+use crate::AppState;
+
+const MAX_KEYS: usize = 1000;
+const BATCH_SIZE: u64 = 1000;
+const STORAGE_CLASS: &str = "STANDARD";
+
+// sorts after any other character, appended to a common prefix to jump past all of its keys
+const PAST_PREFIX: char = '\u{10FFFF}';
+
+/// Where the next batch of rows starts (exclusive): after `path`, or after one of its versions.
+#[derive(Debug, Clone, PartialEq)]
+struct Cursor {
+    path: String,
+    version: Option<(DateTimeWithTimeZone, Uuid)>,
+}
+
+impl Cursor {
+    fn after(path: String) -> Self {
+        Self { path, version: None }
+    }
+
+    fn condition(&self) -> Condition {
+        let after_path = file::Column::FilePath.gt(self.path.as_str());
+        match self.version {
+            None => Condition::all().add(after_path),
+            // versions of a key come newest first
+            Some((added_at, id)) => Condition::any().add(after_path).add(
+                Condition::all()
+                    .add(file::Column::FilePath.eq(self.path.as_str()))
+                    .add(
+                        Condition::any()
+                            .add(file::Column::AddedAt.lt(added_at))
+                            .add(
+                                Condition::all()
+                                    .add(file::Column::AddedAt.eq(added_at))
+                                    .add(file::Column::Id.lt(id)),
+                            ),
+                    ),
+            ),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Step {
+    Continue,
+    SkipPast(String),
+    Stop,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Entry {
+    File(Box<file::Model>),
+    Prefix(String),
+}
+
+/// Groups rows in key order into S3 `Contents` and `CommonPrefixes`, up to `max_keys` entries.
+#[derive(Debug)]
+struct Listing {
+    prefix: String,
+    delimiter: Option<String>,
+    max_keys: usize,
+    entries: Vec<Entry>,
+    truncated: bool,
+}
+
+impl Listing {
+    fn new(prefix: String, delimiter: Option<String>, max_keys: usize) -> Self {
+        Self {
+            prefix,
+            delimiter: delimiter.filter(|d| !d.is_empty()),
+            max_keys,
+            entries: Vec::new(),
+            truncated: false,
+        }
+    }
+
+    fn common_prefix_of(&self, key: &str) -> Option<String> {
+        let delimiter = self.delimiter.as_deref()?;
+        let rest = key.strip_prefix(self.prefix.as_str())?;
+        let end = rest.find(delimiter)? + delimiter.len();
+        Some(format!("{}{}", self.prefix, &rest[..end]))
+    }
+
+    /// Cursor resuming after `marker`, jumping past its keys when it is a common prefix.
+    fn resume_after(&self, marker: &str) -> Cursor {
+        if self.common_prefix_of(marker).as_deref() == Some(marker) {
+            Cursor::after(format!("{}{}", marker, PAST_PREFIX))
+        } else {
+            Cursor::after(marker.to_string())
+        }
+    }
+
+    fn push(&mut self, file: file::Model) -> Step {
+        let common_prefix = self.common_prefix_of(&file.file_path);
+        if let Some(ref cp) = common_prefix
+            && matches!(self.entries.last(), Some(Entry::Prefix(last)) if last == cp)
+        {
+            return Step::SkipPast(cp.clone());
+        }
+        if self.entries.len() >= self.max_keys {
+            self.truncated = true;
+            return Step::Stop;
+        }
+        match common_prefix {
+            Some(cp) => {
+                self.entries.push(Entry::Prefix(cp.clone()));
+                Step::SkipPast(cp)
+            }
+            None => {
+                self.entries.push(Entry::File(Box::new(file)));
+                Step::Continue
+            }
+        }
+    }
+
+    /// Key (or common prefix) and version id to resume from when the listing is truncated.
+    fn next_marker(&self) -> Option<(String, Option<String>)> {
+        if !self.truncated {
+            return None;
+        }
+        match self.entries.last()? {
+            Entry::File(file) => Some((file.file_path.clone(), Some(file.s3_version_id.clone()))),
+            Entry::Prefix(cp) => Some((cp.clone(), None)),
+        }
+    }
+
+    fn split(self) -> (Vec<file::Model>, Vec<CommonPrefix>) {
+        let mut files = Vec::new();
+        let mut prefixes = Vec::new();
+        for entry in self.entries {
+            match entry {
+                Entry::File(file) => files.push(*file),
+                Entry::Prefix(prefix) => prefixes.push(CommonPrefix { prefix }),
+            }
+        }
+        (files, prefixes)
+    }
+}
+
+/// Feeds `listing` with the user's rows in key order, from `cursor` on.
+async fn collect(
+    state: &AppState,
+    auth: &AuthUser,
+    listing: &mut Listing,
+    all_versions: bool,
+    mut cursor: Option<Cursor>,
+) -> Result<(), S3Error> {
+    loop {
+        let mut query = file::Entity::find()
+            .filter(file::Column::UserId.eq(auth.user_id))
+            .filter(objects::bucket_condition(None))
+            .order_by_asc(file::Column::FilePath)
+            .order_by_desc(file::Column::AddedAt)
+            .order_by_desc(file::Column::Id)
+            .limit(BATCH_SIZE);
+
+        if !all_versions {
+            query = query.filter(file::Column::IsLatest.eq(true));
+        }
+        if !listing.prefix.is_empty() {
+            query = query.filter(objects::starts_with(file::Column::FilePath, &listing.prefix));
+        }
+        // credentials restricted to a prefix only see what is under it
+        if let Some(ref key_prefix) = auth.path_prefix {
+            query = query.filter(objects::starts_with(file::Column::FilePath, key_prefix));
+        }
+        if let Some(ref cursor) = cursor {
+            query = query.filter(cursor.condition());
+        }
+
+        let rows = query.all(&state.db).await?;
+        let exhausted = (rows.len() as u64) < BATCH_SIZE;
+        let mut skipped = false;
+
+        for row in rows {
+            let next = Cursor {
+                path: row.file_path.clone(),
+                version: all_versions.then_some((row.added_at, row.id)),
+            };
+            match listing.push(row) {
+                Step::Continue => cursor = Some(next),
+                Step::SkipPast(cp) => {
+                    cursor = Some(Cursor::after(format!("{}{}", cp, PAST_PREFIX)));
+                    skipped = true;
+                    break;
+                }
+                Step::Stop => return Ok(()),
+            }
+        }
+
+        if exhausted && !skipped {
+            return Ok(());
+        }
+    }
+}
+
+fn param<'a>(query: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    query.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+}
+
+fn max_keys(query: &[(String, String)]) -> Result<usize, S3Error> {
+    match param(query, "max-keys") {
+        None => Ok(MAX_KEYS),
+        Some(value) => value
+            .parse::<usize>()
+            .map(|n| n.min(MAX_KEYS))
+            .map_err(|_| S3Error::invalid_argument("max-keys must be a positive integer")),
+    }
+}
+
+/// Keys are url-encoded when the client asks for `encoding-type=url`.
+struct Encoder(bool);
+
+impl Encoder {
+    fn from_query(query: &[(String, String)]) -> Result<Self, S3Error> {
+        match param(query, "encoding-type") {
+            None => Ok(Self(false)),
+            Some("url") => Ok(Self(true)),
+            Some(_) => Err(S3Error::invalid_argument("Invalid encoding-type")),
+        }
+    }
+
+    fn encode(&self, value: &str) -> String {
+        if self.0 { uri_encode(value) } else { value.to_string() }
+    }
+
+    fn encoding_type(&self) -> Option<&'static str> {
+        self.0.then_some("url")
+    }
+
+    fn prefixes(&self, prefixes: Vec<CommonPrefix>) -> Vec<CommonPrefix> {
+        prefixes
+            .into_iter()
+            .map(|cp| CommonPrefix { prefix: self.encode(&cp.prefix) })
+            .collect()
+    }
+
+    fn object(&self, file: &file::Model) -> ObjectEntry {
+        ObjectEntry {
+            key: self.encode(&file.file_path),
+            last_modified: xml::timestamp(&file.added_at),
+            etag: format!("\"{}\"", file.s3_version_id),
+            size: file.content_size,
+            storage_class: STORAGE_CLASS,
+        }
+    }
+}
+
+pub fn encode_token(key: &str) -> String {
+    URL_SAFE_NO_PAD.encode(key.as_bytes())
+}
+
+pub fn decode_token(token: &str) -> Result<String, S3Error> {
+    URL_SAFE_NO_PAD
+        .decode(token)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| S3Error::invalid_argument("The continuation token provided is incorrect"))
+}
+
+pub async fn list_objects_v2(
+    state: &AppState,
+    auth: &AuthUser,
+    bucket: &str,
+    query: &[(String, String)],
+) -> Result<ListBucketResultV2, S3Error> {
+    let encoder = Encoder::from_query(query)?;
+    let prefix = param(query, "prefix").unwrap_or_default().to_string();
+    let delimiter = param(query, "delimiter").map(str::to_string);
+    let max_keys = max_keys(query)?;
+    let continuation_token = param(query, "continuation-token").map(str::to_string);
+    let start_after = param(query, "start-after").map(str::to_string);
+
+    let mut listing = Listing::new(prefix.clone(), delimiter.clone(), max_keys);
+    let marker = match continuation_token {
+        Some(ref token) => Some(decode_token(token)?),
+        None => start_after.clone(),
+    };
+    let cursor = marker.map(|m| listing.resume_after(&m));
+    collect(state, auth, &mut listing, false, cursor).await?;
+
+    let next_continuation_token = listing.next_marker().map(|(key, _)| encode_token(&key));
+    let is_truncated = listing.truncated;
+    let (files, common_prefixes) = listing.split();
+
+    Ok(ListBucketResultV2 {
+        xmlns: S3_NAMESPACE,
+        name: bucket.to_string(),
+        prefix: encoder.encode(&prefix),
+        delimiter: delimiter.map(|d| encoder.encode(&d)),
+        max_keys,
+        key_count: files.len() + common_prefixes.len(),
+        encoding_type: encoder.encoding_type(),
+        is_truncated,
+        continuation_token,
+        next_continuation_token,
+        start_after: start_after.map(|s| encoder.encode(&s)),
+        contents: files.iter().map(|f| encoder.object(f)).collect(),
+        common_prefixes: encoder.prefixes(common_prefixes),
+    })
+}
+
+pub async fn list_objects_v1(
+    state: &AppState,
+    auth: &AuthUser,
+    bucket: &str,
+    query: &[(String, String)],
+) -> Result<ListBucketResultV1, S3Error> {
+    let encoder = Encoder::from_query(query)?;
+    let prefix = param(query, "prefix").unwrap_or_default().to_string();
+    let delimiter = param(query, "delimiter").map(str::to_string);
+    let max_keys = max_keys(query)?;
+    let marker = param(query, "marker").unwrap_or_default().to_string();
+
+    let mut listing = Listing::new(prefix.clone(), delimiter.clone(), max_keys);
+    let cursor = (!marker.is_empty()).then(|| listing.resume_after(&marker));
+    collect(state, auth, &mut listing, false, cursor).await?;
+
+    let next_marker = listing.next_marker().map(|(key, _)| encoder.encode(&key));
+    let is_truncated = listing.truncated;
+    let (files, common_prefixes) = listing.split();
+
+    Ok(ListBucketResultV1 {
+        xmlns: S3_NAMESPACE,
+        name: bucket.to_string(),
+        prefix: encoder.encode(&prefix),
+        marker: encoder.encode(&marker),
+        next_marker,
+        max_keys,
+        delimiter: delimiter.map(|d| encoder.encode(&d)),
+        encoding_type: encoder.encoding_type(),
+        is_truncated,
+        contents: files.iter().map(|f| encoder.object(f)).collect(),
+        common_prefixes: encoder.prefixes(common_prefixes),
+    })
+}
+
+pub async fn list_object_versions(
+    state: &AppState,
+    auth: &AuthUser,
+    bucket: &str,
+    query: &[(String, String)],
+) -> Result<ListVersionsResult, S3Error> {
+    let encoder = Encoder::from_query(query)?;
+    let prefix = param(query, "prefix").unwrap_or_default().to_string();
+    let delimiter = param(query, "delimiter").map(str::to_string);
+    let max_keys = max_keys(query)?;
+    let key_marker = param(query, "key-marker").unwrap_or_default().to_string();
+    let version_id_marker = param(query, "version-id-marker").unwrap_or_default().to_string();
+
+    let mut listing = Listing::new(prefix.clone(), delimiter.clone(), max_keys);
+    let cursor = if key_marker.is_empty() {
+        None
+    } else if version_id_marker.is_empty() {
+        Some(listing.resume_after(&key_marker))
+    } else {
+        let marker = file::Entity::find()
+            .filter(file::Column::UserId.eq(auth.user_id))
+            .filter(objects::bucket_condition(None))
+            .filter(file::Column::FilePath.eq(key_marker.as_str()))
+            .filter(file::Column::S3VersionId.eq(version_id_marker.as_str()))
+            .one(&state.db)
+            .await?
+            .ok_or_else(|| S3Error::invalid_argument("Invalid version id marker"))?;
+        Some(Cursor {
+            path: marker.file_path,
+            version: Some((marker.added_at, marker.id)),
+        })
+    };
+    collect(state, auth, &mut listing, true, cursor).await?;
+
+    let next = listing.next_marker();
+    let is_truncated = listing.truncated;
+    let (files, common_prefixes) = listing.split();
+
+    Ok(ListVersionsResult {
+        xmlns: S3_NAMESPACE,
+        name: bucket.to_string(),
+        prefix: encoder.encode(&prefix),
+        key_marker: encoder.encode(&key_marker),
+        version_id_marker,
+        next_key_marker: next.as_ref().map(|(key, _)| encoder.encode(key)),
+        next_version_id_marker: next.and_then(|(_, version)| version),
+        max_keys,
+        delimiter: delimiter.map(|d| encoder.encode(&d)),
+        encoding_type: encoder.encoding_type(),
+        is_truncated,
+        versions: files
+            .iter()
+            .map(|f| VersionEntry {
+                key: encoder.encode(&f.file_path),
+                version_id: f.s3_version_id.clone(),
+                is_latest: f.is_latest,
+                last_modified: xml::timestamp(&f.added_at),
+                etag: format!("\"{}\"", f.s3_version_id),
+                size: f.content_size,
+                storage_class: STORAGE_CLASS,
+            })
+            .collect(),
+        common_prefixes: encoder.prefixes(common_prefixes),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(path: &str) -> file::Model {
+        file::Model {
+            id: Uuid::now_v7(),
+            file_key: Uuid::now_v7(),
+            user_id: Uuid::nil(),
+            file_name: crate::objects::file_name_from_key(path),
+            file_path: path.to_string(),
+            content_type: "text/plain".to_string(),
+            content_size: 1,
+            s3_version_id: format!("v-{}", path),
+            is_latest: true,
+            added_at: chrono::Utc::now().into(),
+            uploaded_by: None,
+            bucket_id: None,
+            retain_until: None,
+            checksum_sha256: None,
+            verified_at: None,
+            corrupted_at: None,
+        }
+    }
+
+    #[test]
+    fn listing_groups_common_prefixes() {
+        let mut listing = Listing::new("docs/".to_string(), Some("/".to_string()), 10);
+
+        assert_eq!(listing.push(row("docs/a.txt")), Step::Continue);
+        assert_eq!(listing.push(row("docs/img/1.png")), Step::SkipPast("docs/img/".to_string()));
+        assert_eq!(listing.push(row("docs/img/2.png")), Step::SkipPast("docs/img/".to_string()));
+        assert_eq!(listing.push(row("docs/z.txt")), Step::Continue);
+
+        let (files, prefixes) = listing.split();
+        assert_eq!(files.len(), 2);
+        assert_eq!(prefixes.len(), 1);
+        assert_eq!(prefixes[0].prefix, "docs/img/");
+    }
+
+    #[test]
+    fn listing_stops_at_max_keys() {
+        let mut listing = Listing::new(String::new(), None, 2);
+
+        assert_eq!(listing.push(row("a")), Step::Continue);
+        assert_eq!(listing.push(row("b")), Step::Continue);
+        assert!(listing.next_marker().is_none());
+        assert_eq!(listing.push(row("c")), Step::Stop);
+
+        assert!(listing.truncated);
+        assert_eq!(listing.next_marker(), Some(("b".to_string(), Some("v-b".to_string()))));
+    }
+
+    #[test]
+    fn resume_after_common_prefix_skips_its_keys() {
+        let listing = Listing::new(String::new(), Some("/".to_string()), 10);
+
+        assert_eq!(listing.resume_after("a.txt"), Cursor::after("a.txt".to_string()));
+        let cursor = listing.resume_after("docs/");
+        assert!(cursor.path.as_str() > "docs/zzz/last.txt");
+        assert!(cursor.path.as_str() < "docs0");
+    }
+
+    #[test]
+    fn continuation_token_round_trips() {
+        let token = encode_token("docs/été 1.txt");
+        assert_eq!(decode_token(&token).unwrap(), "docs/été 1.txt");
+        assert!(decode_token("not base64!").is_err());
+    }
+}
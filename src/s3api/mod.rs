@@ -0,0 +1,259 @@
+//! S3 compatible front end, served on its own port so that standard tools (aws cli, rclone, SDKs)
+//! can use Rose. Requests are signed with SigV4 using Rose-issued access keys, each key acting
+//! as its user (scopes and path prefix included) inside a single virtual bucket.
+
+pub mod auth;
+mod list;
+mod multipart;
+mod object;
+pub mod sigv4;
+pub mod xml;
+
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, Path, RawQuery, State},
+    http::{HeaderMap, StatusCode},
+    middleware,
+    response::{IntoResponse, Response},
+    routing::get,
+    Extension, Router,
+};
+use tower_http::trace::TraceLayer;
+
+use crate::audit;
+use crate::auth::{AuthUser, Scope};
+use crate::AppState;
+use xml::{
+    xml_response, BucketEntry, Buckets, ListAllMyBucketsResult, LocationConstraint, Owner, S3Error,
+    S3_NAMESPACE,
+};
+
+pub fn router(state: AppState) -> Router {
+    let routes = Router::new()
+        .route("/", get(list_buckets))
+        .route(
+            "/{bucket}",
+            get(get_bucket).head(head_bucket).put(create_bucket).post(post_bucket),
+        )
+        .route(
+            "/{bucket}/",
+            get(get_bucket).head(head_bucket).put(create_bucket).post(post_bucket),
+        )
+        .route(
+            "/{bucket}/{*key}",
+            get(get_object)
+                .head(head_object)
+                .put(put_object)
+                .delete(delete_object)
+                .post(post_object),
+        );
+    unlimited_bodies(routes)
+        .layer(middleware::from_fn_with_state(state.clone(), auth::authenticate))
+        // outside of authentication, so rejected requests are recorded too
+        .layer(middleware::from_fn_with_state(state.clone(), audit::record))
+        .layer(TraceLayer::new_for_http())
+        .with_state(state)
+}
+
+/// Bodies are buffered by `auth::authenticate` up to `S3_API_MAX_BODY_SIZE`, axum's own 2 MiB default would
+/// refuse most uploads (and every multipart part) before the handlers.
+fn unlimited_bodies<S: Clone + Send + Sync + 'static>(routes: Router<S>) -> Router<S> {
+    routes.layer(DefaultBodyLimit::disable())
+}
+
+fn check_bucket(state: &AppState, bucket: &str) -> Result<(), S3Error> {
+    if bucket == state.config.s3_api_bucket {
+        Ok(())
+    } else {
+        Err(S3Error::no_such_bucket())
+    }
+}
+
+fn parse_query(query: Option<String>) -> Vec<(String, String)> {
+    sigv4::parse_query(query.as_deref().unwrap_or(""))
+}
+
+fn param<'a>(query: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    query.iter().find(|(k, _)| k == name).map(|(_, v)| v.as_str())
+}
+
+async fn list_buckets(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+) -> Result<Response, S3Error> {
+    Ok(xml_response(
+        StatusCode::OK,
+        &ListAllMyBucketsResult {
+            xmlns: S3_NAMESPACE,
+            owner: Owner {
+                id: auth.user_id.to_string(),
+                display_name: auth.user_id.to_string(),
+            },
+            buckets: Buckets {
+                buckets: vec![BucketEntry {
+                    name: state.config.s3_api_bucket.clone(),
+                    creation_date: xml::timestamp(&chrono::DateTime::UNIX_EPOCH.into()),
+                }],
+            },
+        },
+    ))
+}
+
+async fn head_bucket(
+    State(state): State<AppState>,
+    Path(bucket): Path<String>,
+) -> Result<Response, S3Error> {
+    check_bucket(&state, &bucket)?;
+    Ok(StatusCode::OK.into_response())
+}
+
+/// The bucket always exists, creating it again is a no-op as for its owner on S3.
+async fn create_bucket(
+    State(state): State<AppState>,
+    Path(bucket): Path<String>,
+) -> Result<Response, S3Error> {
+    check_bucket(&state, &bucket)?;
+    Ok(StatusCode::OK.into_response())
+}
+
+async fn post_bucket(
+    State(state): State<AppState>,
+    Path(bucket): Path<String>,
+) -> Result<Response, S3Error> {
+    check_bucket(&state, &bucket)?;
+    Err(S3Error::not_implemented("This bucket operation is not supported"))
+}
+
+async fn get_bucket(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path(bucket): Path<String>,
+    RawQuery(query): RawQuery,
+) -> Result<Response, S3Error> {
+    check_bucket(&state, &bucket)?;
+    let query = parse_query(query);
+
+    if param(&query, "location").is_some() {
+        return Ok(xml_response(StatusCode::OK, &LocationConstraint { xmlns: S3_NAMESPACE }));
+    }
+
+    auth.require_scope(Scope::Read)?;
+    let response = if param(&query, "versions").is_some() {
+        xml_response(StatusCode::OK, &list::list_object_versions(&state, &auth, &bucket, &query).await?)
+    } else if param(&query, "list-type") == Some("2") {
+        xml_response(StatusCode::OK, &list::list_objects_v2(&state, &auth, &bucket, &query).await?)
+    } else {
+        xml_response(StatusCode::OK, &list::list_objects_v1(&state, &auth, &bucket, &query).await?)
+    };
+
+    Ok(response)
+}
+
+async fn get_object(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path((bucket, key)): Path<(String, String)>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+) -> Result<Response, S3Error> {
+    check_bucket(&state, &bucket)?;
+    let query = parse_query(query);
+
+    object::get_object(&state, &auth, &key, param(&query, "versionId"), &headers).await
+}
+
+async fn head_object(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path((bucket, key)): Path<(String, String)>,
+    RawQuery(query): RawQuery,
+) -> Result<Response, S3Error> {
+    check_bucket(&state, &bucket)?;
+    let query = parse_query(query);
+
+    object::head_object(&state, &auth, &key, param(&query, "versionId")).await
+}
+
+async fn put_object(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path((bucket, key)): Path<(String, String)>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, S3Error> {
+    check_bucket(&state, &bucket)?;
+    let query = parse_query(query);
+
+    match (param(&query, "uploadId"), param(&query, "partNumber")) {
+        (Some(upload_id), Some(part_number)) => {
+            multipart::upload_part(&state, &auth, &key, upload_id, part_number, &headers, body).await
+        }
+        _ => object::put_object(&state, &auth, &key, &headers, body).await,
+    }
+}
+
+async fn delete_object(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path((bucket, key)): Path<(String, String)>,
+    RawQuery(query): RawQuery,
+) -> Result<Response, S3Error> {
+    check_bucket(&state, &bucket)?;
+    let query = parse_query(query);
+
+    match param(&query, "uploadId") {
+        Some(upload_id) => multipart::abort_multipart_upload(&state, &auth, &key, upload_id).await,
+        None => object::delete_object(&state, &auth, &key, param(&query, "versionId")).await,
+    }
+}
+
+async fn post_object(
+    State(state): State<AppState>,
+    Extension(auth): Extension<AuthUser>,
+    Path((bucket, key)): Path<(String, String)>,
+    RawQuery(query): RawQuery,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, S3Error> {
+    check_bucket(&state, &bucket)?;
+    let query = parse_query(query);
+
+    if param(&query, "uploads").is_some() {
+        multipart::create_multipart_upload(&state, &auth, &bucket, &key, &headers).await
+    } else if let Some(upload_id) = param(&query, "uploadId") {
+        multipart::complete_multipart_upload(&state, &auth, &bucket, &key, upload_id, &body).await
+    } else {
+        Err(S3Error::not_implemented("This object operation is not supported"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, extract::Request, middleware::Next, routing::put};
+    use tower::ServiceExt;
+
+    // buffers the body and rebuilds the request, as `auth::authenticate` does
+    async fn buffer(request: Request, next: Next) -> Response {
+        let (parts, body) = request.into_parts();
+        let payload = axum::body::to_bytes(body, 64 * 1024 * 1024).await.unwrap();
+        next.run(Request::from_parts(parts, Body::from(payload))).await
+    }
+
+    async fn send(routes: Router, size: usize) -> (StatusCode, Bytes) {
+        let request = Request::put("/rose/big.bin").body(Body::from(vec![0u8; size])).unwrap();
+        let response = routes.layer(middleware::from_fn(buffer)).oneshot(request).await.unwrap();
+        let status = response.status();
+        (status, axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn unlimited_bodies_lets_uploads_over_2_mib_through() {
+        let routes = || Router::new().route("/{bucket}/{*key}", put(|body: Bytes| async move { body.len().to_string() }));
+        let size = 5 * 1024 * 1024;
+
+        assert_eq!(send(routes(), size).await.0, StatusCode::PAYLOAD_TOO_LARGE);
+        assert_eq!(send(unlimited_bodies(routes()), size).await, (StatusCode::OK, Bytes::from(size.to_string())));
+    }
+}
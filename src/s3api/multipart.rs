@@ -0,0 +1,252 @@
+use axum::{
+    body::Bytes,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use sea_orm::{ActiveModelTrait, ColumnTrait, EntityTrait, ModelTrait, QueryFilter};
+use uuid::Uuid;
+
+use super::object::etag;
+use super::xml::{
+    xml_response, CompleteMultipartUpload, CompleteMultipartUploadResult,
+    InitiateMultipartUploadResult, S3Error, S3_NAMESPACE,
+};
+use crate::audit::AuditRecord;
+use crate::auth::{AuthUser, Scope};
+use crate::checksums;
+use crate::entities::multipart_upload;
+use crate::error::AppError;
+use crate::handlers::put::content_type_from_headers_or_path;
+use crate::objects;
+use crate::AppState;
+
+const MAX_PART_NUMBER: i32 = 10_000;
+
+/// Upload of `key` owned by the caller, uploads of other keys or users are not visible.
+async fn find_upload(
+    state: &AppState,
+    auth: &AuthUser,
+    key: &str,
+    upload_id: &str,
+) -> Result<multipart_upload::Model, S3Error> {
+    let id = Uuid::parse_str(upload_id).map_err(|_| S3Error::no_such_upload())?;
+    multipart_upload::Entity::find_by_id(id)
+        .filter(multipart_upload::Column::UserId.eq(auth.user_id))
+        .filter(multipart_upload::Column::FilePath.eq(key))
+        .one(&state.db)
+        .await?
+        .ok_or_else(S3Error::no_such_upload)
+}
+
+pub fn parse_part_number(value: &str) -> Result<i32, S3Error> {
+    value
+        .parse::<i32>()
+        .ok()
+        .filter(|n| (1..=MAX_PART_NUMBER).contains(n))
+        .ok_or_else(|| S3Error::invalid_argument("Part number must be an integer between 1 and 10000"))
+}
+
+/// Parts must be listed in strictly ascending order, as S3 requires.
+fn validate_parts(request: &CompleteMultipartUpload) -> Result<Vec<(i32, String)>, S3Error> {
+    if request.parts.is_empty() {
+        return Err(S3Error::new(
+            StatusCode::BAD_REQUEST,
+            "MalformedXML",
+            "At least one part must be specified",
+        ));
+    }
+    if request.parts.windows(2).any(|w| w[0].part_number >= w[1].part_number) {
+        return Err(S3Error::new(
+            StatusCode::BAD_REQUEST,
+            "InvalidPartOrder",
+            "The list of parts was not in ascending order",
+        ));
+    }
+    Ok(request
+        .parts
+        .iter()
+        .map(|part| (part.part_number, part.etag.clone()))
+        .collect())
+}
+
+pub async fn create_multipart_upload(
+    state: &AppState,
+    auth: &AuthUser,
+    bucket: &str,
+    key: &str,
+    headers: &HeaderMap,
+) -> Result<Response, S3Error> {
+    auth.require(Scope::Write, key)?;
+    let content_type = content_type_from_headers_or_path(headers, key);
+
+    objects::ensure_user(state, auth.user_id).await?;
+
+    let file_key = Uuid::now_v7();
+    let output = state
+        .store_client
+        .create_multipart_upload(&file_key.to_string(), &content_type)
+        .await
+        .map_err(AppError::from)?;
+    let backend_upload_id = output
+        .upload_id
+        .ok_or_else(|| AppError::InternalError("Storage returned no upload id".to_string()))?;
+
+    let upload = multipart_upload::ActiveModel::new(
+        auth.user_id,
+        key.to_string(),
+        file_key,
+        backend_upload_id,
+        content_type,
+    )
+    .insert(&state.db)
+    .await?;
+
+    tracing::info!("Started multipart upload {} of user {} for key {}", upload.id, auth.user_id, key);
+
+    Ok(xml_response(
+        StatusCode::OK,
+        &InitiateMultipartUploadResult {
+            xmlns: S3_NAMESPACE,
+            bucket: bucket.to_string(),
+            key: key.to_string(),
+            upload_id: upload.id.to_string(),
+        },
+    ))
+}
+
+pub async fn upload_part(
+    state: &AppState,
+    auth: &AuthUser,
+    key: &str,
+    upload_id: &str,
+    part_number: &str,
+    headers: &HeaderMap,
+    body: Bytes,
+) -> Result<Response, S3Error> {
+    auth.require(Scope::Write, key)?;
+    let part_number = parse_part_number(part_number)?;
+    let upload = find_upload(state, auth, key, upload_id).await?;
+    // checksums of a part cover the part only, headers and trailers alike
+    checksums::verify_upload(headers, &body)?;
+
+    let output = state
+        .store_client
+        .upload_part(&upload.file_key.to_string(), &upload.backend_upload_id, part_number, body)
+        .await
+        .map_err(AppError::from)?;
+
+    Ok((StatusCode::OK, [(header::ETAG.as_str(), output.e_tag.unwrap_or_default())]).into_response())
+}
+
+pub async fn complete_multipart_upload(
+    state: &AppState,
+    auth: &AuthUser,
+    bucket: &str,
+    key: &str,
+    upload_id: &str,
+    body: &[u8],
+) -> Result<Response, S3Error> {
+    auth.require(Scope::Write, key)?;
+    let upload = find_upload(state, auth, key, upload_id).await?;
+    let parts = validate_parts(&CompleteMultipartUpload::parse(body)?)?;
+    let file_key = upload.file_key.to_string();
+
+    let output = state
+        .store_client
+        .complete_multipart_upload(&file_key, &upload.backend_upload_id, parts)
+        .await
+        .map_err(|err| match AppError::from(err) {
+            AppError::BadRequest(msg) => S3Error::new(StatusCode::BAD_REQUEST, "InvalidPart", msg),
+            other => other.into(),
+        })?;
+
+    // the size of the assembled object is only known by the storage
+    let head = state
+        .store_client
+        .head(&file_key, output.version_id.as_deref())
+        .await
+        .map_err(AppError::from)?;
+    let s3_version_id = output.version_id.unwrap_or_else(|| "null".to_string());
+
+    let ns = objects::Namespace::own(auth.user_id);
+    let new_file = objects::commit_version(
+        state,
+        ns,
+        key,
+        upload.file_key,
+        upload.content_type.clone(),
+        head.content_length.unwrap_or_default(),
+        s3_version_id,
+        None,
+        Default::default(),
+    )
+    .await?;
+    upload.delete(&state.db).await?;
+
+    tracing::info!("Completed multipart upload {} of user {} for key {}", upload_id, auth.user_id, key);
+
+    let record = AuditRecord::new(&ns, &new_file);
+    let response = (
+        [("x-amz-version-id", new_file.s3_version_id.clone())],
+        xml_response(
+            StatusCode::OK,
+            &CompleteMultipartUploadResult {
+                xmlns: S3_NAMESPACE,
+                location: format!("/{}/{}", bucket, key),
+                bucket: bucket.to_string(),
+                key: key.to_string(),
+                etag: etag(&new_file),
+            },
+        ),
+    );
+    Ok(record.attach(response.into_response()))
+}
+
+pub async fn abort_multipart_upload(
+    state: &AppState,
+    auth: &AuthUser,
+    key: &str,
+    upload_id: &str,
+) -> Result<Response, S3Error> {
+    auth.require(Scope::Write, key)?;
+    let upload = find_upload(state, auth, key, upload_id).await?;
+
+    state
+        .store_client
+        .abort_multipart_upload(&upload.file_key.to_string(), &upload.backend_upload_id)
+        .await
+        .map_err(AppError::from)?;
+    upload.delete(&state.db).await?;
+
+    tracing::info!("Aborted multipart upload {} of user {}", upload_id, auth.user_id);
+
+    Ok(StatusCode::NO_CONTENT.into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_part_number_bounds() {
+        assert_eq!(parse_part_number("1").unwrap(), 1);
+        assert_eq!(parse_part_number("10000").unwrap(), 10_000);
+        assert!(parse_part_number("0").is_err());
+        assert!(parse_part_number("10001").is_err());
+        assert!(parse_part_number("one").is_err());
+    }
+
+    #[test]
+    fn validate_parts_requires_ascending_order() {
+        let parse = |xml: &str| CompleteMultipartUpload::parse(xml.as_bytes()).unwrap();
+
+        let ordered = parse("<CompleteMultipartUpload><Part><PartNumber>1</PartNumber><ETag>a</ETag></Part><Part><PartNumber>3</PartNumber><ETag>b</ETag></Part></CompleteMultipartUpload>");
+        assert_eq!(validate_parts(&ordered).unwrap(), vec![(1, "a".to_string()), (3, "b".to_string())]);
+
+        let unordered = parse("<CompleteMultipartUpload><Part><PartNumber>2</PartNumber><ETag>a</ETag></Part><Part><PartNumber>1</PartNumber><ETag>b</ETag></Part></CompleteMultipartUpload>");
+        assert_eq!(validate_parts(&unordered).unwrap_err().code, "InvalidPartOrder");
+
+        let empty = parse("<CompleteMultipartUpload></CompleteMultipartUpload>");
+        assert!(validate_parts(&empty).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use aws_sdk_s3::error::SdkError;
+use axum::{
+    body::{Body, Bytes},
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use tokio_util::io::ReaderStream;
+
+use super::xml::S3Error;
+use crate::audit::AuditRecord;
+use crate::auth::{AuthUser, Scope};
+use crate::checksums;
+use crate::entities::file;
+use crate::error::AppError;
+use crate::handlers::put::content_type_from_headers_or_path;
+use crate::objects::{self, Namespace};
+use crate::AppState;
+
+pub fn etag(file: &file::Model) -> String {
+    format!("\"{}\"", file.s3_version_id)
+}
+
+fn object_headers(file: &file::Model) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+
+    let mut insert_header = |key: HeaderName, value: String| {
+        if let Ok(val) = HeaderValue::from_str(&value) {
+            headers.insert(key, val);
+        }
+    };
+
+    insert_header(header::CONTENT_TYPE, file.content_type.clone());
+    insert_header(header::CONTENT_LENGTH, file.content_size.to_string());
+    insert_header(header::ETAG, etag(file));
+    insert_header(
+        header::LAST_MODIFIED,
+        file.added_at.to_utc().format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+    );
+    insert_header(HeaderName::from_static("x-amz-version-id"), file.s3_version_id.clone());
+    insert_header(header::ACCEPT_RANGES, "bytes".to_string());
+
+    headers
+}
+
+async fn find_object(
+    state: &AppState,
+    auth: &AuthUser,
+    key: &str,
+    version_id: Option<&str>,
+) -> Result<file::Model, S3Error> {
+    objects::find_version(&state.db, auth.user_id, key, version_id)
+        .await?
+        .ok_or_else(S3Error::no_such_key)
+}
+
+pub async fn get_object(
+    state: &AppState,
+    auth: &AuthUser,
+    key: &str,
+    version_id: Option<&str>,
+    headers: &HeaderMap,
+) -> Result<Response, S3Error> {
+    auth.require(Scope::Read, key)?;
+    let file_meta = find_object(state, auth, key, version_id).await?;
+    let range = headers.get(header::RANGE).and_then(|v| v.to_str().ok());
+
+    tracing::info!("S3 GET request for user {}, key {}:{:?} ({:?})", auth.user_id, key, version_id, range);
+
+    let s3_output = state
+        .store_client
+        .get_range(&file_meta.file_key.to_string(), version_id, range)
+        .await
+        .map_err(|err| match err {
+            SdkError::ServiceError(ref e) if e.raw().status().as_u16() == 416 => S3Error::new(
+                StatusCode::RANGE_NOT_SATISFIABLE,
+                "InvalidRange",
+                "The requested range is not satisfiable",
+            ),
+            err => AppError::from(err).into(),
+        })?;
+
+    let mut response_headers = object_headers(&file_meta);
+    let status = match s3_output.content_range.as_deref().map(HeaderValue::from_str) {
+        Some(Ok(content_range)) => {
+            response_headers.insert(header::CONTENT_RANGE, content_range);
+            StatusCode::PARTIAL_CONTENT
+        }
+        _ => StatusCode::OK,
+    };
+    if let Some(length) = s3_output.content_length {
+        response_headers.insert(header::CONTENT_LENGTH, HeaderValue::from(length));
+    }
+
+    let body = Body::from_stream(ReaderStream::new(s3_output.body.into_async_read()));
+
+    let record = AuditRecord::new(&Namespace::own(auth.user_id), &file_meta);
+    Ok(record.attach((status, response_headers, body).into_response()))
+}
+
+pub async fn head_object(
+    state: &AppState,
+    auth: &AuthUser,
+    key: &str,
+    version_id: Option<&str>,
+) -> Result<Response, S3Error> {
+    auth.require(Scope::Read, key)?;
+    let file_meta = find_object(state, auth, key, version_id).await?;
+
+    let record = AuditRecord::new(&Namespace::own(auth.user_id), &file_meta);
+    Ok(record.attach((StatusCode::OK, object_headers(&file_meta)).into_response()))
+}
+
+pub async fn put_object(
+    state: &AppState,
+    auth: &AuthUser,
+    key: &str,
+    headers: &HeaderMap,
+    body: Bytes,
+) -> Result<Response, S3Error> {
+    auth.require(Scope::Write, key)?;
+    if headers.contains_key("x-amz-copy-source") {
+        return Err(S3Error::not_implemented("CopyObject is not supported"));
+    }
+
+    let content_type = content_type_from_headers_or_path(headers, key);
+    tracing::info!("S3 PUT request from user {} for key {} ({} bytes)", auth.user_id, key, body.len());
+
+    let checksums = checksums::verify_upload(headers, &body)?;
+    let ns = Namespace::own(auth.user_id);
+    let new_file = objects::store_object(state, ns, key, content_type, body, &checksums, Default::default()).await?;
+    let record = AuditRecord::new(&ns, &new_file);
+
+    let response = (
+        StatusCode::OK,
+        [
+            (header::ETAG.as_str(), etag(&new_file)),
+            ("x-amz-version-id", new_file.s3_version_id),
+            ("x-amz-checksum-sha256", checksums.sha256_base64()),
+            ("x-amz-checksum-crc32c", checksums.crc32c_base64()),
+        ],
+    );
+    Ok(record.attach(response.into_response()))
+}
+
+/// Deleting a missing key succeeds, like on S3.
+pub async fn delete_object(
+    state: &AppState,
+    auth: &AuthUser,
+    key: &str,
+    version_id: Option<&str>,
+) -> Result<Response, S3Error> {
+    auth.require(Scope::Delete, key)?;
+    tracing::info!("S3 DELETE request for user {}, key {}:{:?}", auth.user_id, key, version_id);
+
+    let Some(file_meta) = objects::find_version(&state.db, auth.user_id, key, version_id).await? else {
+        return Ok(StatusCode::NO_CONTENT.into_response());
+    };
+    let s3_version_id = file_meta.s3_version_id.clone();
+    let ns = Namespace::own(auth.user_id);
+    let record = AuditRecord::new(&ns, &file_meta);
+    objects::delete_version(state, ns, file_meta).await?;
+
+    Ok(record.attach((StatusCode::NO_CONTENT, [("x-amz-version-id", s3_version_id)]).into_response()))
+}
@@ -0,0 +1,437 @@
+use axum::{
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::AppError;
+
+pub const S3_NAMESPACE: &str = "http://s3.amazonaws.com/doc/2006-03-01/";
+const XML_DECLARATION: &str = r#"<?xml version="1.0" encoding="UTF-8"?>"#;
+
+/// Error answered with the `<Error>` document S3 clients know how to parse.
+#[derive(Debug)]
+pub struct S3Error {
+    pub status: StatusCode,
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl S3Error {
+    pub fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn access_denied(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::FORBIDDEN, "AccessDenied", message)
+    }
+
+    pub fn authorization_malformed(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "AuthorizationHeaderMalformed", message)
+    }
+
+    pub fn signature_mismatch() -> Self {
+        Self::new(
+            StatusCode::FORBIDDEN,
+            "SignatureDoesNotMatch",
+            "The request signature we calculated does not match the signature you provided",
+        )
+    }
+
+    pub fn incomplete_body() -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "IncompleteBody", "Malformed aws-chunked body")
+    }
+
+    pub fn invalid_argument(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST, "InvalidArgument", message)
+    }
+
+    pub fn no_such_bucket() -> Self {
+        Self::new(StatusCode::NOT_FOUND, "NoSuchBucket", "The specified bucket does not exist")
+    }
+
+    pub fn no_such_key() -> Self {
+        Self::new(StatusCode::NOT_FOUND, "NoSuchKey", "The specified key does not exist")
+    }
+
+    pub fn no_such_upload() -> Self {
+        Self::new(StatusCode::NOT_FOUND, "NoSuchUpload", "The specified upload does not exist")
+    }
+
+    pub fn not_implemented(message: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_IMPLEMENTED, "NotImplemented", message)
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename = "Error")]
+struct ErrorBody<'a> {
+    #[serde(rename = "Code")]
+    code: &'a str,
+    #[serde(rename = "Message")]
+    message: &'a str,
+}
+
+impl IntoResponse for S3Error {
+    fn into_response(self) -> Response {
+        if self.status.is_server_error() {
+            tracing::error!("S3 API error {}: {}", self.code, self.message);
+        }
+        let body = ErrorBody {
+            code: self.code,
+            message: &self.message,
+        };
+        xml_response(self.status, &body)
+    }
+}
+
+impl From<AppError> for S3Error {
+    fn from(err: AppError) -> Self {
+        match err {
+            AppError::BadRequest(msg) => Self::new(StatusCode::BAD_REQUEST, "InvalidRequest", msg),
+            AppError::Unauthorized(msg) | AppError::Forbidden(msg) => Self::access_denied(msg),
+            AppError::NotFound(_) => Self::no_such_key(),
+            AppError::Conflict(msg) => Self::new(StatusCode::CONFLICT, "OperationAborted", msg),
+            AppError::QuotaExceeded(msg) => Self::new(StatusCode::FORBIDDEN, "QuotaExceeded", msg),
+            AppError::TooLarge(msg) => Self::new(StatusCode::BAD_REQUEST, "EntityTooLarge", msg),
+            AppError::Contention(msg) => Self::new(StatusCode::SERVICE_UNAVAILABLE, "SlowDown", msg),
+            AppError::DatabaseError(msg) | AppError::InternalError(msg) => {
+                Self::new(StatusCode::INTERNAL_SERVER_ERROR, "InternalError", msg)
+            }
+        }
+    }
+}
+
+impl From<sea_orm::DbErr> for S3Error {
+    fn from(err: sea_orm::DbErr) -> Self {
+        AppError::from(err).into()
+    }
+}
+
+pub fn to_xml<T: Serialize>(value: &T) -> String {
+    match quick_xml::se::to_string(value) {
+        Ok(body) => format!("{}{}", XML_DECLARATION, body),
+        Err(err) => {
+            tracing::error!("Failed to serialize S3 response: {}", err);
+            String::new()
+        }
+    }
+}
+
+pub fn xml_response<T: Serialize>(status: StatusCode, value: &T) -> Response {
+    (status, [(header::CONTENT_TYPE, "application/xml")], to_xml(value)).into_response()
+}
+
+/// S3 timestamps, e.g. `2026-10-18T11:00:00.000Z`.
+pub fn timestamp(date: &chrono::DateTime<chrono::FixedOffset>) -> String {
+    date.to_utc().format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
+
+#[derive(Serialize)]
+pub struct Owner {
+    #[serde(rename = "ID")]
+    pub id: String,
+    #[serde(rename = "DisplayName")]
+    pub display_name: String,
+}
+
+#[derive(Serialize)]
+pub struct BucketEntry {
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "CreationDate")]
+    pub creation_date: String,
+}
+
+#[derive(Serialize)]
+pub struct Buckets {
+    #[serde(rename = "Bucket")]
+    pub buckets: Vec<BucketEntry>,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "ListAllMyBucketsResult")]
+pub struct ListAllMyBucketsResult {
+    #[serde(rename = "@xmlns")]
+    pub xmlns: &'static str,
+    #[serde(rename = "Owner")]
+    pub owner: Owner,
+    #[serde(rename = "Buckets")]
+    pub buckets: Buckets,
+}
+
+#[derive(Serialize)]
+pub struct ObjectEntry {
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "LastModified")]
+    pub last_modified: String,
+    #[serde(rename = "ETag")]
+    pub etag: String,
+    #[serde(rename = "Size")]
+    pub size: i64,
+    #[serde(rename = "StorageClass")]
+    pub storage_class: &'static str,
+}
+
+#[derive(Serialize)]
+pub struct VersionEntry {
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "VersionId")]
+    pub version_id: String,
+    #[serde(rename = "IsLatest")]
+    pub is_latest: bool,
+    #[serde(rename = "LastModified")]
+    pub last_modified: String,
+    #[serde(rename = "ETag")]
+    pub etag: String,
+    #[serde(rename = "Size")]
+    pub size: i64,
+    #[serde(rename = "StorageClass")]
+    pub storage_class: &'static str,
+}
+
+#[derive(Serialize)]
+pub struct CommonPrefix {
+    #[serde(rename = "Prefix")]
+    pub prefix: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "ListBucketResult")]
+pub struct ListBucketResultV1 {
+    #[serde(rename = "@xmlns")]
+    pub xmlns: &'static str,
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Prefix")]
+    pub prefix: String,
+    #[serde(rename = "Marker")]
+    pub marker: String,
+    #[serde(rename = "NextMarker", skip_serializing_if = "Option::is_none")]
+    pub next_marker: Option<String>,
+    #[serde(rename = "MaxKeys")]
+    pub max_keys: usize,
+    #[serde(rename = "Delimiter", skip_serializing_if = "Option::is_none")]
+    pub delimiter: Option<String>,
+    #[serde(rename = "EncodingType", skip_serializing_if = "Option::is_none")]
+    pub encoding_type: Option<&'static str>,
+    #[serde(rename = "IsTruncated")]
+    pub is_truncated: bool,
+    #[serde(rename = "Contents")]
+    pub contents: Vec<ObjectEntry>,
+    #[serde(rename = "CommonPrefixes")]
+    pub common_prefixes: Vec<CommonPrefix>,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "ListBucketResult")]
+pub struct ListBucketResultV2 {
+    #[serde(rename = "@xmlns")]
+    pub xmlns: &'static str,
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Prefix")]
+    pub prefix: String,
+    #[serde(rename = "Delimiter", skip_serializing_if = "Option::is_none")]
+    pub delimiter: Option<String>,
+    #[serde(rename = "MaxKeys")]
+    pub max_keys: usize,
+    #[serde(rename = "KeyCount")]
+    pub key_count: usize,
+    #[serde(rename = "EncodingType", skip_serializing_if = "Option::is_none")]
+    pub encoding_type: Option<&'static str>,
+    #[serde(rename = "IsTruncated")]
+    pub is_truncated: bool,
+    #[serde(rename = "ContinuationToken", skip_serializing_if = "Option::is_none")]
+    pub continuation_token: Option<String>,
+    #[serde(rename = "NextContinuationToken", skip_serializing_if = "Option::is_none")]
+    pub next_continuation_token: Option<String>,
+    #[serde(rename = "StartAfter", skip_serializing_if = "Option::is_none")]
+    pub start_after: Option<String>,
+    #[serde(rename = "Contents")]
+    pub contents: Vec<ObjectEntry>,
+    #[serde(rename = "CommonPrefixes")]
+    pub common_prefixes: Vec<CommonPrefix>,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "ListVersionsResult")]
+pub struct ListVersionsResult {
+    #[serde(rename = "@xmlns")]
+    pub xmlns: &'static str,
+    #[serde(rename = "Name")]
+    pub name: String,
+    #[serde(rename = "Prefix")]
+    pub prefix: String,
+    #[serde(rename = "KeyMarker")]
+    pub key_marker: String,
+    #[serde(rename = "VersionIdMarker")]
+    pub version_id_marker: String,
+    #[serde(rename = "NextKeyMarker", skip_serializing_if = "Option::is_none")]
+    pub next_key_marker: Option<String>,
+    #[serde(rename = "NextVersionIdMarker", skip_serializing_if = "Option::is_none")]
+    pub next_version_id_marker: Option<String>,
+    #[serde(rename = "MaxKeys")]
+    pub max_keys: usize,
+    #[serde(rename = "Delimiter", skip_serializing_if = "Option::is_none")]
+    pub delimiter: Option<String>,
+    #[serde(rename = "EncodingType", skip_serializing_if = "Option::is_none")]
+    pub encoding_type: Option<&'static str>,
+    #[serde(rename = "IsTruncated")]
+    pub is_truncated: bool,
+    #[serde(rename = "Version")]
+    pub versions: Vec<VersionEntry>,
+    #[serde(rename = "CommonPrefixes")]
+    pub common_prefixes: Vec<CommonPrefix>,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "LocationConstraint")]
+pub struct LocationConstraint {
+    #[serde(rename = "@xmlns")]
+    pub xmlns: &'static str,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "InitiateMultipartUploadResult")]
+pub struct InitiateMultipartUploadResult {
+    #[serde(rename = "@xmlns")]
+    pub xmlns: &'static str,
+    #[serde(rename = "Bucket")]
+    pub bucket: String,
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "UploadId")]
+    pub upload_id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "CompleteMultipartUploadResult")]
+pub struct CompleteMultipartUploadResult {
+    #[serde(rename = "@xmlns")]
+    pub xmlns: &'static str,
+    #[serde(rename = "Location")]
+    pub location: String,
+    #[serde(rename = "Bucket")]
+    pub bucket: String,
+    #[serde(rename = "Key")]
+    pub key: String,
+    #[serde(rename = "ETag")]
+    pub etag: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompletedPart {
+    #[serde(rename = "PartNumber")]
+    pub part_number: i32,
+    #[serde(rename = "ETag")]
+    pub etag: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompleteMultipartUpload {
+    #[serde(rename = "Part", default)]
+    pub parts: Vec<CompletedPart>,
+}
+
+impl CompleteMultipartUpload {
+    pub fn parse(body: &[u8]) -> Result<Self, S3Error> {
+        let body = std::str::from_utf8(body)
+            .map_err(|_| S3Error::new(StatusCode::BAD_REQUEST, "MalformedXML", "Body is not UTF-8"))?;
+        quick_xml::de::from_str(body).map_err(|err| {
+            S3Error::new(StatusCode::BAD_REQUEST, "MalformedXML", err.to_string())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_body_is_s3_error_document() {
+        let body = to_xml(&ErrorBody {
+            code: "NoSuchKey",
+            message: "a < b",
+        });
+
+        assert_eq!(
+            body,
+            r#"<?xml version="1.0" encoding="UTF-8"?><Error><Code>NoSuchKey</Code><Message>a &lt; b</Message></Error>"#
+        );
+    }
+
+    #[test]
+    fn list_result_repeats_contents_elements() {
+        let body = to_xml(&ListBucketResultV2 {
+            xmlns: S3_NAMESPACE,
+            name: "rose".to_string(),
+            prefix: String::new(),
+            delimiter: Some("/".to_string()),
+            max_keys: 1000,
+            key_count: 3,
+            encoding_type: None,
+            is_truncated: false,
+            continuation_token: None,
+            next_continuation_token: None,
+            start_after: None,
+            contents: vec![
+                ObjectEntry {
+                    key: "a.txt".to_string(),
+                    last_modified: "2026-10-18T11:00:00.000Z".to_string(),
+                    etag: "\"v1\"".to_string(),
+                    size: 3,
+                    storage_class: "STANDARD",
+                },
+                ObjectEntry {
+                    key: "b.txt".to_string(),
+                    last_modified: "2026-10-18T11:00:00.000Z".to_string(),
+                    etag: "\"v2\"".to_string(),
+                    size: 4,
+                    storage_class: "STANDARD",
+                },
+            ],
+            common_prefixes: vec![CommonPrefix {
+                prefix: "docs/".to_string(),
+            }],
+        });
+
+        assert!(body.contains(r#"<ListBucketResult xmlns="http://s3.amazonaws.com/doc/2006-03-01/">"#));
+        assert_eq!(body.matches("<Contents>").count(), 2);
+        assert!(body.contains("<CommonPrefixes><Prefix>docs/</Prefix></CommonPrefixes>"));
+        assert!(body.contains("<IsTruncated>false</IsTruncated>"));
+        assert!(!body.contains("ContinuationToken"));
+    }
+
+    #[test]
+    fn complete_multipart_upload_parses_parts() {
+        let body = br#"<CompleteMultipartUpload xmlns="http://s3.amazonaws.com/doc/2006-03-01/">
+            <Part><ETag>"etag-1"</ETag><PartNumber>1</PartNumber></Part>
+            <Part><PartNumber>2</PartNumber><ETag>"etag-2"</ETag><ChecksumCRC32>AAAAAA==</ChecksumCRC32></Part>
+        </CompleteMultipartUpload>"#;
+
+        let parsed = CompleteMultipartUpload::parse(body).unwrap();
+
+        assert_eq!(parsed.parts.len(), 2);
+        assert_eq!(parsed.parts[1].part_number, 2);
+        assert_eq!(parsed.parts[0].etag, "\"etag-1\"");
+    }
+
+    #[test]
+    fn app_errors_map_to_s3_codes() {
+        assert_eq!(S3Error::from(AppError::NotFound("x".to_string())).code, "NoSuchKey");
+        assert_eq!(S3Error::from(AppError::Forbidden("x".to_string())).code, "AccessDenied");
+        assert_eq!(S3Error::from(AppError::Contention("x".to_string())).code, "SlowDown");
+        assert_eq!(S3Error::from(AppError::TooLarge("x".to_string())).code, "EntityTooLarge");
+        assert_eq!(
+            S3Error::from(AppError::DatabaseError("x".to_string())).status,
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+    }
+}
@@ -0,0 +1,277 @@
+use std::collections::HashSet;
+use std::io;
+use std::time::Duration;
+
+use bytes::Bytes;
+use futures::stream::{self, Stream, StreamExt};
+use sea_orm::{
+    sea_query::{Expr, NullOrdering},
+    ColumnTrait, Condition, DatabaseConnection, EntityTrait, Order, PaginatorTrait, QueryFilter, QueryOrder,
+    QuerySelect,
+};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tokio::time::Instant;
+use uuid::Uuid;
+
+use crate::entities::file;
+use crate::error::AppError;
+use crate::AppState;
+
+const BATCH_SIZE: u64 = 100;
+const IDLE_INTERVAL: Duration = Duration::from_secs(3600);
+/// Versions are read back again once their last verification is this old.
+const REVERIFY_DAYS: i64 = 30;
+
+#[derive(Debug, Serialize)]
+pub struct CorruptedVersion {
+    pub file_id: Uuid,
+    pub owner_id: Uuid,
+    pub key: String,
+    pub version_id: String,
+    pub file_key: Uuid,
+    pub checksum_sha256: Option<String>,
+    pub corrupted_at: Option<chrono::DateTime<chrono::FixedOffset>>,
+}
+
+impl From<file::Model> for CorruptedVersion {
+    fn from(file: file::Model) -> Self {
+        Self {
+            file_id: file.id,
+            owner_id: file.user_id,
+            key: file.file_path,
+            version_id: file.s3_version_id,
+            file_key: file.file_key,
+            checksum_sha256: file.checksum_sha256,
+            corrupted_at: file.corrupted_at,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ScrubReport {
+    pub versions: u64,
+    pub verified: u64,
+    pub never_verified: u64,
+    pub corrupted: Vec<CorruptedVersion>,
+}
+
+/// Reads every version back at `SCRUB_BYTES_PER_SEC` and checks it against its stored checksum.
+///
+/// Versions written without a checksum (presigned or multipart uploads) get the one computed at their first scrub.
+pub fn spawn_scrubber(state: AppState) {
+    let Some(rate) = state.config.scrub_bytes_per_sec else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        // versions that could not be read, tried again after the next idle period
+        let mut skipped = HashSet::new();
+        loop {
+            match scrub_batch(&state, rate, &mut skipped).await {
+                Ok(0) => {
+                    skipped.clear();
+                    tokio::time::sleep(IDLE_INTERVAL).await
+                }
+                Ok(_) => {}
+                Err(err) => {
+                    tracing::error!("Failed to scrub objects: {:?}", err);
+                    tokio::time::sleep(IDLE_INTERVAL).await;
+                }
+            }
+        }
+    });
+}
+
+/// Scrubs the versions due first, leaving out the `skipped` ones. A version that cannot be read is added to them,
+/// rather than holding up the versions after it.
+async fn scrub_batch(state: &AppState, rate: u64, skipped: &mut HashSet<Uuid>) -> Result<usize, AppError> {
+    let reverify_before = chrono::Utc::now() - chrono::Duration::days(REVERIFY_DAYS);
+    let due = file::Entity::find()
+        .filter(file::Column::CorruptedAt.is_null())
+        .filter(file::Column::Id.is_not_in(skipped.iter().copied()))
+        .filter(
+            Condition::any()
+                .add(file::Column::VerifiedAt.is_null())
+                .add(file::Column::VerifiedAt.lt(reverify_before)),
+        )
+        .order_by_with_nulls(file::Column::VerifiedAt, Order::Asc, NullOrdering::First)
+        .limit(BATCH_SIZE)
+        .all(&state.db)
+        .await?;
+
+    for file in &due {
+        if let Err(err) = scrub_version(state, file, rate).await {
+            tracing::error!("Failed to scrub version {} of {}: {:?}", file.s3_version_id, file.file_path, err);
+            skipped.insert(file.id);
+        }
+    }
+    Ok(due.len())
+}
+
+async fn scrub_version(state: &AppState, file: &file::Model, rate: u64) -> Result<(), AppError> {
+    let output = match state.store_client.get(&file.file_key.to_string(), Some(&file.s3_version_id)).await {
+        Ok(output) => output,
+        Err(err) => {
+            return match AppError::from(err) {
+                AppError::NotFound(_) => {
+                    tracing::error!("Version {} of {} is gone from the store", file.s3_version_id, file.file_path);
+                    mark_corrupted(&state.db, file.id).await
+                }
+                err => Err(err),
+            };
+        }
+    };
+
+    let mut body = output.body;
+    let mut hasher = Sha256::new();
+    let (started, mut read) = (Instant::now(), 0u64);
+    while let Some(chunk) = body.try_next().await.map_err(|err| AppError::InternalError(err.to_string()))? {
+        hasher.update(&chunk);
+        read += chunk.len() as u64;
+        tokio::time::sleep(throttle_delay(read, rate, started.elapsed())).await;
+    }
+    let checksum = hex::encode(hasher.finalize());
+
+    match file.checksum_sha256 {
+        Some(ref expected) if *expected != checksum => {
+            tracing::error!(
+                "Version {} of {} is corrupted: checksum {} instead of {}",
+                file.s3_version_id,
+                file.file_path,
+                checksum,
+                expected
+            );
+            mark_corrupted(&state.db, file.id).await
+        }
+        _ => {
+            file::Entity::update_many()
+                .col_expr(file::Column::ChecksumSha256, Expr::value(checksum))
+                .col_expr(file::Column::VerifiedAt, Expr::value(chrono::Utc::now()))
+                .filter(file::Column::Id.eq(file.id))
+                .exec(&state.db)
+                .await?;
+            Ok(())
+        }
+    }
+}
+
+/// How long to wait after reading `read` bytes in `elapsed` to stay under `rate` bytes per second.
+fn throttle_delay(read: u64, rate: u64, elapsed: Duration) -> Duration {
+    Duration::from_secs_f64(read as f64 / rate as f64).saturating_sub(elapsed)
+}
+
+pub async fn mark_corrupted(db: &DatabaseConnection, file_id: Uuid) -> Result<(), AppError> {
+    file::Entity::update_many()
+        .col_expr(file::Column::CorruptedAt, Expr::value(chrono::Utc::now()))
+        .filter(file::Column::Id.eq(file_id))
+        .exec(db)
+        .await?;
+    Ok(())
+}
+
+pub async fn report(db: &DatabaseConnection) -> Result<ScrubReport, AppError> {
+    let versions = file::Entity::find().count(db).await?;
+    let verified = file::Entity::find()
+        .filter(file::Column::VerifiedAt.is_not_null())
+        .filter(file::Column::CorruptedAt.is_null())
+        .count(db)
+        .await?;
+    let never_verified = file::Entity::find()
+        .filter(file::Column::VerifiedAt.is_null())
+        .filter(file::Column::CorruptedAt.is_null())
+        .count(db)
+        .await?;
+    let corrupted = file::Entity::find()
+        .filter(file::Column::CorruptedAt.is_not_null())
+        .order_by_desc(file::Column::CorruptedAt)
+        .all(db)
+        .await?;
+
+    Ok(ScrubReport {
+        versions,
+        verified,
+        never_verified,
+        corrupted: corrupted.into_iter().map(CorruptedVersion::from).collect(),
+    })
+}
+
+/// Passes `body` through and fails it at the end when its SHA-256 is not `expected`, calling `on_mismatch`.
+///
+/// The error cuts the response short, so the client never takes a corrupted body for a complete one.
+pub fn verify_stream<S>(
+    body: S,
+    expected: String,
+    on_mismatch: impl FnOnce() + Send + 'static,
+) -> impl Stream<Item = io::Result<Bytes>>
+where
+    S: Stream<Item = io::Result<Bytes>> + Unpin,
+{
+    let state = (body, Some((Sha256::new(), on_mismatch)));
+    stream::unfold(state, move |(mut body, verifier)| {
+        let expected = expected.clone();
+        async move {
+            let (mut hasher, on_mismatch) = verifier?;
+            match body.next().await {
+                Some(Ok(chunk)) => {
+                    hasher.update(&chunk);
+                    Some((Ok(chunk), (body, Some((hasher, on_mismatch)))))
+                }
+                Some(Err(err)) => Some((Err(err), (body, None))),
+                None if hex::encode(hasher.finalize()) == expected => None,
+                None => {
+                    on_mismatch();
+                    Some((Err(io::Error::other("checksum mismatch")), (body, None)))
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+
+    fn chunks() -> impl Stream<Item = io::Result<Bytes>> + Unpin {
+        stream::iter(vec![Ok(Bytes::from_static(b"hello ")), Ok(Bytes::from_static(b"world"))])
+    }
+
+    #[test]
+    fn throttle_delay_keeps_the_rate() {
+        assert_eq!(throttle_delay(1000, 1000, Duration::ZERO), Duration::from_secs(1));
+        assert_eq!(throttle_delay(1000, 1000, Duration::from_millis(400)), Duration::from_millis(600));
+        assert_eq!(throttle_delay(1000, 1000, Duration::from_secs(2)), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn verify_stream_passes_matching_content() {
+        let expected = hex::encode(Sha256::digest(b"hello world"));
+        let mismatched = Arc::new(AtomicBool::new(false));
+        let flag = mismatched.clone();
+
+        let items: Vec<_> = verify_stream(chunks(), expected, move || flag.store(true, Ordering::SeqCst))
+            .collect()
+            .await;
+
+        assert_eq!(items.len(), 2);
+        assert!(items.iter().all(|item| item.is_ok()));
+        assert!(!mismatched.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn verify_stream_fails_on_mismatch() {
+        let expected = hex::encode(Sha256::digest(b"something else"));
+        let mismatched = Arc::new(AtomicBool::new(false));
+        let flag = mismatched.clone();
+
+        let items: Vec<_> = verify_stream(chunks(), expected, move || flag.store(true, Ordering::SeqCst))
+            .collect()
+            .await;
+
+        assert_eq!(items.len(), 3);
+        assert!(items[2].is_err());
+        assert!(mismatched.load(Ordering::SeqCst));
+    }
+}
@@ -4,13 +4,22 @@ use aws_sdk_s3::{
     error::SdkError,
     config::Builder as S3ConfigBuilder,
     operation::{
+        abort_multipart_upload::{AbortMultipartUploadError, AbortMultipartUploadOutput},
+        complete_multipart_upload::{CompleteMultipartUploadError, CompleteMultipartUploadOutput},
+        create_multipart_upload::{CreateMultipartUploadError, CreateMultipartUploadOutput},
         delete_object::{DeleteObjectError, DeleteObjectOutput}, 
+        delete_objects::{DeleteObjectsError, DeleteObjectsOutput},
         get_object::{GetObjectError, GetObjectOutput}, 
         head_object::{HeadObjectError, HeadObjectOutput}, 
-        put_object::{PutObjectError, PutObjectOutput}
+        list_object_versions::{ListObjectVersionsError, ListObjectVersionsOutput},
+        put_object::{PutObjectError, PutObjectOutput},
+        upload_part::{UploadPartError, UploadPartOutput},
     },
+    presigning::{PresignedRequest, PresigningConfig},
     primitives::ByteStream,
+    types::{CompletedMultipartUpload, CompletedPart, Delete, ObjectIdentifier},
 };
+use crate::checksums::Checksums;
 use crate::config::Config;
 use bytes::Bytes;
 
@@ -39,6 +48,16 @@ impl S3Client {
         path: &str, 
         version_id: Option<&str>
     ) -> Result<GetObjectOutput, SdkError<GetObjectError>> 
+    {
+        self.get_range(path, version_id, None).await
+    }
+
+    pub async fn get_range(
+        &self,
+        path: &str,
+        version_id: Option<&str>,
+        range: Option<&str>,
+    ) -> Result<GetObjectOutput, SdkError<GetObjectError>>
     {
         let mut request = self.client
             .get_object()
@@ -48,6 +67,9 @@ impl S3Client {
         if let Some(vid) = version_id {
             request = request.version_id(vid);
         }
+        if let Some(range) = range {
+            request = request.range(range);
+        }
 
         request.send().await
     }
@@ -74,12 +96,16 @@ impl S3Client {
         &self,
         path: &str,
         data: Bytes,
+        checksums: &Checksums,
     ) -> Result<PutObjectOutput, SdkError<PutObjectError>>
     {
+        // S3 accepts a single x-amz-checksum-* header per request, SHA-256 is the one we keep
         self.client
             .put_object()
             .bucket(&self.bucket_name)
             .key(path)
+            .content_md5(checksums.md5_base64())
+            .checksum_sha256(checksums.sha256_base64())
             .body(ByteStream::from(data))
             .send()
             .await
@@ -101,6 +127,155 @@ impl S3Client {
         }
 
         request.send().await
-    } 
+    }
+
+    /// Deletes up to 1000 object versions in one request, the keys that failed are listed in the output.
+    pub async fn delete_many(
+        &self,
+        versions: &[(String, String)],
+    ) -> Result<DeleteObjectsOutput, SdkError<DeleteObjectsError>>
+    {
+        let objects = versions
+            .iter()
+            .map(|(path, version_id)| ObjectIdentifier::builder().key(path).version_id(version_id).build())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(SdkError::construction_failure)?;
+        let delete = Delete::builder()
+            .set_objects(Some(objects))
+            .quiet(true)
+            .build()
+            .map_err(SdkError::construction_failure)?;
+
+        self.client
+            .delete_objects()
+            .bucket(&self.bucket_name)
+            .delete(delete)
+            .send()
+            .await
+    }
+
+    /// One page of the versions of the objects whose key starts with `prefix`, continued from the markers.
+    pub async fn list_versions(
+        &self,
+        prefix: Option<&str>,
+        key_marker: Option<&str>,
+        version_id_marker: Option<&str>,
+    ) -> Result<ListObjectVersionsOutput, SdkError<ListObjectVersionsError>>
+    {
+        self.client
+            .list_object_versions()
+            .bucket(&self.bucket_name)
+            .set_prefix(prefix.map(str::to_string))
+            .set_key_marker(key_marker.map(str::to_string))
+            .set_version_id_marker(version_id_marker.map(str::to_string))
+            .send()
+            .await
+    }
+
+    pub async fn create_multipart_upload(
+        &self,
+        path: &str,
+        content_type: &str,
+    ) -> Result<CreateMultipartUploadOutput, SdkError<CreateMultipartUploadError>>
+    {
+        self.client
+            .create_multipart_upload()
+            .bucket(&self.bucket_name)
+            .key(path)
+            .content_type(content_type)
+            .send()
+            .await
+    }
+
+    pub async fn upload_part(
+        &self,
+        path: &str,
+        upload_id: &str,
+        part_number: i32,
+        data: Bytes,
+    ) -> Result<UploadPartOutput, SdkError<UploadPartError>>
+    {
+        self.client
+            .upload_part()
+            .bucket(&self.bucket_name)
+            .key(path)
+            .upload_id(upload_id)
+            .part_number(part_number)
+            .body(ByteStream::from(data))
+            .send()
+            .await
+    }
+
+    pub async fn complete_multipart_upload(
+        &self,
+        path: &str,
+        upload_id: &str,
+        parts: Vec<(i32, String)>,
+    ) -> Result<CompleteMultipartUploadOutput, SdkError<CompleteMultipartUploadError>>
+    {
+        let parts = parts
+            .into_iter()
+            .map(|(number, etag)| CompletedPart::builder().part_number(number).e_tag(etag).build())
+            .collect();
+
+        self.client
+            .complete_multipart_upload()
+            .bucket(&self.bucket_name)
+            .key(path)
+            .upload_id(upload_id)
+            .multipart_upload(CompletedMultipartUpload::builder().set_parts(Some(parts)).build())
+            .send()
+            .await
+    }
+
+    pub async fn abort_multipart_upload(
+        &self,
+        path: &str,
+        upload_id: &str,
+    ) -> Result<AbortMultipartUploadOutput, SdkError<AbortMultipartUploadError>>
+    {
+        self.client
+            .abort_multipart_upload()
+            .bucket(&self.bucket_name)
+            .key(path)
+            .upload_id(upload_id)
+            .send()
+            .await
+    }
+
+    pub async fn presign_get(
+        &self,
+        path: &str,
+        version_id: Option<&str>,
+        config: PresigningConfig,
+    ) -> Result<PresignedRequest, SdkError<GetObjectError>>
+    {
+        let mut request = self.client
+            .get_object()
+            .bucket(&self.bucket_name)
+            .key(path);
+
+        if let Some(vid) = version_id {
+            request = request.version_id(vid);
+        }
+
+        request.presigned(config).await
+    }
+
+    pub async fn presign_put(
+        &self,
+        path: &str,
+        content_type: &str,
+        config: PresigningConfig,
+    ) -> Result<PresignedRequest, SdkError<PutObjectError>>
+    {
+        self.client
+            .put_object()
+            .bucket(&self.bucket_name)
+            .key(path)
+            .content_type(content_type)
+            .presigned(config)
+            .await
+    }
 
 }
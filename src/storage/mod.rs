@@ -1,2 +1,4 @@
 pub mod client;
-pub use client::S3Client;
\ No newline at end of file
+pub mod writer;
+pub use client::S3Client;
+pub use writer::MultipartWriter;
\ No newline at end of file
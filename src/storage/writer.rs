@@ -0,0 +1,79 @@
+use bytes::{Bytes, BytesMut};
+
+use super::S3Client;
+use crate::error::AppError;
+
+/// Parts are sent once this big, S3 wants at least 5 MiB for every part but the last, and at most 10000 parts.
+const PART_SIZE: usize = 16 * 1024 * 1024;
+
+/// Writes an object of unknown size to the store as a multipart upload, holding a single part in memory.
+pub struct MultipartWriter {
+    client: S3Client,
+    path: String,
+    upload_id: String,
+    buffer: BytesMut,
+    parts: Vec<(i32, String)>,
+    written: u64,
+}
+
+impl MultipartWriter {
+    pub async fn create(client: &S3Client, path: &str, content_type: &str) -> Result<Self, AppError> {
+        let output = client.create_multipart_upload(path, content_type).await?;
+        let upload_id = output
+            .upload_id
+            .ok_or_else(|| AppError::InternalError("Store returned no multipart upload id".to_string()))?;
+
+        Ok(Self {
+            client: client.clone(),
+            path: path.to_string(),
+            upload_id,
+            buffer: BytesMut::with_capacity(PART_SIZE),
+            parts: Vec::new(),
+            written: 0,
+        })
+    }
+
+    pub fn upload_id(&self) -> &str {
+        &self.upload_id
+    }
+
+    pub async fn write(&mut self, data: &[u8]) -> Result<(), AppError> {
+        self.buffer.extend_from_slice(data);
+        self.written += data.len() as u64;
+        while self.buffer.len() >= PART_SIZE {
+            let part = self.buffer.split_to(PART_SIZE).freeze();
+            self.send_part(part).await?;
+        }
+        Ok(())
+    }
+
+    /// Completes the upload and returns the version id of the object and its size.
+    pub async fn finish(mut self) -> Result<(Option<String>, u64), AppError> {
+        // the last part can be small, or even empty for an empty object
+        if !self.buffer.is_empty() || self.parts.is_empty() {
+            let part = self.buffer.split().freeze();
+            self.send_part(part).await?;
+        }
+        let output = self
+            .client
+            .complete_multipart_upload(&self.path, &self.upload_id, self.parts)
+            .await?;
+        Ok((output.version_id, self.written))
+    }
+
+    pub async fn abort(self) {
+        if let Err(err) = self.client.abort_multipart_upload(&self.path, &self.upload_id).await {
+            tracing::error!("Failed to abort the multipart upload of {}: {:?}", self.path, err);
+        }
+    }
+
+    async fn send_part(&mut self, part: Bytes) -> Result<(), AppError> {
+        let number = self.parts.len() as i32 + 1;
+        let output = self.client.upload_part(&self.path, &self.upload_id, number, part).await?;
+        let etag = output
+            .e_tag
+            .ok_or_else(|| AppError::InternalError("Store returned no part etag".to_string()))?;
+        self.parts.push((number, etag));
+        Ok(())
+    }
+}
@@ -0,0 +1,113 @@
+use std::future::Future;
+use std::time::Duration;
+
+use rand::Rng;
+use sea_orm::{DbErr, RuntimeErr};
+
+use crate::error::AppError;
+
+/// Partial unique index keeping a single `is_latest` row per path, see the `add_latest_file_index` migration.
+pub const LATEST_FILE_INDEX: &str = "idx_files_latest_path";
+
+const MAX_ATTEMPTS: u32 = 5;
+const SERIALIZATION_FAILURE: &str = "40001";
+const UNIQUE_VIOLATION: &str = "23505";
+
+/// Serialization failures, and two writers racing for the latest version of a path: running the
+/// transaction again sees the winner's rows and succeeds.
+pub fn is_retryable(err: &DbErr) -> bool {
+    let (DbErr::Exec(RuntimeErr::SqlxError(err)) | DbErr::Query(RuntimeErr::SqlxError(err)) | DbErr::Conn(RuntimeErr::SqlxError(err))) = err else {
+        return false;
+    };
+    let Some(db_err) = err.as_database_error() else {
+        return false;
+    };
+    match db_err.code().as_deref() {
+        Some(SERIALIZATION_FAILURE) => true,
+        Some(UNIQUE_VIOLATION) => db_err.constraint() == Some(LATEST_FILE_INDEX),
+        _ => false,
+    }
+}
+
+/// Runs `attempt`, a whole write transaction from `begin` to `commit`, again when it ends with `AppError::Contention`.
+///
+/// CockroachDB aborts conflicting serializable transactions with SQLSTATE 40001 and expects the client to retry them.
+/// Effects outside of the database must come after the commit, or be undone before a contention error is returned.
+pub async fn retry<T, F, Fut>(mut attempt: F) -> Result<T, AppError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, AppError>>,
+{
+    let mut attempts = 1;
+    loop {
+        match attempt().await {
+            Err(AppError::Contention(msg)) if attempts < MAX_ATTEMPTS => {
+                tracing::warn!("Retrying transaction after attempt {}: {}", attempts, msg);
+                tokio::time::sleep(backoff(attempts)).await;
+                attempts += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Random delay growing with the attempts, so that the transactions that conflicted do not meet again.
+fn backoff(attempts: u32) -> Duration {
+    Duration::from_millis(rand::thread_rng().gen_range(0..10u64 << attempts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn backoff_grows_with_attempts() {
+        for attempts in 1..MAX_ATTEMPTS {
+            assert!(backoff(attempts) < Duration::from_millis(10 << attempts));
+        }
+    }
+
+    #[test]
+    fn other_errors_are_not_retryable() {
+        assert!(!is_retryable(&DbErr::RecordNotFound("files".to_string())));
+        assert!(!is_retryable(&DbErr::Exec(RuntimeErr::Internal("40001".to_string()))));
+    }
+
+    #[tokio::test]
+    async fn retry_runs_again_on_contention_only() {
+        let calls = Cell::new(0);
+        let result = retry(|| {
+            calls.set(calls.get() + 1);
+            async {
+                match calls.get() {
+                    1 | 2 => Err(AppError::Contention("restart transaction".to_string())),
+                    n => Ok(n),
+                }
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 3);
+
+        calls.set(0);
+        let result: Result<(), _> = retry(|| {
+            calls.set(calls.get() + 1);
+            async { Err(AppError::Conflict("exists".to_string())) }
+        })
+        .await;
+        assert!(matches!(result, Err(AppError::Conflict(_))));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn retry_gives_up_after_max_attempts() {
+        let calls = Cell::new(0);
+        let result: Result<(), _> = retry(|| {
+            calls.set(calls.get() + 1);
+            async { Err(AppError::Contention("restart transaction".to_string())) }
+        })
+        .await;
+        assert!(matches!(result, Err(AppError::Contention(_))));
+        assert_eq!(calls.get(), MAX_ATTEMPTS);
+    }
+}
@@ -0,0 +1,280 @@
+//! Reading of the zip, tar and tar.gz archives uploaded for extraction, with limits against archives
+//! that expand far beyond their size.
+
+use std::io::{Cursor, Read};
+
+use bytes::Bytes;
+use flate2::read::MultiGzDecoder;
+
+use crate::error::AppError;
+
+const TAR_BLOCK: usize = 512;
+/// Entries smaller than this are not checked against `Limits::max_ratio`, tiny files compress very well.
+const RATIO_FLOOR: u64 = 1024 * 1024;
+
+pub struct Limits {
+    pub max_entries: usize,
+    /// Total size of the extracted content.
+    pub max_bytes: u64,
+    /// Largest extracted size to compressed size ratio of an entry.
+    pub max_ratio: u64,
+}
+
+/// File of an archive, with its content or the reason it could not be read.
+pub struct Entry {
+    pub name: String,
+    content: Result<Bytes, String>,
+}
+
+impl Entry {
+    pub fn read(&self) -> Result<Bytes, String> {
+        self.content.clone()
+    }
+}
+
+/// Files of a zip, tar or gzip compressed tar archive, told apart by their first bytes. Directories are left out.
+pub fn unpack(body: Bytes, limits: &Limits) -> Result<Vec<Entry>, AppError> {
+    if body.starts_with(b"PK\x03\x04") || body.starts_with(b"PK\x05\x06") {
+        read_zip(body, limits)
+    } else if body.starts_with(&[0x1f, 0x8b]) {
+        let tar = gunzip(&body, limits)?;
+        read_tar(&tar, limits)
+    } else if body.len() >= TAR_BLOCK && tar_checksum_matches(&body[..TAR_BLOCK]) {
+        read_tar(&body, limits)
+    } else {
+        Err(AppError::BadRequest("The body is not a zip, tar or tar.gz archive".to_string()))
+    }
+}
+
+/// Whether the path of an entry can be used under the destination prefix: relative, without `..`.
+/// Returns it without `.` and empty segments, backslashes taken as separators.
+pub fn safe_path(name: &str) -> Result<String, &'static str> {
+    let name = name.replace('\\', "/");
+    if name.starts_with('/') {
+        return Err("Absolute paths are not allowed");
+    }
+    if name.chars().any(char::is_control) {
+        return Err("Control characters are not allowed in paths");
+    }
+
+    let mut segments = Vec::new();
+    for segment in name.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => return Err("Paths cannot go up with '..'"),
+            // C:foo
+            s if segments.is_empty() && s.len() >= 2 && s.as_bytes()[1] == b':' => {
+                return Err("Drive letters are not allowed in paths");
+            }
+            s => segments.push(s),
+        }
+    }
+    if segments.is_empty() {
+        return Err("Empty path");
+    }
+    Ok(segments.join("/"))
+}
+
+fn invalid(format: &str, err: impl std::fmt::Display) -> AppError {
+    AppError::BadRequest(format!("Invalid {} archive: {}", format, err))
+}
+
+fn too_many(limits: &Limits) -> AppError {
+    AppError::TooLarge(format!("Archives can hold at most {} files", limits.max_entries))
+}
+
+fn too_large(limits: &Limits) -> AppError {
+    AppError::TooLarge(format!("Archives can expand to at most {} bytes", limits.max_bytes))
+}
+
+/// Content of `reader`, refused once it goes over `max` bytes.
+fn read_at_most(reader: impl Read, max: u64) -> std::io::Result<Option<Vec<u8>>> {
+    let mut content = Vec::new();
+    reader.take(max.saturating_add(1)).read_to_end(&mut content)?;
+    Ok((content.len() as u64 <= max).then_some(content))
+}
+
+/// Entries as listed by the central directory, the sizes it declares are checked against the limits up front
+/// and enforced while decompressing.
+fn read_zip(body: Bytes, limits: &Limits) -> Result<Vec<Entry>, AppError> {
+    let mut zip = zip::ZipArchive::new(Cursor::new(body)).map_err(|e| invalid("zip", e))?;
+    if zip.len() > limits.max_entries {
+        return Err(too_many(limits));
+    }
+
+    let mut entries = Vec::new();
+    let mut total = 0u64;
+    for index in 0..zip.len() {
+        let name = zip.name_for_index(index).and_then(Result::ok).unwrap_or_default().into_owned();
+        let mut file = match zip.by_index(index) {
+            Ok(file) => file,
+            // encrypted entries and unsupported compression methods
+            Err(err @ zip::result::ZipError::UnsupportedArchive(_)) => {
+                entries.push(Entry { name, content: Err(err.to_string()) });
+                continue;
+            }
+            Err(err) => return Err(invalid("zip", err)),
+        };
+        if file.is_dir() {
+            continue;
+        }
+        let (size, compressed) = (file.size(), file.compressed_size());
+
+        // refused as a whole, a zip bomb is not worth the rest of its content
+        if size > RATIO_FLOOR && size / compressed.max(1) > limits.max_ratio {
+            return Err(AppError::TooLarge(format!(
+                "'{}' expands more than {} times, refusing the archive",
+                name, limits.max_ratio
+            )));
+        }
+        total += size;
+        if total > limits.max_bytes {
+            return Err(too_large(limits));
+        }
+
+        let content = match read_at_most(&mut file, size) {
+            Ok(Some(content)) if content.len() as u64 == size => Ok(content.into()),
+            Ok(_) => Err("Content does not match the size in the archive".to_string()),
+            Err(err) => Err(format!("Corrupt content: {}", err)),
+        };
+        entries.push(Entry { name, content });
+    }
+    Ok(entries)
+}
+
+/// Content of the gzip members of `body`, decompressed up to the extraction limit.
+fn gunzip(body: &[u8], limits: &Limits) -> Result<Vec<u8>, AppError> {
+    let out = read_at_most(MultiGzDecoder::new(body), limits.max_bytes)
+        .map_err(|e| invalid("gzip", e))?
+        .ok_or_else(|| too_large(limits))?;
+
+    if out.len() as u64 > RATIO_FLOOR && out.len() as u64 / body.len() as u64 > limits.max_ratio {
+        return Err(too_large(limits));
+    }
+    Ok(out)
+}
+
+fn tar_checksum_matches(block: &[u8]) -> bool {
+    let mut header = tar::Header::from_byte_slice(block).clone();
+    let Ok(stored) = header.cksum() else {
+        return false;
+    };
+    header.set_cksum();
+    header.cksum().is_ok_and(|sum| sum == stored)
+}
+
+fn read_tar(body: &[u8], limits: &Limits) -> Result<Vec<Entry>, AppError> {
+    let mut archive = tar::Archive::new(body);
+    let mut entries = Vec::new();
+    let mut total = 0u64;
+
+    for entry in archive.entries().map_err(|e| invalid("tar", e))? {
+        let mut entry = entry.map_err(|e| invalid("tar", e))?;
+        let name = String::from_utf8_lossy(&entry.path_bytes()).into_owned();
+
+        let content = match entry.header().entry_type() {
+            tar::EntryType::Regular | tar::EntryType::Continuous => {
+                let remaining = limits.max_bytes - total;
+                match read_at_most(&mut entry, remaining).map_err(|e| invalid("tar", e))? {
+                    Some(content) => {
+                        total += content.len() as u64;
+                        Ok(content.into())
+                    }
+                    None => return Err(too_large(limits)),
+                }
+            }
+            // global PAX records
+            tar::EntryType::Directory | tar::EntryType::XGlobalHeader => continue,
+            _ => Err("Links and special files are not supported".to_string()),
+        };
+        entries.push(Entry { name, content });
+        if entries.len() > limits.max_entries {
+            return Err(too_many(limits));
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::{self, GzipWriter};
+    use std::io::Write;
+
+    const LIMITS: Limits = Limits { max_entries: 10, max_bytes: 1 << 20, max_ratio: 100 };
+
+    fn tar(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut out = Vec::new();
+        for (name, data) in files {
+            out.extend(archive::tar_header(name, data.len() as u64, 0).unwrap());
+            out.extend_from_slice(data);
+            out.resize(out.len() + archive::tar_padding(data.len() as u64), 0);
+        }
+        out.extend(archive::tar_end());
+        out
+    }
+
+    fn read_all(entries: &[Entry]) -> Vec<(String, Result<Bytes, String>)> {
+        entries.iter().map(|e| (e.name.clone(), e.read())).collect()
+    }
+
+    #[test]
+    fn safe_path_refuses_to_leave_the_prefix() {
+        assert_eq!(safe_path("docs/./a.txt"), Ok("docs/a.txt".to_string()));
+        assert_eq!(safe_path("docs\\b.txt"), Ok("docs/b.txt".to_string()));
+        for name in ["../x", "a/../../x", "/etc/passwd", "C:/x", "c:x", "", "./", "a\nb"] {
+            assert!(safe_path(name).is_err(), "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn unpack_reads_tar_and_tar_gz() {
+        let long = format!("{}/deep.txt", "d".repeat(120));
+        let body = tar(&[("a.txt", b"hello"), (&long, b"world")]);
+        let entries = unpack(body.clone().into(), &LIMITS).unwrap();
+        assert_eq!(
+            read_all(&entries),
+            vec![("a.txt".to_string(), Ok(Bytes::from("hello"))), (long.clone(), Ok(Bytes::from("world")))]
+        );
+
+        let mut gzip = GzipWriter::default();
+        let mut compressed = gzip.write(&body).unwrap();
+        compressed.extend(gzip.finish().unwrap());
+        let entries = unpack(compressed.into(), &LIMITS).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].name, long);
+    }
+
+    #[test]
+    fn unpack_reads_zip_and_checks_crc() {
+        // stored, so that the content can be altered in place
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+        zip.start_file("docs/a.txt", options).unwrap();
+        zip.write_all(b"hello").unwrap();
+        let mut body = zip.finish().unwrap().into_inner();
+
+        let entries = unpack(Bytes::from(body.clone()), &LIMITS).unwrap();
+        assert_eq!(read_all(&entries), vec![("docs/a.txt".to_string(), Ok(Bytes::from("hello")))]);
+
+        let at = body.windows(5).position(|w| w == b"hello").unwrap();
+        body[at] = b'j';
+        let entries = unpack(body.into(), &LIMITS).unwrap();
+        assert!(entries[0].read().is_err());
+    }
+
+    #[test]
+    fn unpack_enforces_limits() {
+        let files: Vec<(String, &[u8])> = (0..11).map(|i| (format!("{}.txt", i), &b"x"[..])).collect();
+        let files: Vec<(&str, &[u8])> = files.iter().map(|(n, d)| (n.as_str(), *d)).collect();
+        assert!(matches!(unpack(tar(&files).into(), &LIMITS), Err(AppError::TooLarge(_))));
+
+        // 29 bytes once decompressed
+        let mut gzip = vec![0x1f, 0x8b, 8, 0, 0, 0, 0, 0, 0, 0xff];
+        gzip.extend(hex::decode("cb48cdc9c957c8c020cbf38b725200").unwrap());
+        let small = Limits { max_bytes: 20, ..LIMITS };
+        assert!(matches!(unpack(gzip.into(), &small), Err(AppError::TooLarge(_))));
+
+        assert!(matches!(unpack(Bytes::from_static(b"plain text"), &LIMITS), Err(AppError::BadRequest(_))));
+    }
+}
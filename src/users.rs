@@ -0,0 +1,54 @@
+use sea_orm::{sea_query::OnConflict, ConnectionTrait, DbErr, EntityTrait};
+use uuid::Uuid;
+
+use crate::entities::user;
+use crate::error::AppError;
+
+/// Creates the profile of `user_id` unless it exists, and tells whether it did.
+///
+/// A single `INSERT .. ON CONFLICT DO NOTHING`, so that concurrent first requests of a user do not race.
+pub async fn provision<C: ConnectionTrait>(db: &C, user_id: Uuid, quota_bytes: Option<i64>) -> Result<bool, DbErr> {
+    let inserted = user::Entity::insert(user::ActiveModel::new(user_id, 0, quota_bytes))
+        .on_conflict(OnConflict::column(user::Column::UserId).do_nothing().to_owned())
+        .exec_without_returning(db)
+        .await?;
+    Ok(inserted > 0)
+}
+
+pub fn require_enabled(user: &user::Model) -> Result<(), AppError> {
+    if user.disabled_at.is_some() {
+        return Err(AppError::Forbidden("User account is disabled".to_string()));
+    }
+    Ok(())
+}
+
+/// Refuses disabled users, the ones without a profile yet are let through.
+pub async fn check_enabled<C: ConnectionTrait>(db: &C, user_id: Uuid) -> Result<(), AppError> {
+    match user::Entity::find_by_id(user_id).one(db).await? {
+        Some(user) => require_enabled(&user),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn require_enabled_refuses_disabled_users() {
+        let mut user = user::Model {
+            user_id: Uuid::now_v7(),
+            total_space_used: 0,
+            quota_bytes: None,
+            updated_at: chrono::Utc::now().into(),
+            last_auto_sync_at: None,
+            change_seq: 0,
+            created_at: chrono::Utc::now().into(),
+            disabled_at: None,
+        };
+        assert!(require_enabled(&user).is_ok());
+
+        user.disabled_at = Some(chrono::Utc::now().into());
+        assert!(matches!(require_enabled(&user), Err(AppError::Forbidden(_))));
+    }
+}